@@ -0,0 +1,122 @@
+//! The sparse connection storage against the layered matrix it replaced.
+//!
+//! Run with `cargo bench --bench connections`. Prints the best of a few runs per operation and drawing size.
+
+#[path = "../tests/common/layered.rs"]
+mod layered;
+
+use std::{
+	hint::black_box,
+	time::{Duration, Instant},
+};
+
+use arcviz::{
+	common::XorShift,
+	model::{Connection, ConnectionOrientation, Connections, VertexKey},
+};
+use layered::LayeredConnections;
+
+/// The number of vertices of the drawings (the layered matrix has one entry per vertex pair).
+const SIZES: [usize; 3] = [100, 500, 2000];
+/// The number of connections per vertex.
+const DEGREE: usize = 3;
+/// How often each operation is repeated (the best run is reported).
+const RUNS: usize = 5;
+
+fn best_of<T>(mut run: impl FnMut() -> T) -> Duration {
+	(0..RUNS)
+		.map(|_| {
+			let start = Instant::now();
+			black_box(run());
+			start.elapsed()
+		})
+		.min()
+		.unwrap_or_default()
+}
+
+fn report(operation: &str, size: usize, layered: Duration, sparse: Duration) {
+	println!(
+		"{:<10} {:>5} vertices: layered {:>12.3?}  sparse {:>12.3?}  ({:.1}x)",
+		operation,
+		size,
+		layered,
+		sparse,
+		layered.as_secs_f64() / sparse.as_secs_f64().max(f64::EPSILON)
+	);
+}
+
+fn main() {
+	for size in SIZES {
+		let mut random = XorShift::new(size as u64);
+		let edges = (0..size * DEGREE).map(|_| (random.below(size), random.below(size))).collect::<Vec<_>>();
+		let lookups = (0..10_000).map(|_| (random.below(size), random.below(size))).collect::<Vec<_>>();
+		// descending, so the positions of the other removed vertices don't shift in the layered matrix
+		let mut removed = (0..size / 10).map(|_| random.below(size)).collect::<Vec<_>>();
+		removed.sort_unstable_by(|a, b| b.cmp(a));
+		removed.dedup();
+		let connection = Connection::new(ConnectionOrientation::InnerRight, 0);
+
+		let build_layered = || {
+			let mut connections = LayeredConnections::new(0);
+			for vertex in 0..size {
+				connections.resize(vertex + 1);
+			}
+			for &(start, end) in &edges {
+				*connections.entry_mut(start, end).unwrap() = Some(connection.clone());
+			}
+			connections
+		};
+		let build_sparse = || {
+			let mut connections = Connections::new();
+			for vertex in 0..size {
+				connections.add_vertex(vertex as VertexKey);
+			}
+			for &(start, end) in &edges {
+				connections.set(start as VertexKey, end as VertexKey, Some(connection.clone())).unwrap();
+			}
+			connections
+		};
+		report("build", size, best_of(build_layered), best_of(build_sparse));
+
+		let (layered, sparse) = (build_layered(), build_sparse());
+		let iterate_layered = || {
+			let mut count = 0;
+			layered.foreach(|_, _, _| count += 1);
+			count
+		};
+		let iterate_sparse = || {
+			let mut count = 0;
+			sparse.foreach(|_, _, _| count += 1);
+			count
+		};
+		assert_eq!(iterate_layered(), iterate_sparse());
+		report("iterate", size, best_of(iterate_layered), best_of(iterate_sparse));
+
+		let lookup_layered = || lookups.iter().filter(|&&(a, b)| layered.get(a, b).is_some()).count();
+		let lookup_sparse =
+			|| lookups.iter().filter(|&&(a, b)| sparse.get(a as VertexKey, b as VertexKey).is_some()).count();
+		assert_eq!(lookup_layered(), lookup_sparse());
+		report("lookup", size, best_of(lookup_layered), best_of(lookup_sparse));
+
+		let remove_layered = || {
+			let mut connections = layered.clone();
+			let start = Instant::now();
+			for &vertex in &removed {
+				connections.shrink_by_vertex(vertex).unwrap();
+			}
+			start.elapsed()
+		};
+		let remove_sparse = || {
+			let mut connections = sparse.clone();
+			let start = Instant::now();
+			for &vertex in &removed {
+				connections.remove_vertex(vertex as VertexKey).unwrap();
+			}
+			start.elapsed()
+		};
+		// without the clones
+		let remove_layered = (0..RUNS).map(|_| remove_layered()).min().unwrap_or_default();
+		let remove_sparse = (0..RUNS).map(|_| remove_sparse()).min().unwrap_or_default();
+		report("remove", size, remove_layered, remove_sparse);
+	}
+}
@@ -0,0 +1,251 @@
+//! Headless analysis of drawings.
+//!
+//! Usage: `arcviz <command> [--lenient] [--epsilon <value>] <files..>`
+//!
+//! Every file is loaded in the format given by its extension (see [FileFormat::from_path]).
+//! Files with several graphs (graph6) are handled graph by graph (named `file#1`, `file#2`, ..).
+//! Files that can't be loaded are reported on stderr and skipped.
+//! Text files are read in [TextMode::Strict] unless `--lenient` is given, then skipped lines are reported on
+//! stderr.
+//! `--epsilon` decides borderline intersections with the given [Tolerance] instead of the one of each file.
+//! Unknown options are rejected.
+//!
+//! Exports are written next to the input, an export that would overwrite its input (like `dot` of a DOT file)
+//! fails instead.
+
+use std::{env, fs, path::Path, process::ExitCode};
+
+use arcviz::{
+	io::{
+		dto::{DataDto, TextMode},
+		graph::{Graph, GraphFormat},
+		ipe::IpeExporter,
+		tikz::TikzExporter,
+		FileFormat,
+	},
+	model::{ConflictStatus, Data, Settings, Tolerance},
+};
+
+const USAGE: &str = "usage: arcviz <command> [--lenient] [--epsilon <value>] <files..>
+
+options:
+	--lenient    skip invalid lines of text files (and report them) instead of failing the file
+	--epsilon    the tolerance for borderline intersections, in [0, 1) (overrides the one of the files)
+
+commands:
+	stats        one tab separated line of statistics per file
+	conflicts    all intersections (crossing or touching) and missing intersections with their status
+	unreachable  all connections whose arc can't be constructed
+	ipe          export every file to Ipe (next to the input, with extension .ipe)
+	tikz         export every file to TikZ (next to the input, with extension .tex)
+	graph6       print the graph of every file in the graph6 format
+	edges        export the graph of every file as edge list (next to the input, with extension .edges)
+	dot          export every file to DOT with geometry attributes (next to the input, with extension .dot)
+	graphml      export every file to GraphML with geometry attributes (next to the input, with extension .graphml)
+
+exports never overwrite their input file (that file fails instead)";
+
+#[derive(Copy, Clone)]
+enum Command {
+	Stats,
+	Conflicts,
+	Unreachable,
+	Ipe,
+	Tikz,
+	Graph(GraphFormat),
+}
+impl Command {
+	fn parse(name: &str) -> Option<Self> {
+		match name {
+			"stats" => Some(Command::Stats),
+			"conflicts" => Some(Command::Conflicts),
+			"unreachable" => Some(Command::Unreachable),
+			"ipe" => Some(Command::Ipe),
+			"tikz" => Some(Command::Tikz),
+			"graph6" => Some(Command::Graph(GraphFormat::Graph6)),
+			"edges" => Some(Command::Graph(GraphFormat::EdgeList)),
+			"dot" => Some(Command::Graph(GraphFormat::Dot)),
+			"graphml" => Some(Command::Graph(GraphFormat::GraphMl)),
+			_ => None,
+		}
+	}
+}
+
+fn main() -> ExitCode {
+	let args = env::args().skip(1).collect::<Vec<_>>();
+	let Some(command) = args.first().and_then(|a| Command::parse(a)) else {
+		eprintln!("{}", USAGE);
+		return ExitCode::FAILURE;
+	};
+	let (mut lenient, mut tolerance, mut files) = (false, None, Vec::new());
+	let mut options = args[1..].iter();
+	while let Some(arg) = options.next() {
+		match arg.as_str() {
+			"--lenient" => lenient = true,
+			"--epsilon" => match options.next().and_then(|v| v.parse().ok()).and_then(|e| Tolerance::new(e).ok()) {
+				Some(value) => tolerance = Some(value),
+				None => {
+					eprintln!("--epsilon needs a number in [0, 1)");
+					return ExitCode::FAILURE;
+				},
+			},
+			option if option.starts_with('-') => {
+				eprintln!("unknown option {}\n\n{}", option, USAGE);
+				return ExitCode::FAILURE;
+			},
+			_ => files.push(arg),
+		}
+	}
+	let mode = if lenient { TextMode::Lenient } else { TextMode::Strict };
+	if files.is_empty() {
+		eprintln!("{}", USAGE);
+		return ExitCode::FAILURE;
+	}
+
+	if let Command::Stats = command {
+		println!("file\tvertices\tconnections\tunreachable\trequired\ttolerated\tforbidden\tmissing");
+	}
+	let mut failed = false;
+	for file in files {
+		let mut drawings = match load(file, mode) {
+			Ok(drawings) => drawings,
+			Err(message) => {
+				eprintln!("{}: {}", file, message);
+				failed = true;
+				continue;
+			},
+		};
+		if let Some(tolerance) = tolerance {
+			drawings.iter_mut().for_each(|data| data.tolerance = tolerance);
+		}
+		let count = drawings.len();
+		for (i, data) in drawings.iter().enumerate() {
+			let path = match count {
+				1 => file.to_owned(),
+				_ => format!("{}#{}", file, i + 1),
+			};
+			let path = path.as_str();
+			let result = match command {
+				Command::Stats => Ok(stats(path, data)),
+				Command::Conflicts => Ok(conflicts(path, data)),
+				Command::Unreachable => Ok(unreachable(path, data)),
+				Command::Ipe => export_ipe(file, path, data),
+				Command::Tikz => export_tikz(file, path, data),
+				Command::Graph(GraphFormat::Graph6) => Ok(print_graph6(data)),
+				Command::Graph(format) => export_graph(file, path, data, format),
+			};
+			if let Err(message) = result {
+				eprintln!("{}: {}", path, message);
+				failed = true;
+			}
+		}
+	}
+
+	match failed {
+		true => ExitCode::FAILURE,
+		false => ExitCode::SUCCESS,
+	}
+}
+
+fn load(path: &str, mode: TextMode) -> Result<Vec<Data>, String> {
+	let bytes = fs::read(path).map_err(|e| format!("can't read file ({})", e))?;
+	let (drawings, skipped) = FileFormat::from_path(path).read_all(&bytes, mode).map_err(|e| e.to_string())?;
+	for problem in skipped {
+		eprintln!("{}: skipped: {}", path, problem);
+	}
+	Ok(drawings)
+}
+
+/// The file next to the input file with the given extension (the drawing `file.g6#2` becomes `file-2`).
+///
+/// Fails if that is the input file itself.
+fn target(file: &str, path: &str, extension: &str) -> Result<String, String> {
+	let stem = Path::new(file).with_extension("");
+	let target = format!("{}{}.{}", stem.to_string_lossy(), path[file.len()..].replace('#', "-"), extension);
+	match Path::new(&target) == Path::new(file) {
+		true => Err(format!("can't export to {} (that would overwrite the input)", target)),
+		false => Ok(target),
+	}
+}
+
+fn stats(path: &str, data: &Data) {
+	let conflicts = data.conflicts();
+	let count = |status: ConflictStatus| conflicts.iter().filter(|c| c.status == status).count();
+	println!(
+		"{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+		path,
+		data.vertices.len(),
+		data.edges().count(),
+		data.unreachable().count(),
+		count(ConflictStatus::Satisfied),
+		count(ConflictStatus::Tolerated),
+		count(ConflictStatus::Violated),
+		data.missing_intersections(&conflicts).len(),
+	);
+}
+
+fn conflicts(path: &str, data: &Data) {
+	let name = |id| data.vertex_name(id);
+	let conflicts = data.conflicts();
+	for conflict in &conflicts {
+		let ((a, b), (c, d)) = (conflict.first, conflict.second);
+		println!(
+			"{}: ({}) ({}) - ({}) ({}) {} at [{:.2}; {:.2}] {}{}",
+			path,
+			name(a),
+			name(b),
+			name(c),
+			name(d),
+			conflict.contact.name(),
+			conflict.position.x,
+			conflict.position.y,
+			conflict.status.name(),
+			if conflict.certain { "" } else { " (uncertain)" },
+		);
+	}
+	for ((a, b), (c, d)) in data.missing_intersections(&conflicts) {
+		println!("{}: ({}) ({}) - ({}) ({}) missing", path, name(a), name(b), name(c), name(d));
+	}
+}
+
+fn unreachable(path: &str, data: &Data) {
+	for (start, end) in data.unreachable() {
+		println!("{}: ({}) ({}) unreachable", path, data.vertex_name(start), data.vertex_name(end));
+	}
+}
+
+fn export_ipe(file: &str, path: &str, data: &Data) -> Result<(), String> {
+	let target = target(file, path, "ipe")?;
+	let mut exporter = IpeExporter::new(&data.classes);
+	data.render_to(&mut exporter, &Settings::default());
+	for color in exporter.unknown_colors() {
+		eprintln!("{}: unknown color \"{}\" (exported as black)", path, color);
+	}
+	fs::write(&target, exporter.to_string()).map_err(|e| format!("can't write {} ({})", target, e))?;
+	println!("{}", target);
+	Ok(())
+}
+
+fn export_tikz(file: &str, path: &str, data: &Data) -> Result<(), String> {
+	let target = target(file, path, "tex")?;
+	let mut exporter = TikzExporter::new(data, true, true);
+	data.render_to(&mut exporter, &Settings::default());
+	for color in exporter.unknown_colors() {
+		eprintln!("{}: unknown color \"{}\" (exported as black)", path, color);
+	}
+	fs::write(&target, exporter.to_string()).map_err(|e| format!("can't write {} ({})", target, e))?;
+	println!("{}", target);
+	Ok(())
+}
+
+fn print_graph6(data: &Data) {
+	print!("{}", GraphFormat::Graph6.write(&Graph::from(&DataDto::from(data))));
+}
+
+fn export_graph(file: &str, path: &str, data: &Data, format: GraphFormat) -> Result<(), String> {
+	let target = target(file, path, format.extension())?;
+	let graph = Graph::from(&DataDto::from(data));
+	fs::write(&target, format.write(&graph)).map_err(|e| format!("can't write {} ({})", target, e))?;
+	println!("{}", target);
+	Ok(())
+}
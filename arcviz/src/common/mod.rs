@@ -8,3 +8,9 @@ pub use vector::*;
 
 mod bounds;
 pub use bounds::*;
+
+mod random;
+pub use random::*;
+
+mod precise;
+pub use precise::*;
@@ -0,0 +1,113 @@
+use std::{
+	cmp::Ordering,
+	ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use super::Number;
+
+/// A number with about twice the precision of [Number] (double-double arithmetic).
+///
+/// Only meant for the few predicates that can't be decided with [Number]s (see [crate::model::Tolerance]),
+/// the sum or product of 2 [Number]s is exact (as long as it doesn't overflow).
+#[derive(Copy, Clone, PartialEq)]
+pub struct Precise {
+	high: Number,
+	low: Number,
+}
+
+impl Precise {
+	/// A bound on the relative rounding error of each operation (generous, it is about `2^-104`).
+	pub const UNIT_ROUNDOFF: Number = 256.0 * Number::EPSILON * Number::EPSILON;
+
+	/// The nearest [Number].
+	pub fn value(&self) -> Number {
+		self.high + self.low
+	}
+	/// The sign of the number.
+	pub fn ordering(&self) -> Ordering {
+		match (self.high + self.low).partial_cmp(&0.0) {
+			Some(Ordering::Equal) | None => self.high.partial_cmp(&0.0).unwrap_or(Ordering::Equal),
+			Some(ordering) => ordering,
+		}
+	}
+	pub fn abs(&self) -> Number {
+		(self.high + self.low).abs()
+	}
+	pub fn is_zero(&self) -> bool {
+		self.high == 0.0 && self.low == 0.0
+	}
+	/// The square root (0 for numbers that are not positive).
+	pub fn sqrt(self) -> Self {
+		if self.ordering() != Ordering::Greater {
+			return Self::from(0.0);
+		}
+		// one Newton step doubles the precision of the root
+		let root = self.high.sqrt();
+		let correction = (self - Self::two_product(root, root)).value() / (2.0 * root);
+		Self::two_sum(root, correction)
+	}
+
+	/// The sum with its rounding error (exact).
+	fn two_sum(a: Number, b: Number) -> Self {
+		let high = a + b;
+		let b_virtual = high - a;
+		let low = (a - (high - b_virtual)) + (b - b_virtual);
+		Self { high, low }
+	}
+	/// Requires `|a| >= |b|`.
+	fn quick_two_sum(a: Number, b: Number) -> Self {
+		let high = a + b;
+		Self { high, low: b - (high - a) }
+	}
+	/// The product with its rounding error (exact).
+	fn two_product(a: Number, b: Number) -> Self {
+		let high = a * b;
+		Self { high, low: a.mul_add(b, -high) }
+	}
+}
+
+impl From<Number> for Precise {
+	fn from(value: Number) -> Self {
+		Self { high: value, low: 0.0 }
+	}
+}
+
+impl Neg for Precise {
+	type Output = Self;
+
+	fn neg(self) -> Self {
+		Self { high: -self.high, low: -self.low }
+	}
+}
+impl Add for Precise {
+	type Output = Self;
+
+	fn add(self, other: Self) -> Self {
+		let sum = Self::two_sum(self.high, other.high);
+		Self::quick_two_sum(sum.high, sum.low + self.low + other.low)
+	}
+}
+impl Sub for Precise {
+	type Output = Self;
+
+	fn sub(self, other: Self) -> Self {
+		self + -other
+	}
+}
+impl Mul for Precise {
+	type Output = Self;
+
+	fn mul(self, other: Self) -> Self {
+		let product = Self::two_product(self.high, other.high);
+		Self::quick_two_sum(product.high, product.low + self.high * other.low + self.low * other.high)
+	}
+}
+impl Div for Precise {
+	type Output = Self;
+
+	fn div(self, other: Self) -> Self {
+		let quotient = self.high / other.high;
+		let correction = (self - other * Self::from(quotient)).value() / other.high;
+		Self::two_sum(quotient, correction)
+	}
+}
@@ -0,0 +1,29 @@
+use super::Number;
+
+/// Minimal pseudo random number generator (xorshift64*), so results don't depend on the platform.
+pub struct XorShift {
+	state: u64,
+}
+impl XorShift {
+	pub fn new(seed: u64) -> Self {
+		Self { state: seed.max(1) }
+	}
+	pub fn next(&mut self) -> u64 {
+		self.state ^= self.state >> 12;
+		self.state ^= self.state << 25;
+		self.state ^= self.state >> 27;
+		self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+	}
+	/// A random number in [0, 1).
+	pub fn unit(&mut self) -> Number {
+		(self.next() >> 11) as Number / (1u64 << 53) as Number
+	}
+	/// A random number in [-1, 1).
+	pub fn signed_unit(&mut self) -> Number {
+		2.0 * self.unit() - 1.0
+	}
+	/// A random number in [0, bound).
+	pub fn below(&mut self, bound: usize) -> usize {
+		(self.unit() * bound as Number) as usize % bound.max(1)
+	}
+}
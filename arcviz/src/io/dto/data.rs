@@ -0,0 +1,448 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	common::{Number, Vector},
+	model::{
+		Class, Classes, Connection, ConnectionOrientation, Data, Edge, IntersectionConstraint, SizeId, Tolerance,
+		Vertex, VertexId, VertexKey,
+	},
+};
+
+/// A [Vertex] as stored on disk.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct VertexDto {
+	pub position: Vector,
+	pub label: Option<String>,
+	/// Not stored in the text format.
+	#[serde(default)]
+	pub pinned: bool,
+	/// The persistent key (a new one is assigned if it is missing or already taken).
+	#[serde(default)]
+	pub id: Option<VertexKey>,
+}
+impl From<&Vertex> for VertexDto {
+	/// Without key (see [DataDto::from] for vertices of a drawing).
+	fn from(vertex: &Vertex) -> Self {
+		Self { position: vertex.position, label: vertex.label.clone(), pinned: vertex.pinned, id: None }
+	}
+}
+impl From<VertexDto> for Vertex {
+	fn from(dto: VertexDto) -> Self {
+		Self { position: dto.position, label: dto.label, pinned: dto.pinned }
+	}
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct VerticesDto {
+	pub items: Vec<VertexDto>,
+}
+
+/// A [ConnectionOrientation] as stored on disk.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub enum OrientationDto {
+	InnerRight,
+	InnerLeft,
+	OuterRight,
+	OuterLeft,
+}
+impl From<ConnectionOrientation> for OrientationDto {
+	fn from(orientation: ConnectionOrientation) -> Self {
+		match orientation {
+			ConnectionOrientation::InnerRight => OrientationDto::InnerRight,
+			ConnectionOrientation::InnerLeft => OrientationDto::InnerLeft,
+			ConnectionOrientation::OuterRight => OrientationDto::OuterRight,
+			ConnectionOrientation::OuterLeft => OrientationDto::OuterLeft,
+		}
+	}
+}
+impl From<OrientationDto> for ConnectionOrientation {
+	fn from(dto: OrientationDto) -> Self {
+		match dto {
+			OrientationDto::InnerRight => ConnectionOrientation::InnerRight,
+			OrientationDto::InnerLeft => ConnectionOrientation::InnerLeft,
+			OrientationDto::OuterRight => ConnectionOrientation::OuterRight,
+			OrientationDto::OuterLeft => ConnectionOrientation::OuterLeft,
+		}
+	}
+}
+
+/// A [Connection] as stored on disk.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConnectionDto {
+	pub orientation: OrientationDto,
+	pub size: SizeId,
+}
+impl From<&Connection> for ConnectionDto {
+	fn from(connection: &Connection) -> Self {
+		Self { orientation: connection.orientation.into(), size: connection.size }
+	}
+}
+
+/// A [Class] as stored on disk.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClassDto {
+	pub size: Number,
+	#[serde(default = "default_color")]
+	pub color: String,
+}
+/// The color of classes stored without one.
+pub fn default_color() -> String {
+	"white".to_string()
+}
+impl From<&Class> for ClassDto {
+	fn from(class: &Class) -> Self {
+		Self { size: class.size(), color: class.color().to_owned() }
+	}
+}
+impl From<ClassDto> for Class {
+	fn from(dto: ClassDto) -> Self {
+		Class::new(dto.size, dto.color)
+	}
+}
+
+/// [Classes] as stored on disk.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClassesDto {
+	pub items: Vec<ClassDto>,
+	/// The class used for sizes without entry in the items.
+	pub default: ClassDto,
+}
+impl From<&Classes> for ClassesDto {
+	fn from(classes: &Classes) -> Self {
+		Self { items: classes.iter().map(ClassDto::from).collect(), default: classes.default_class().into() }
+	}
+}
+impl From<ClassesDto> for Classes {
+	fn from(dto: ClassesDto) -> Self {
+		Classes::new(dto.items.into_iter().map(Class::from).collect(), dto.default.into())
+	}
+}
+
+/// An [IntersectionConstraint] as stored on disk.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub enum ConstraintDto {
+	Necessary,
+	Forbidden,
+	Irrelevant,
+}
+impl From<IntersectionConstraint> for ConstraintDto {
+	fn from(constraint: IntersectionConstraint) -> Self {
+		match constraint {
+			IntersectionConstraint::Necessary => ConstraintDto::Necessary,
+			IntersectionConstraint::Forbidden => ConstraintDto::Forbidden,
+			IntersectionConstraint::Irrelevant => ConstraintDto::Irrelevant,
+		}
+	}
+}
+impl From<ConstraintDto> for IntersectionConstraint {
+	fn from(dto: ConstraintDto) -> Self {
+		match dto {
+			ConstraintDto::Necessary => IntersectionConstraint::Necessary,
+			ConstraintDto::Forbidden => IntersectionConstraint::Forbidden,
+			ConstraintDto::Irrelevant => IntersectionConstraint::Irrelevant,
+		}
+	}
+}
+
+/// A drawing as stored on disk.
+///
+/// The fields mirror the JSON layout (which is why the classes are called sizes).
+/// Entries that don't fit the drawing (connections between unknown vertices, constraints between unknown edges)
+/// are dropped when converting to [Data].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DataDto {
+	pub vertices: VerticesDto,
+	pub connections: Vec<(VertexId, VertexId, ConnectionDto)>,
+	pub sizes: ClassesDto,
+	#[serde(default)]
+	pub constraints: Vec<(Edge, Edge, ConstraintDto)>,
+	/// The epsilon of the [Tolerance], only stored if it isn't the default one (not stored in the text format).
+	///
+	/// Invalid values (see [Tolerance::new]) are replaced by the default.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub epsilon: Option<Number>,
+}
+impl DataDto {
+	/// An empty drawing with the given classes.
+	pub fn new(sizes: ClassesDto) -> Self {
+		Self {
+			vertices: VerticesDto::default(),
+			connections: Vec::new(),
+			sizes,
+			constraints: Vec::new(),
+			epsilon: None,
+		}
+	}
+}
+impl From<&Data> for DataDto {
+	/// Connections are normalized: the lower vertex index comes first (with the orientation adjusted) and they are sorted.
+	/// Constraints are sorted as well.
+	fn from(data: &Data) -> Self {
+		let mut connections = data
+			.edges()
+			.map(|(a, b, connection)| match a <= b {
+				true => (a, b, ConnectionDto::from(connection)),
+				false => {
+					let orientation = connection.orientation.flipped().into();
+					(b, a, ConnectionDto { orientation, size: connection.size })
+				},
+			})
+			.collect::<Vec<_>>();
+		connections.sort_by_key(|&(a, b, _)| (a, b));
+		let mut constraints =
+			data.constraints().map(|(a, b, constraint)| (a, b, constraint.into())).collect::<Vec<_>>();
+		constraints.sort_by_key(|&(a, b, _)| (a, b));
+		let vertices = data.vertices.iter().enumerate();
+		DataDto {
+			vertices: VerticesDto {
+				items: vertices.map(|(i, v)| VertexDto { id: data.vertices.key(i), ..v.into() }).collect(),
+			},
+			connections,
+			sizes: (&data.classes).into(),
+			constraints,
+			epsilon: (data.tolerance != Tolerance::default()).then_some(data.tolerance.epsilon),
+		}
+	}
+}
+impl From<DataDto> for Data {
+	fn from(dto: DataDto) -> Self {
+		let mut data = Data::new(dto.sizes.into());
+		data.tolerance = dto.epsilon.and_then(|epsilon| Tolerance::new(epsilon).ok()).unwrap_or_default();
+		// vertices without key get new ones that don't collide with the keys of later vertices
+		if let Some(last) = dto.vertices.items.iter().filter_map(|v| v.id).max() {
+			data.vertices.reserve_keys(last.saturating_add(1));
+		}
+		for vertex in dto.vertices.items {
+			let key = vertex.id;
+			data.add_vertex_with_key(vertex.into(), key);
+		}
+		for (a, b, connection) in dto.connections {
+			let _ = data.add_connection(a, b, connection.orientation.into(), connection.size);
+		}
+		for (a, b, constraint) in dto.constraints {
+			let _ = data.set_constraint(a, b, constraint.into()); // base constraints are ignored
+		}
+		data
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::iter::once;
+
+	use super::*;
+	use crate::{
+		common::XorShift,
+		io::{
+			dto::{Document, Metadata, TextMode},
+			graph::{Graph, GraphFormat, GraphImporter},
+			ipe::{IpeExporter, IpeImporter},
+		},
+		model::{edge, Settings},
+	};
+
+	const ORIENTATIONS: [ConnectionOrientation; 4] = [
+		ConnectionOrientation::InnerRight,
+		ConnectionOrientation::InnerLeft,
+		ConnectionOrientation::OuterRight,
+		ConnectionOrientation::OuterLeft,
+	];
+	const CONSTRAINTS: [IntersectionConstraint; 3] =
+		[IntersectionConstraint::Necessary, IntersectionConstraint::Forbidden, IntersectionConstraint::Irrelevant];
+
+	/// A random drawing with every orientation, constraints, unlabeled vertices and non-positional ids.
+	///
+	/// The vertices are at least 30 apart and all connections are reachable with every class, so the drawing
+	/// survives the geometric Ipe import. Coordinates are multiples of 1/64, so they are exact in every format.
+	fn drawing(seed: u64) -> DataDto {
+		let mut random = XorShift::new(seed);
+		let mut data = Data::new(Classes::default());
+		let count = 6 + random.below(3);
+		for i in 0..=count {
+			let x = 40.0 * (i % 4) as Number + random.below(640) as Number / 64.0;
+			let y = 40.0 * (i / 4) as Number + random.below(640) as Number / 64.0;
+			let label = match (i % 3, random.below(2)) {
+				(0, _) => None,
+				(_, 0) => Some(format!("label {}", i)),
+				_ => Some(format!("x_{}", i)),
+			};
+			let vertex = Vertex { position: Vector::new(x, y), label, pinned: random.below(2) == 0 };
+			data.add_vertex_with_key(vertex, Some(10 * i as VertexKey + 7));
+		}
+		// removing the first vertex moves all others, so neither their ids nor their keys are positional
+		data.remove_vertex(0).unwrap();
+
+		for i in 0..count - 1 {
+			let size = random.below(Classes::default().len());
+			data.add_connection(i, i + 1, ORIENTATIONS[i % ORIENTATIONS.len()], size).unwrap();
+		}
+		for _ in 0..count {
+			let (a, b) = (random.below(count), random.below(count));
+			if a != b && !data.has_connection(a, b) {
+				data.add_connection(a, b, ORIENTATIONS[random.below(4)], random.below(4)).unwrap();
+			}
+		}
+		data.set_constraint(edge(0, 1), edge(2, 3), CONSTRAINTS[random.below(3)]).unwrap();
+		data.set_constraint(edge(1, 2), edge(4, 5), CONSTRAINTS[random.below(3)]).unwrap();
+		DataDto::from(&data)
+	}
+
+	/// The label, pinned state and id (the position if it has none) of every vertex.
+	fn vertices(dto: &DataDto) -> Vec<(Option<String>, bool, VertexKey)> {
+		let vertices = dto.vertices.items.iter().enumerate();
+		vertices.map(|(i, v)| (v.label.clone(), v.pinned, v.id.unwrap_or(i as VertexKey))).collect()
+	}
+	fn positions(dto: &DataDto) -> Vec<(Number, Number)> {
+		dto.vertices.items.iter().map(|v| (v.position.x, v.position.y)).collect()
+	}
+	/// The connections as [DataDto::from] normalizes them.
+	fn connections(dto: &DataDto) -> Vec<(VertexId, VertexId, &'static str, SizeId)> {
+		let normalized = DataDto::from(&Data::from(dto.clone()));
+		let connections = normalized.connections.iter();
+		connections.map(|(a, b, c)| (*a, *b, ConnectionOrientation::from(c.orientation).name(), c.size)).collect()
+	}
+	fn constraints(dto: &DataDto) -> Vec<(Edge, Edge, &'static str)> {
+		let constraints = dto.constraints.iter();
+		constraints.map(|(a, b, c)| (*a, *b, IntersectionConstraint::from(*c).name())).collect()
+	}
+	fn classes(dto: &DataDto) -> Vec<(Number, String)> {
+		let classes = dto.sizes.items.iter().chain(once(&dto.sizes.default));
+		classes.map(|c| (c.size, c.color.clone())).collect()
+	}
+	#[test]
+	fn drawings_cover_all_cases() {
+		for seed in 1..20 {
+			let dto = drawing(seed);
+			let connections = connections(&dto);
+			for orientation in ORIENTATIONS {
+				assert!(connections.iter().any(|c| c.2 == orientation.name()));
+			}
+			assert!(dto.vertices.items.iter().any(|v| v.label.is_none()));
+			assert!(vertices(&dto).iter().enumerate().all(|(i, v)| v.2 != i as VertexKey));
+			assert_eq!(dto.constraints.len(), 2);
+		}
+	}
+
+	#[test]
+	fn json_round_trip() {
+		for seed in 1..20 {
+			let dto = drawing(seed);
+			let json = Document::new(dto.clone(), Metadata::default()).to_json().unwrap();
+			let read = Document::from_json(&json).unwrap().drawing;
+
+			assert_eq!(vertices(&read), vertices(&dto));
+			assert_eq!(positions(&read), positions(&dto));
+			assert_eq!(connections(&read), connections(&dto));
+			assert_eq!(constraints(&read), constraints(&dto));
+			assert_eq!(classes(&read), classes(&dto));
+		}
+	}
+
+	#[test]
+	fn epsilon_is_only_stored_if_it_is_not_the_default() {
+		let json = |data: &Data| Document::new(DataDto::from(data), Metadata::default()).to_json().unwrap();
+		let mut data = Data::from(drawing(1));
+		assert!(!String::from_utf8(json(&data)).unwrap().contains("epsilon"));
+
+		data.tolerance = Tolerance::new(1e-6).unwrap();
+		let read = Data::from(Document::from_json(&json(&data)).unwrap().drawing);
+		assert_eq!(read.tolerance.epsilon, 1e-6);
+
+		let invalid = DataDto { epsilon: Some(-1.0), ..DataDto::from(&data) };
+		assert!(Data::from(invalid).tolerance == Tolerance::default());
+	}
+
+	#[test]
+	fn text_round_trip() {
+		for seed in 1..20 {
+			let dto = drawing(seed);
+			let import = DataDto::from_text(&dto.to_text(), TextMode::Strict).unwrap();
+			assert!(import.diagnostics.is_empty());
+			let read = import.data;
+
+			// the pinned state is not stored in the text format
+			let unpinned =
+				|dto: &DataDto| vertices(dto).into_iter().map(|(label, _, id)| (label, id)).collect::<Vec<_>>();
+			assert_eq!(unpinned(&read), unpinned(&dto));
+			assert_eq!(positions(&read), positions(&dto));
+			assert_eq!(connections(&read), connections(&dto));
+			assert_eq!(constraints(&read), constraints(&dto));
+			assert_eq!(classes(&read), classes(&dto));
+		}
+	}
+
+	#[test]
+	fn ipe_round_trip() {
+		let settings = Settings { show_missing_intersections: false, ..Default::default() };
+		for seed in 1..20 {
+			let dto = drawing(seed);
+			let data = Data::from(dto.clone());
+			let mut exporter = IpeExporter::new(&data.classes);
+			data.render_to(&mut exporter, &settings);
+			let read = IpeImporter::default().import(&exporter.to_string()).unwrap().data;
+
+			// Ipe only stores the geometry: no ids, no constraints and the positions rounded
+			let geometry =
+				|dto: &DataDto| vertices(dto).into_iter().map(|(label, pinned, _)| (label, pinned)).collect::<Vec<_>>();
+			assert_eq!(geometry(&read), geometry(&dto));
+			assert_eq!(read.vertices.items.len(), dto.vertices.items.len());
+			for (read, original) in read.vertices.items.iter().zip(&dto.vertices.items) {
+				assert!((read.position - original.position).length() < 1e-3);
+			}
+			assert_eq!(connections(&read), connections(&dto));
+		}
+	}
+
+	/// Exports the graph of the drawing in the format and imports it again with the classes of the drawing.
+	fn graph_round_trip(dto: &DataDto, format: GraphFormat) -> DataDto {
+		let text = format.write(&Graph::from(dto));
+		let graphs = format.read(&text).unwrap();
+		assert_eq!(graphs.len(), 1);
+		let importer = GraphImporter { classes: Classes::from(dto.sizes.clone()), ..Default::default() };
+		importer.import(&graphs[0])
+	}
+	/// The vertex pairs of all connections (without their direction).
+	fn pairs(dto: &DataDto) -> Vec<(VertexId, VertexId)> {
+		let mut pairs = dto.connections.iter().map(|&(a, b, _)| (a.min(b), a.max(b))).collect::<Vec<_>>();
+		pairs.sort();
+		pairs
+	}
+
+	#[test]
+	fn dot_and_graphml_round_trip() {
+		for format in [GraphFormat::Dot, GraphFormat::GraphMl] {
+			for seed in 1..20 {
+				let mut dto = drawing(seed);
+				dto.vertices.items[1].label = Some("a \"quoted\" <label> & more".to_owned());
+				let read = graph_round_trip(&dto, format);
+
+				// only the pinned state and the ids are lost (besides the constraints)
+				let labels = |dto: &DataDto| dto.vertices.items.iter().map(|v| v.label.clone()).collect::<Vec<_>>();
+				assert_eq!(labels(&read), labels(&dto));
+				assert_eq!(positions(&read), positions(&dto));
+				assert_eq!(connections(&read), connections(&dto));
+			}
+		}
+	}
+
+	#[test]
+	fn graph6_and_edge_list_round_trip() {
+		for format in [GraphFormat::Graph6, GraphFormat::EdgeList] {
+			for seed in 1..20 {
+				let mut dto = drawing(seed);
+				// an isolated vertex at the end, which only the vertex count of the file knows about
+				dto.vertices.items.push(VertexDto {
+					position: Vector::new(0.0, 0.0),
+					label: None,
+					pinned: false,
+					id: None,
+				});
+				let read = graph_round_trip(&dto, format);
+
+				// only the graph itself is stored
+				assert_eq!(read.vertices.items.len(), dto.vertices.items.len());
+				assert_eq!(pairs(&read), pairs(&dto));
+			}
+		}
+	}
+}
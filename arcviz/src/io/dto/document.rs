@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::io::LoadError;
+
+use super::{migrate, DataDto};
+
+/// The version of the JSON file format written by this build (see [Document]).
+pub const FORMAT_VERSION: u32 = 2;
+
+/// Descriptive information about a drawing (only stored in the JSON format).
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metadata {
+	pub title: String,
+	pub author: String,
+	pub notes: String,
+	/// When the file was first saved (ISO 8601).
+	pub created: Option<String>,
+	/// When the file was last saved (ISO 8601).
+	pub modified: Option<String>,
+}
+
+/// The versioned envelope of the JSON file format.
+///
+/// Files written in older format versions are migrated when they are read (see [migrate]),
+/// files without envelope (as written before it was introduced) count as version 0.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Document {
+	pub format_version: u32,
+	/// The version of arcviz that wrote the file.
+	pub app_version: String,
+	#[serde(default)]
+	pub metadata: Metadata,
+	pub drawing: DataDto,
+}
+impl Document {
+	pub fn new(drawing: DataDto, metadata: Metadata) -> Self {
+		Self {
+			format_version: FORMAT_VERSION,
+			app_version: env!("CARGO_PKG_VERSION").to_owned(),
+			metadata,
+			drawing,
+		}
+	}
+
+	pub fn to_json(&self) -> Result<Vec<u8>, ()> {
+		serde_json::to_vec(self).map_err(|_| ())
+	}
+
+	/// Reads a file in any version of the format.
+	pub fn from_json(bytes: &[u8]) -> Result<Self, LoadError> {
+		let text = std::str::from_utf8(bytes).map_err(|_| LoadError::new("the file is not valid UTF-8"))?;
+		let value = serde_json::from_str::<Value>(text).map_err(|e| LoadError::json(text, &e))?;
+		let version = match value.get("format_version") {
+			None => 0,
+			Some(version) => version.as_u64().ok_or(LoadError {
+				section: Some("format_version".to_owned()),
+				..LoadError::new("the format version is not a number")
+			})?,
+		};
+		if version > FORMAT_VERSION as u64 {
+			let app_version = value.get("app_version").and_then(|v| v.as_str()).unwrap_or("unknown");
+			return Err(LoadError::new(format!(
+				"the file has format version {} (written by arcviz {}), this version only reads up to {}",
+				version, app_version, FORMAT_VERSION
+			)));
+		}
+		if version == FORMAT_VERSION as u64 {
+			return serde_json::from_str(text).map_err(|e| LoadError::json(text, &e));
+		}
+
+		// positions in the migrated json mean nothing to the user, but the section still helps
+		let migrated = migrate(value, version as u32)?;
+		let migrated = serde_json::to_string_pretty(&migrated).map_err(|e| LoadError::new(e.to_string()))?;
+		serde_json::from_str(&migrated).map_err(|e| {
+			let error = LoadError::json(&migrated, &e).without_position();
+			let message = format!("{} (after migrating from format version {})", error.message, version);
+			LoadError { message, ..error }
+		})
+	}
+}
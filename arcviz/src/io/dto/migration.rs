@@ -0,0 +1,46 @@
+use serde_json::{json, Value};
+
+use crate::io::LoadError;
+
+use super::FORMAT_VERSION;
+
+/// A step of the migration chain, turning the json of one format version into the json of the next one.
+type Migration = fn(Value) -> Result<Value, LoadError>;
+
+/// The migration from version `i` is at index `i`.
+const MIGRATIONS: [Migration; FORMAT_VERSION as usize] = [version_0_to_1, version_1_to_2];
+
+/// Upgrades the json of a file in the given format version to the current [FORMAT_VERSION].
+///
+/// Old files pass through every step of the chain, so each change of the format only needs one new step.
+pub fn migrate(mut value: Value, version: u32) -> Result<Value, LoadError> {
+	for migration in MIGRATIONS.iter().skip(version as usize) {
+		value = migration(value)?;
+	}
+	Ok(value)
+}
+
+/// Version 0 is the bare drawing (without envelope).
+fn version_0_to_1(drawing: Value) -> Result<Value, LoadError> {
+	if !drawing.is_object() {
+		return Err(LoadError::new("the file doesn't contain a drawing"));
+	}
+	Ok(json!({
+		"format_version": 1,
+		"app_version": "unknown",
+		"metadata": {},
+		"drawing": drawing,
+	}))
+}
+
+/// Version 2 stores the persistent key of every vertex, older files get their position as key.
+fn version_1_to_2(mut document: Value) -> Result<Value, LoadError> {
+	let vertices = document.pointer_mut("/drawing/vertices/items").and_then(Value::as_array_mut);
+	for (i, vertex) in vertices.into_iter().flatten().enumerate() {
+		if let Some(vertex) = vertex.as_object_mut() {
+			vertex.entry("id").or_insert(json!(i));
+		}
+	}
+	document["format_version"] = json!(2);
+	Ok(document)
+}
@@ -1,3 +1,19 @@
 //! 'Plain old data' representations of the [crate::model] data structures.
 //! These are meant as an intermediary between the runtime data structures and the different file formats.
 //! As such they are meant to be plain data structures with public members and without much behavior that directly translate into what is stored on disk.
+//!
+//! Every format that stores the drawing itself reads into and writes from a [DataDto],
+//! only the conversion between [DataDto] and [crate::model::Data] knows about the model.
+//! (Exports that draw the drawing, like Ipe, SVG and TikZ, render the model instead.)
+
+mod data;
+pub use data::*;
+
+mod text;
+pub use text::*;
+
+mod document;
+pub use document::*;
+
+mod migration;
+pub use migration::*;
@@ -0,0 +1,448 @@
+use std::{collections::HashSet, iter::once};
+
+use crate::{
+	common::{Number, Vector},
+	io::LoadError,
+	model::{
+		edge, edges_are_adjacent, ConnectionOrientation, Edge, IntersectionConstraint, VertexId, VertexKey,
+	},
+};
+
+use super::{default_color, ClassDto, ClassesDto, ConnectionDto, ConstraintDto, DataDto, VertexDto};
+
+/// How [DataDto::from_text] treats lines it can't make sense of.
+#[derive(Copy, Clone, PartialEq)]
+pub enum TextMode {
+	/// The first invalid line (or missing section) fails the whole file.
+	Strict,
+	/// Invalid lines are skipped and reported in [TextImport::diagnostics].
+	Lenient,
+}
+
+/// The result of [DataDto::from_text].
+pub struct TextImport {
+	pub data: DataDto,
+	/// Why lines were skipped (always empty in [TextMode::Strict]).
+	pub diagnostics: Vec<LoadError>,
+}
+
+/// The plain text format.
+///
+/// The sections are separated by a single empty line (so an empty section is an additional empty line):
+/// 1. the classes (`size color`, one per line, the last one is the default)
+/// 2. the vertices (`x y label`, the label is optional)
+/// 3. the connections (`start end orientation class`, 1-based, the orientation is one of `right`, `left`, `Right` and `Left`)
+/// 4. the intersection constraints (`a b c d constraint`, 1-based, optional)
+/// 5. the ids of the vertices (`id`, one per vertex line, optional, only written if not `0, 1, 2, ...`)
+///
+/// The connections and constraints always refer to the positions of the vertices, the ids only keep
+/// the names of unlabeled vertices stable (see [crate::model::Data::vertex_name]).
+///
+/// Lines starting with `#` are comments, Windows line endings are accepted.
+/// Connection and constraint lines with additional tokens are invalid,
+/// and so are connections with the default class (the last one).
+impl DataDto {
+	pub fn to_text(&self) -> String {
+		let mut result = String::new();
+		result.push_str(&classes_to_text(&self.sizes));
+		result.push_str("\n\n");
+		result.push_str(&self.vertices.items.iter().map(vertex_to_text).collect::<Vec<_>>().join("\n"));
+		result.push_str("\n\n");
+		let connections = self.connections.iter().map(|(a, b, c)| connection_to_text(*a, *b, c));
+		result.push_str(&connections.collect::<Vec<_>>().join("\n"));
+		let ids = self.vertices.items.iter().enumerate().map(|(i, v)| v.id.unwrap_or(i as VertexKey));
+		let positional = ids.clone().enumerate().all(|(i, id)| id == i as VertexKey);
+		if !self.constraints.is_empty() || !positional {
+			result.push_str("\n\n");
+			let constraints = self.constraints.iter().map(|(first, second, constraint)| {
+				let constraint = IntersectionConstraint::from(*constraint);
+				format!("{} {} {} {} {}", first.0 + 1, first.1 + 1, second.0 + 1, second.1 + 1, constraint.name())
+			});
+			result.push_str(&constraints.collect::<Vec<_>>().join("\n"));
+		}
+		if !positional {
+			result.push_str("\n\n");
+			result.push_str(&ids.map(|id| id.to_string()).collect::<Vec<_>>().join("\n"));
+		}
+		result
+	}
+
+	/// Reads the text format.
+	///
+	/// Even in [TextMode::Lenient] this fails if there is no valid class,
+	/// since the text is most likely not in this format at all then.
+	pub fn from_text(text: &str, mode: TextMode) -> Result<TextImport, LoadError> {
+		// the lines of each section with their line number
+		let (mut sections, mut current) = (Vec::new(), Vec::new());
+		for (i, line) in text.lines().enumerate() {
+			match line.trim_start() {
+				"" => sections.push(std::mem::take(&mut current)),
+				content if content.starts_with('#') => (),
+				_ => current.push((i + 1, line)),
+			}
+		}
+		sections.push(current);
+		let section = |i: usize| sections.get(i).map(Vec::as_slice).unwrap_or_default();
+
+		let mut reader = TextReader { mode, diagnostics: Vec::new() };
+		for name in SECTIONS.iter().take(3).skip(sections.len()) {
+			let message = format!("the file has no {} section", name);
+			reader.report(LoadError { section: Some(name.to_string()), ..LoadError::new(message) })?;
+		}
+
+		let mut classes = Vec::new();
+		for &(number, line) in section(0) {
+			classes.extend(reader.check(SECTIONS[0], number, class_from_text(line))?);
+		}
+		let default = classes.pop().ok_or(LoadError {
+			section: Some(SECTIONS[0].to_owned()),
+			..LoadError::new("the file has no valid class")
+		})?;
+		let mut data = DataDto::new(ClassesDto { items: classes, default });
+
+		// the id of the vertex of each line (None if it was skipped), so later vertices keep their connections
+		let mut ids = Vec::new();
+		for &(number, line) in section(1) {
+			let vertex = reader.check(SECTIONS[1], number, vertex_from_text(line))?;
+			ids.push(vertex.map(|vertex| {
+				data.vertices.items.push(vertex);
+				data.vertices.items.len() - 1
+			}));
+		}
+
+		let mut edges = HashSet::new();
+		for &(number, line) in section(2) {
+			let connection = connection_from_text(line, &ids, &edges, data.sizes.items.len());
+			let connection = reader.check(SECTIONS[2], number, connection)?;
+			let Some((start, end, connection)) = connection else { continue };
+			edges.insert(edge(start, end));
+			data.connections.push((start, end, connection));
+		}
+
+		for &(number, line) in section(3) {
+			data.constraints.extend(reader.check(SECTIONS[3], number, constraint_from_text(line, &ids, &edges))?);
+		}
+
+		// one id per vertex line (including skipped ones)
+		let mut keys = HashSet::new();
+		for (i, &(number, line)) in section(4).iter().enumerate() {
+			let Some(key) = reader.check(SECTIONS[4], number, key_from_text(line, i, ids.len(), &keys))? else {
+				continue;
+			};
+			keys.insert(key);
+			if let Some(id) = ids[i] {
+				data.vertices.items[id].id = Some(key);
+			}
+		}
+
+		if let Some(&(line, _)) = sections.iter().skip(SECTIONS.len()).flatten().next() {
+			let message = "skipped everything after the ids section";
+			reader.report(LoadError { position: Some((line, 1)), ..LoadError::new(message) })?;
+		}
+
+		Ok(TextImport { data, diagnostics: reader.diagnostics })
+	}
+}
+
+/// The names of the sections of the text format (in order).
+const SECTIONS: [&str; 5] = ["classes", "vertices", "connections", "constraints", "ids"];
+
+/// What is wrong with a line: the column (starting at 1) and why.
+type Problem = (usize, String);
+
+struct TextReader {
+	mode: TextMode,
+	diagnostics: Vec<LoadError>,
+}
+impl TextReader {
+	/// Fails in [TextMode::Strict], keeps the error as diagnostic otherwise.
+	fn report(&mut self, error: LoadError) -> Result<(), LoadError> {
+		match self.mode {
+			TextMode::Strict => Err(error),
+			TextMode::Lenient => {
+				self.diagnostics.push(error);
+				Ok(())
+			},
+		}
+	}
+	/// The value of a valid line, None if the line is skipped.
+	fn check<T>(
+		&mut self,
+		section: &str,
+		line: usize,
+		result: Result<T, Problem>,
+	) -> Result<Option<T>, LoadError> {
+		match result {
+			Ok(value) => Ok(Some(value)),
+			Err((column, message)) => {
+				let section = Some(section.to_owned());
+				self.report(LoadError { position: Some((line, column)), section, ..LoadError::new(message) })?;
+				Ok(None)
+			},
+		}
+	}
+}
+
+/// The column (starting at 1) of a token that is a slice of the line.
+fn column(line: &str, token: &str) -> usize {
+	let offset = (token.as_ptr() as usize).saturating_sub(line.as_ptr() as usize).min(line.len());
+	line[..offset].chars().count() + 1
+}
+/// The column after the last token of the line (where a missing token is expected).
+fn end_column(line: &str) -> usize {
+	line.trim_end().chars().count() + 1
+}
+/// Splits off the first whitespace separated token.
+fn split_token(text: &str) -> (&str, &str) {
+	let text = text.trim_start();
+	match text.find(char::is_whitespace) {
+		Some(end) => (&text[..end], text[end..].trim_start()),
+		None => (text, ""),
+	}
+}
+fn number_from_text(line: &str, token: &str, what: &str) -> Result<Number, Problem> {
+	if token.is_empty() {
+		return Err((end_column(line), format!("missing {}", what)));
+	}
+	let number = token.parse::<Number>().ok().filter(|n| n.is_finite());
+	number.ok_or_else(|| (column(line, token), format!("invalid {} \"{}\"", what, token)))
+}
+/// Parses a 1-based index (of a vertex or class) into a 0-based one.
+fn index_from_text(line: &str, token: &str, what: &str) -> Result<usize, Problem> {
+	let index =
+		token.parse::<usize>().map_err(|_| (column(line, token), format!("invalid {} \"{}\"", what, token)))?;
+	index.checked_sub(1).ok_or_else(|| (column(line, token), format!("{} indices start at 1", what)))
+}
+fn vertex_id_from_text(line: &str, token: &str, ids: &[Option<VertexId>]) -> Result<VertexId, Problem> {
+	match ids.get(index_from_text(line, token, "vertex")?) {
+		Some(Some(id)) => Ok(*id),
+		Some(None) => Err((column(line, token), format!("vertex {} was skipped", token))),
+		None => Err((column(line, token), format!("there is no vertex {}", token))),
+	}
+}
+
+fn classes_to_text(classes: &ClassesDto) -> String {
+	let lines = classes.items.iter().chain(once(&classes.default)).map(|c| format!("{} {}", c.size, c.color));
+	lines.collect::<Vec<_>>().join("\n")
+}
+fn class_from_text(line: &str) -> Result<ClassDto, Problem> {
+	let (size_token, color) = split_token(line);
+	let size = number_from_text(line, size_token, "size")?;
+	if size <= 0.0 {
+		return Err((column(line, size_token), "sizes need to be positive".to_owned()));
+	}
+	let color = match color.trim_end() {
+		"" => default_color(),
+		color => color.to_owned(),
+	};
+	Ok(ClassDto { size, color })
+}
+
+fn vertex_to_text(vertex: &VertexDto) -> String {
+	match &vertex.label {
+		None => format!("{} {}", vertex.position.x, vertex.position.y),
+		Some(label) => format!("{} {} {}", vertex.position.x, vertex.position.y, label),
+	}
+}
+fn vertex_from_text(line: &str) -> Result<VertexDto, Problem> {
+	let (x, rest) = split_token(line);
+	let (y, label) = split_token(rest);
+	let position =
+		Vector::new(number_from_text(line, x, "x coordinate")?, number_from_text(line, y, "y coordinate")?);
+	let label = match label.trim_end() {
+		"" => None,
+		label => Some(label.to_owned()),
+	};
+	Ok(VertexDto { id: None, position, label, pinned: false })
+}
+
+/// - index: the position of the line in the ids section (the vertex line it belongs to)
+/// - count: the number of vertex lines
+fn key_from_text(
+	line: &str,
+	index: usize,
+	count: usize,
+	keys: &HashSet<VertexKey>,
+) -> Result<VertexKey, Problem> {
+	let token = line.trim();
+	if index >= count {
+		return Err((1, format!("there are only {} vertices", count)));
+	}
+	let key = token.parse().map_err(|_| (column(line, token), format!("invalid id \"{}\"", token)))?;
+	match keys.contains(&key) {
+		true => Err((column(line, token), format!("the id {} is used twice", key))),
+		false => Ok(key),
+	}
+}
+
+fn connection_to_text(start: VertexId, end: VertexId, connection: &ConnectionDto) -> String {
+	let orientation = ConnectionOrientation::from(connection.orientation);
+	format!("{} {} {} {}", start + 1, end + 1, orientation.name(), connection.size + 1)
+}
+/// The problem with a line that has more or less than the expected number of tokens.
+fn token_count_problem(line: &str, tokens: &[&str], count: usize, expected: &str) -> Problem {
+	match tokens.get(count) {
+		Some(extra) => (column(line, extra), format!("unexpected \"{}\" after {}", extra, expected)),
+		None => (end_column(line), format!("expected {}", expected)),
+	}
+}
+
+/// - classes: the number of classes (not counting the default)
+fn connection_from_text(
+	line: &str,
+	ids: &[Option<VertexId>],
+	edges: &HashSet<Edge>,
+	classes: usize,
+) -> Result<(VertexId, VertexId, ConnectionDto), Problem> {
+	let tokens = line.split_whitespace().collect::<Vec<_>>();
+	let [start_token, end_token, orientation_token, class_token] = tokens[..] else {
+		return Err(token_count_problem(line, &tokens, 4, "start, end, orientation and class"));
+	};
+	let start = vertex_id_from_text(line, start_token, ids)?;
+	let end = vertex_id_from_text(line, end_token, ids)?;
+	if start == end {
+		return Err((column(line, end_token), "a vertex can't be connected to itself".to_owned()));
+	}
+	if edges.contains(&edge(start, end)) {
+		let message =
+			format!("there already is a connection between {} and {} (in either order)", start_token, end_token);
+		return Err((column(line, start_token), message));
+	}
+	let orientation = ConnectionOrientation::from_name(orientation_token).ok_or_else(|| {
+		let message =
+			format!("unknown orientation \"{}\" (expected right, left, Right or Left)", orientation_token);
+		(column(line, orientation_token), message)
+	})?;
+	let size = index_from_text(line, class_token, "class")?;
+	if size >= classes {
+		return Err((column(line, class_token), format!("there is no class {}", class_token)));
+	}
+	Ok((start, end, ConnectionDto { orientation: orientation.into(), size }))
+}
+
+fn constraint_from_text(
+	line: &str,
+	ids: &[Option<VertexId>],
+	edges: &HashSet<Edge>,
+) -> Result<(Edge, Edge, ConstraintDto), Problem> {
+	let tokens = line.split_whitespace().collect::<Vec<_>>();
+	let [a, b, c, d, constraint_token] = tokens[..] else {
+		return Err(token_count_problem(line, &tokens, 5, "4 vertices and a constraint"));
+	};
+	let edge_from_text = |start: &str, end: &str| -> Result<Edge, Problem> {
+		let edge = edge(vertex_id_from_text(line, start, ids)?, vertex_id_from_text(line, end, ids)?);
+		match edges.contains(&edge) {
+			true => Ok(edge),
+			false => Err((column(line, start), format!("there is no connection between {} and {}", start, end))),
+		}
+	};
+	let (first, second) = (edge_from_text(a, b)?, edge_from_text(c, d)?);
+	if first == second || edges_are_adjacent(&first, &second) {
+		return Err((column(line, c), "the constraint of edges sharing a vertex can't be changed".to_owned()));
+	}
+	let constraint = constraint_token.parse::<IntersectionConstraint>().map_err(|()| {
+		let message =
+			format!("unknown constraint \"{}\" (expected necessary, forbidden or irrelevant)", constraint_token);
+		(column(line, constraint_token), message)
+	})?;
+	Ok((first, second, constraint.into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// 2 classes and the default, 3 vertices and the separator before the connections (which start at line 9).
+	const HEAD: &str = "100 red\n200 blue\n300 white\n\n0 0\n10 0 a label\n0 10\n\n";
+
+	/// A valid file with the given line after its 2 connections (line 11).
+	fn with_connection(line: &str) -> String {
+		format!("{}1 2 right 1\n2 3 left 2\n{}\n", HEAD, line)
+	}
+	/// A valid file with the given line as its only constraint (line 12).
+	fn with_constraint(line: &str) -> String {
+		format!("{}1 2 right 1\n2 3 left 2\n\n{}\n", HEAD, line)
+	}
+
+	/// The problem that fails the text in [TextMode::Strict] (checks that [TextMode::Lenient] reports the same).
+	fn problem(text: &str) -> (Option<(usize, usize)>, String) {
+		let strict = DataDto::from_text(text, TextMode::Strict).err().unwrap();
+		let lenient = DataDto::from_text(text, TextMode::Lenient).ok().unwrap();
+		assert_eq!(lenient.diagnostics.len(), 1);
+		assert_eq!(lenient.diagnostics[0].to_string(), strict.to_string());
+		assert_eq!(lenient.data.connections.len(), 2);
+		(strict.position, strict.message)
+	}
+
+	#[test]
+	fn reads_valid_files() {
+		let import = DataDto::from_text(&with_connection("3 1 Left 2"), TextMode::Strict).ok().unwrap();
+		assert!(import.diagnostics.is_empty());
+		let data = import.data;
+		assert_eq!(data.sizes.items.len(), 2);
+		let labels = data.vertices.items.iter().map(|v| v.label.as_deref()).collect::<Vec<_>>();
+		assert_eq!(labels, [None, Some("a label"), None]);
+		let connections = data.connections.iter().map(|(a, b, c)| (*a, *b, c.size)).collect::<Vec<_>>();
+		assert_eq!(connections, [(0, 1, 0), (1, 2, 1), (2, 0, 1)]);
+	}
+
+	#[test]
+	fn strict_fails_where_lenient_skips_the_line() {
+		let cases = [
+			("1 3 right 1 extra", (13, "unexpected \"extra\" after start, end, orientation and class")),
+			("1 3", (4, "expected start, end, orientation and class")),
+			("1 3 right 3", (11, "there is no class 3")),
+			("1 3 right 0", (11, "class indices start at 1")),
+			("1 2 right 1", (1, "there already is a connection between 1 and 2 (in either order)")),
+			("3 3 right 1", (3, "a vertex can't be connected to itself")),
+		];
+		for (line, (column, message)) in cases {
+			let (position, read) = problem(&with_connection(line));
+			assert_eq!((position, read.as_str()), (Some((11, column)), message));
+		}
+
+		let (position, message) = problem(&with_constraint("1 2 3"));
+		assert_eq!((position, message.as_str()), (Some((12, 6)), "expected 4 vertices and a constraint"));
+		let (position, _) = problem(&with_constraint("1 2 2 3 forbidden extra"));
+		assert_eq!(position, Some((12, 19)));
+	}
+
+	#[test]
+	fn rejects_vertex_indices_out_of_range() {
+		for (line, column, message) in [
+			("1 4 right 1", 3, "there is no vertex 4"),
+			("0 1 right 1", 1, "vertex indices start at 1"),
+			("1 18446744073709551616 right 1", 3, "invalid vertex \"18446744073709551616\""),
+		] {
+			let (position, read) = problem(&with_connection(line));
+			assert_eq!((position, read.as_str()), (Some((11, column)), message));
+		}
+		let (position, read) = problem(&with_constraint("1 2 9 3 forbidden"));
+		assert_eq!((position, read.as_str()), (Some((12, 5)), "there is no vertex 9"));
+	}
+
+	#[test]
+	fn connections_of_skipped_vertices_are_skipped() {
+		let text = "100 red\n300 white\n\n0 0\nx 0\n0 10\n\n1 3 right 1\n1 2 right 1\n";
+		let import = DataDto::from_text(text, TextMode::Lenient).ok().unwrap();
+		let problems = import.diagnostics.iter().map(|e| (e.position, e.message.clone())).collect::<Vec<_>>();
+		let expected = [(Some((5, 1)), "invalid x coordinate \"x\""), (Some((9, 3)), "vertex 2 was skipped")];
+		assert_eq!(problems, expected.map(|(position, message)| (position, message.to_owned())));
+		let connections = import.data.connections.iter().map(|(a, b, _)| (*a, *b)).collect::<Vec<_>>();
+		assert_eq!(connections, [(0, 1)]);
+	}
+
+	#[test]
+	fn skips_comments_and_accepts_windows_line_endings() {
+		let plain = format!("{}1 2 right 1\n2 3 left 2\n", HEAD);
+		let commented = format!("# classes\n{}", plain.replace("\n\n0 0\n", "\n\n# vertices\n0 0\n  # indented\n"));
+		let windows = commented.replace('\n', "\r\n");
+		let read = |text: &str| DataDto::from_text(text, TextMode::Strict).ok().unwrap().data.to_text();
+		assert_eq!(read(&commented), read(&plain));
+		assert_eq!(read(&windows), read(&plain));
+
+		// the line numbers count the comments
+		let (position, _) = problem(&format!("# a comment\n{}", with_connection("1 4 right 1")));
+		assert_eq!(position, Some((12, 3)));
+	}
+}
@@ -0,0 +1,73 @@
+/// Replaces the characters that can't appear verbatim in xml text/attributes.
+pub fn escape_xml(text: &str) -> String {
+	text
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+		.replace('\'', "&apos;")
+}
+
+/// Replaces the characters that have a special meaning in LaTeX.
+pub fn escape_latex(text: &str) -> String {
+	text
+		.chars()
+		.map(|c| match c {
+			'\\' => "\\textbackslash{}".to_owned(),
+			'~' => "\\textasciitilde{}".to_owned(),
+			'^' => "\\textasciicircum{}".to_owned(),
+			'&' | '%' | '$' | '#' | '_' | '{' | '}' => format!("\\{}", c),
+			c => c.to_string(),
+		})
+		.collect()
+}
+
+/// Reverses [escape_xml] (and replaces numeric character references).
+pub fn unescape_xml(text: &str) -> String {
+	let mut result = String::new();
+	let mut rest = text;
+	while let Some(start) = rest.find('&') {
+		result.push_str(&rest[..start]);
+		rest = &rest[start..];
+		let Some(end) = rest.find(';') else { break };
+		let entity = &rest[1..end];
+		let replacement = match entity {
+			"amp" => Some('&'),
+			"lt" => Some('<'),
+			"gt" => Some('>'),
+			"quot" => Some('"'),
+			"apos" => Some('\''),
+			_ => match entity.strip_prefix("#x") {
+				Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+				None => entity.strip_prefix('#').and_then(|d| d.parse().ok()).and_then(char::from_u32),
+			},
+		};
+		match replacement {
+			Some(c) => {
+				result.push(c);
+				rest = &rest[end + 1..];
+			},
+			None => {
+				result.push('&');
+				rest = &rest[1..];
+			},
+		}
+	}
+	result.push_str(rest);
+	result
+}
+
+/// Reverses [escape_latex].
+pub fn unescape_latex(text: &str) -> String {
+	text
+		.replace("\\textbackslash{}", "\\")
+		.replace("\\textasciitilde{}", "~")
+		.replace("\\textasciicircum{}", "^")
+		.replace("\\&", "&")
+		.replace("\\%", "%")
+		.replace("\\$", "$")
+		.replace("\\#", "#")
+		.replace("\\_", "_")
+		.replace("\\{", "{")
+		.replace("\\}", "}")
+}
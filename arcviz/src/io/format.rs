@@ -0,0 +1,89 @@
+use crate::model::{Data, Settings};
+
+use super::{
+	dto::{DataDto, Document, Metadata, TextMode},
+	graph::{Graph, GraphFormat, GraphImporter},
+	ipe::{IpeExporter, IpeImporter},
+	LoadError,
+};
+
+/// The file formats [Data] can be stored in.
+#[derive(Copy, Clone, PartialEq)]
+pub enum FileFormat {
+	/// The plain text format (see [DataDto::to_text], read in the given [TextMode]).
+	Text,
+	/// The serde representation of [DataDto] as JSON (in the versioned envelope [Document]).
+	Json,
+	/// A drawing for the Ipe editor (see [IpeExporter] and [IpeImporter], notes about skipped objects are dropped).
+	Ipe,
+	/// A plain graph for other graph tools (see [GraphFormat], imported with the default [GraphImporter]).
+	Graph(GraphFormat),
+}
+impl FileFormat {
+	/// Guesses the format from the extension of the given file name (defaults to [FileFormat::Text]).
+	pub fn from_path(path: &str) -> Self {
+		match path.rsplit_once('.') {
+			Some((_, extension)) if extension.eq_ignore_ascii_case("json") => FileFormat::Json,
+			Some((_, extension)) if extension.eq_ignore_ascii_case("ipe") => FileFormat::Ipe,
+			_ => GraphFormat::from_path(path).map(FileFormat::Graph).unwrap_or(FileFormat::Text),
+		}
+	}
+
+	/// Reads all drawings in the file (only graph6 files may contain more than one).
+	///
+	/// Returns the drawings and the lines that were skipped (see [TextMode::Lenient]).
+	pub fn read_all(&self, bytes: &[u8], mode: TextMode) -> Result<(Vec<Data>, Vec<LoadError>), LoadError> {
+		match self {
+			FileFormat::Graph(format) => {
+				let graphs = format.read(&Self::text(bytes)?)?;
+				let importer = GraphImporter::default();
+				Ok((graphs.iter().map(|graph| importer.import(graph).into()).collect(), Vec::new()))
+			},
+			_ => {
+				let (data, diagnostics) = self.read(bytes, mode)?;
+				Ok((vec![data], diagnostics))
+			},
+		}
+	}
+
+	/// Reads the file, returns the drawing and the lines that were skipped (see [TextMode::Lenient]).
+	pub fn read(&self, bytes: &[u8], mode: TextMode) -> Result<(Data, Vec<LoadError>), LoadError> {
+		let (dto, diagnostics) = self.read_dto(bytes, mode)?;
+		Ok((dto.into(), diagnostics))
+	}
+	/// Reads the file without converting it to [Data].
+	pub fn read_dto(&self, bytes: &[u8], mode: TextMode) -> Result<(DataDto, Vec<LoadError>), LoadError> {
+		match self {
+			FileFormat::Text => {
+				let import = DataDto::from_text(&Self::text(bytes)?, mode)?;
+				Ok((import.data, import.diagnostics))
+			},
+			FileFormat::Json => Ok((Document::from_json(bytes)?.drawing, Vec::new())),
+			FileFormat::Ipe => {
+				let import = IpeImporter::default().import(&Self::text(bytes)?);
+				Ok((import.map_err(|()| LoadError::new("not an Ipe document"))?.data, Vec::new()))
+			},
+			FileFormat::Graph(format) => {
+				let graphs = format.read(&Self::text(bytes)?)?;
+				let graph = graphs.into_iter().next().ok_or(LoadError::new("the file contains no graph"))?;
+				Ok((GraphImporter::default().import(&graph), Vec::new()))
+			},
+		}
+	}
+	fn text(bytes: &[u8]) -> Result<String, LoadError> {
+		String::from_utf8(bytes.to_vec()).map_err(|_| LoadError::new("the file is not valid UTF-8"))
+	}
+
+	pub fn write(&self, data: &Data) -> Result<Vec<u8>, ()> {
+		match self {
+			FileFormat::Text => Ok(DataDto::from(data).to_text().as_bytes().to_vec()),
+			FileFormat::Json => Document::new(data.into(), Metadata::default()).to_json(),
+			FileFormat::Ipe => {
+				let mut exporter = IpeExporter::new(&data.classes);
+				data.render_to(&mut exporter, &Settings::default());
+				Ok(exporter.to_string().as_bytes().to_vec())
+			},
+			FileFormat::Graph(format) => Ok(format.write(&Graph::from(&DataDto::from(data))).as_bytes().to_vec()),
+		}
+	}
+}
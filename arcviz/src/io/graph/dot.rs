@@ -0,0 +1,340 @@
+use std::{iter::Peekable, str::CharIndices};
+
+use crate::{
+	common::{Number, Vector},
+	io::LoadError,
+	model::ConnectionOrientation,
+};
+
+use super::{Graph, GraphEdge, VertexNames};
+
+#[derive(Clone, PartialEq)]
+enum Token {
+	/// An identifier, numeral, quoted or html string.
+	Id(String),
+	/// `--` or `->` (both are read as undirected edges)
+	Edge,
+	Open,
+	Close,
+	OpenAttributes,
+	CloseAttributes,
+	Equals,
+	/// `;` or `,`
+	Separator,
+	/// `:` (introduces a port)
+	Port,
+}
+
+/// Splits the text into tokens, returns them with the byte offsets they start at.
+fn tokens(text: &str) -> Result<(Vec<Token>, Vec<usize>), LoadError> {
+	let (mut tokens, mut offsets) = (Vec::new(), Vec::new());
+	let mut chars = text.char_indices().peekable();
+	let skip_line = |chars: &mut Peekable<CharIndices>| while chars.next_if(|&(_, c)| c != '\n').is_some() {};
+	let next_is = |chars: &mut Peekable<CharIndices>, next: char| chars.next_if(|&(_, c)| c == next).is_some();
+	while let Some((offset, c)) = chars.next() {
+		let unterminated = |what: &str| LoadError::at(text, offset, format!("unterminated {}", what));
+		let token = match c {
+			c if c.is_whitespace() => continue,
+			'/' if next_is(&mut chars, '/') => {
+				skip_line(&mut chars);
+				continue;
+			},
+			'/' if next_is(&mut chars, '*') => {
+				let mut last = ' ';
+				loop {
+					let (_, c) = chars.next().ok_or_else(|| unterminated("comment"))?;
+					if last == '*' && c == '/' {
+						break;
+					}
+					last = c;
+				}
+				continue;
+			},
+			'#' => {
+				skip_line(&mut chars);
+				continue;
+			},
+			'{' => Token::Open,
+			'}' => Token::Close,
+			'[' => Token::OpenAttributes,
+			']' => Token::CloseAttributes,
+			'=' => Token::Equals,
+			';' | ',' => Token::Separator,
+			':' => Token::Port,
+			'-' if chars.next_if(|&(_, c)| c == '-' || c == '>').is_some() => Token::Edge,
+			'"' => {
+				let mut value = String::new();
+				loop {
+					match chars.next().ok_or_else(|| unterminated("string"))?.1 {
+						'"' => break,
+						'\\' => match chars.next().ok_or_else(|| unterminated("string"))?.1 {
+							'"' => value.push('"'),
+							'\n' => (),
+							c => value.extend(['\\', c]),
+						},
+						c => value.push(c),
+					}
+				}
+				Token::Id(value)
+			},
+			'<' => {
+				let (mut value, mut depth) = (String::new(), 1);
+				while depth > 0 {
+					let (_, c) = chars.next().ok_or_else(|| unterminated("html string"))?;
+					depth += match c {
+						'<' => 1,
+						'>' => -1,
+						_ => 0,
+					};
+					if depth > 0 {
+						value.push(c);
+					}
+				}
+				Token::Id(value)
+			},
+			c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+				let mut value = c.to_string();
+				while let Some((_, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_' || c == '.') {
+					value.push(c);
+				}
+				Token::Id(value)
+			},
+			c => return Err(LoadError::at(text, offset, format!("unexpected character {:?}", c))),
+		};
+		tokens.push(token);
+		offsets.push(offset);
+	}
+	Ok((tokens, offsets))
+}
+
+/// Reads the attribute lists (`[a=b, c=d][e=f]`) starting at the given token.
+///
+/// Returns the attributes and the index of the first token after them (or the index of the invalid token).
+fn attributes(tokens: &[Token], mut i: usize) -> Result<(Vec<(String, String)>, usize), usize> {
+	let mut attributes = Vec::new();
+	while tokens.get(i) == Some(&Token::OpenAttributes) {
+		i += 1;
+		loop {
+			match (tokens.get(i), tokens.get(i + 1), tokens.get(i + 2)) {
+				(Some(Token::CloseAttributes), _, _) => break,
+				(Some(Token::Separator), _, _) => i += 1,
+				(Some(Token::Id(key)), Some(Token::Equals), Some(Token::Id(value))) => {
+					attributes.push((key.clone(), value.clone()));
+					i += 3;
+				},
+				(Some(Token::Id(_)), _, _) => i += 1,
+				_ => return Err(i),
+			}
+		}
+		i += 1;
+	}
+	Ok((attributes, i))
+}
+
+/// Reads a node id (ignoring its port) starting at the given token.
+///
+/// Returns the name and the index of the first token after it (or the index of the invalid token).
+fn node(tokens: &[Token], i: usize) -> Result<(&str, usize), usize> {
+	let Some(Token::Id(name)) = tokens.get(i) else { return Err(i) };
+	let mut i = i + 1;
+	while let (Some(Token::Port), Some(Token::Id(_))) = (tokens.get(i), tokens.get(i + 1)) {
+		i += 2;
+	}
+	Ok((name, i))
+}
+
+/// Parses a Graphviz position (`x,y` in points with y pointing up, optionally followed by `!`).
+fn dot_position(value: &str) -> Option<Vector> {
+	let (x, y) = value.trim_end_matches('!').split_once(',')?;
+	Some(Vector::new(x.trim().parse().ok()?, -y.trim().parse::<Number>().ok()?))
+}
+
+fn quote(text: &str) -> String {
+	format!("\"{}\"", text.replace('"', "\\\""))
+}
+
+/// Reads the first graph of a file in the DOT language of Graphviz.
+///
+/// Directed edges are read as undirected ones and subgraphs are flattened
+/// (edges to whole subgraphs are not supported).
+/// The `label` and `pos` attributes of nodes and the `orientation` and `radius` attributes of edges are kept
+/// (other attributes and defaults are ignored).
+pub fn read_dot(text: &str) -> Result<Graph, LoadError> {
+	let (tokens, offsets) = tokens(text)?;
+	// missing tokens are reported at the end of the file
+	let offset = |i: usize| offsets.get(i).copied().unwrap_or(text.len());
+	let error = |i: usize, message: &str| LoadError::at(text, offset(i), message);
+	let start = tokens.iter().position(|t| *t == Token::Open).ok_or_else(|| LoadError::new("no graph found"))?;
+	let is_graph = |t: &Token| match t {
+		Token::Id(k) => k.eq_ignore_ascii_case("graph") || k.eq_ignore_ascii_case("digraph"),
+		_ => false,
+	};
+	if !tokens[..start].iter().any(is_graph) {
+		return Err(error(start, "expected graph or digraph before {"));
+	}
+
+	let mut graph = Graph::default();
+	let mut names = VertexNames::default();
+	let (mut i, mut depth) = (start + 1, 1);
+	while depth > 0 {
+		let token = tokens.get(i).ok_or_else(|| error(i, "missing }"))?;
+		let keyword = match token {
+			Token::Id(k) => k.to_lowercase(),
+			_ => String::new(),
+		};
+		match (token, tokens.get(i + 1)) {
+			(Token::Separator, _) => i += 1,
+			(Token::Open, _) => {
+				depth += 1;
+				i += 1;
+			},
+			(Token::Close, _) => {
+				depth -= 1;
+				i += 1;
+			},
+			(Token::Id(_), _) if keyword == "subgraph" => {
+				i += match tokens.get(i + 1) {
+					Some(Token::Id(_)) => 2,
+					_ => 1,
+				};
+			},
+			(Token::Id(_), Some(Token::OpenAttributes)) if ["graph", "node", "edge"].contains(&keyword.as_str()) => {
+				i = attributes(&tokens, i + 1).map_err(|i| error(i, "invalid attribute"))?.1;
+			},
+			(Token::Id(_), Some(Token::Equals)) => i += 3,
+			(Token::Id(_), _) => {
+				let statement = i;
+				let (name, next) = node(&tokens, i).map_err(|i| error(i, "expected a node"))?;
+				let mut chain = vec![names.id(&mut graph, name)];
+				i = next;
+				while tokens.get(i) == Some(&Token::Edge) {
+					let (name, next) = node(&tokens, i + 1).map_err(|i| error(i, "expected a node"))?;
+					chain.push(names.id(&mut graph, name));
+					i = next;
+				}
+				let (attributes, next) = attributes(&tokens, i).map_err(|i| error(i, "invalid attribute"))?;
+				i = next;
+
+				if let [vertex] = chain[..] {
+					for (key, value) in &attributes {
+						match key.as_str() {
+							"label" => graph.vertices[vertex].label = Some(value.clone()),
+							"pos" => graph.vertices[vertex].position = dot_position(value),
+							_ => (),
+						}
+					}
+				}
+				for pair in chain.windows(2) {
+					let mut edge = GraphEdge::new(pair[0], pair[1]);
+					for (key, value) in &attributes {
+						match key.as_str() {
+							"orientation" => edge.orientation = ConnectionOrientation::from_name(value),
+							"radius" => edge.radius = value.parse().ok(),
+							_ => (),
+						}
+					}
+					graph.add_edge(edge).map_err(|()| error(statement, "invalid edge"))?;
+				}
+			},
+			_ => return Err(error(i, "unexpected token")),
+		}
+	}
+	Ok(graph)
+}
+
+/// Writes the graph in the DOT language of Graphviz (see [read_dot]).
+///
+/// Vertices are named `v0`, `v1`, .. and get their label and position (with y pointing up, as in Graphviz) as attributes,
+/// edges their `orientation` (as in the text format, for the direction they are written in) and `radius`.
+pub fn write_dot(graph: &Graph) -> String {
+	let mut dot = String::from("graph {\n");
+	for (id, vertex) in graph.vertices.iter().enumerate() {
+		let mut attributes = Vec::new();
+		if let Some(label) = &vertex.label {
+			attributes.push(format!("label={}", quote(label)));
+		}
+		if let Some(position) = vertex.position {
+			attributes.push(format!("pos=\"{},{}!\"", position.x, -position.y));
+		}
+		match attributes.is_empty() {
+			true => dot.push_str(&format!("\tv{};\n", id)),
+			false => dot.push_str(&format!("\tv{} [{}];\n", id, attributes.join(", "))),
+		}
+	}
+	for edge in graph.edges() {
+		let mut attributes = Vec::new();
+		if let Some(orientation) = edge.orientation {
+			attributes.push(format!("orientation={}", orientation.name()));
+		}
+		if let Some(radius) = edge.radius {
+			attributes.push(format!("radius={}", radius));
+		}
+		match attributes.is_empty() {
+			true => dot.push_str(&format!("\tv{} -- v{};\n", edge.start, edge.end)),
+			false => dot.push_str(&format!("\tv{} -- v{} [{}];\n", edge.start, edge.end, attributes.join(", "))),
+		}
+	}
+	dot.push_str("}\n");
+	dot
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const DOT: &str = r#"// a line comment
+/* a block
+   comment */
+digraph "G" {
+	graph [rankdir=LR];
+	node [shape=circle]
+	a [label="Start \"A\"", pos="10,20!"];
+	b [pos="30.5,-4"]
+	a -> b -> c [orientation=Left, radius=80][color=red];
+	# a preprocessor line
+	subgraph cluster_0 { c -- d:port:n; }
+	e; 7
+	rankdir = TB
+}
+"#;
+
+	#[test]
+	fn reads_attributes_and_skips_comments() {
+		let graph = read_dot(DOT).unwrap();
+		let labels = graph.vertices.iter().map(|v| v.label.as_deref()).collect::<Vec<_>>();
+		assert_eq!(labels, [Some("Start \"A\""), Some("b"), Some("c"), Some("d"), Some("e"), None]);
+		let positions = graph.vertices.iter().map(|v| v.position.map(|p| (p.x, p.y))).collect::<Vec<_>>();
+		assert_eq!(positions[..3], [Some((10.0, -20.0)), Some((30.5, 4.0)), None]);
+
+		let edges = graph.edges().iter().map(|e| (e.start, e.end, e.orientation.map(|o| o.name()), e.radius));
+		let edges = edges.collect::<Vec<_>>();
+		assert_eq!(edges, [(0, 1, Some("Left"), Some(80.0)), (1, 2, Some("Left"), Some(80.0)), (2, 3, None, None)]);
+	}
+
+	#[test]
+	fn locates_errors() {
+		let error = read_dot("graph {\n\ta -- ;\n}").err().unwrap();
+		assert_eq!((error.message.as_str(), error.position), ("expected a node", Some((2, 7))));
+
+		let error = read_dot("graph {\n a [label=\"x];\n}").err().unwrap();
+		assert_eq!((error.message.as_str(), error.position), ("unterminated string", Some((2, 11))));
+
+		let error = read_dot("graph {\n a -- b").err().unwrap();
+		assert_eq!((error.message.as_str(), error.position), ("missing }", Some((2, 8))));
+
+		assert!(read_dot("{ a -- b }").is_err());
+	}
+
+	#[test]
+	fn writes_what_it_reads() {
+		let graph = read_dot(DOT).unwrap();
+		let read = read_dot(&write_dot(&graph)).unwrap();
+		let labels = |graph: &Graph| graph.vertices.iter().map(|v| v.label.clone()).collect::<Vec<_>>();
+		assert_eq!(labels(&read), labels(&graph));
+		let edges = |graph: &Graph| {
+			let edges = graph.edges().iter();
+			edges.map(|e| (e.start, e.end, e.orientation.map(|o| o.name()), e.radius)).collect::<Vec<_>>()
+		};
+		assert_eq!(edges(&read), edges(&graph));
+	}
+}
@@ -0,0 +1,104 @@
+use std::{collections::HashSet, iter::once};
+
+use crate::io::LoadError;
+
+use super::{Graph, GraphEdge, VertexNames};
+
+/// Reads an edge list: one edge per line given by the names of its end vertices (separated by whitespace or commas).
+///
+/// Anything after the 2 names (like the edge data NetworkX writes) is ignored, as are empty lines and comments (`#`).
+/// A line with a single name adds an isolated vertex.
+/// If all names are integers without large gaps they are used as vertex ids (keeping the isolated vertices in the gaps),
+/// otherwise every name becomes a vertex (in order of appearance).
+pub fn read_edge_list(text: &str) -> Result<Graph, LoadError> {
+	let mut lines = Vec::new();
+	for (i, line) in text.lines().enumerate() {
+		let line = line.split('#').next().unwrap_or_default();
+		let mut names = line.split(|c: char| c.is_whitespace() || c == ',').filter(|name| !name.is_empty());
+		match (names.next(), names.next()) {
+			(Some(a), b) => lines.push((i + 1, a, b)),
+			(None, _) => (),
+		}
+	}
+	let invalid = |line: usize| LoadError { position: Some((line, 1)), ..LoadError::new("invalid edge") };
+
+	let names = lines.iter().flat_map(|&(_, a, b)| once(a).chain(b)).collect::<HashSet<_>>();
+	let ids = names.iter().map(|name| name.parse::<usize>().ok()).collect::<Option<Vec<_>>>();
+	let mut graph = Graph::default();
+	match ids.and_then(|ids| ids.into_iter().max()) {
+		Some(max) if max < 2 * names.len() => {
+			graph = Graph::with_vertices(max + 1);
+			for (line, a, b) in lines {
+				let Some(b) = b else { continue };
+				let (a, b) = (a.parse().map_err(|_| invalid(line))?, b.parse().map_err(|_| invalid(line))?);
+				graph.add_edge(GraphEdge::new(a, b)).map_err(|()| invalid(line))?;
+			}
+		},
+		_ => {
+			let mut names = VertexNames::default();
+			for (line, a, b) in lines {
+				let a = names.id(&mut graph, a);
+				let Some(b) = b else { continue };
+				let b = names.id(&mut graph, b);
+				graph.add_edge(GraphEdge::new(a, b)).map_err(|()| invalid(line))?;
+			}
+		},
+	}
+	Ok(graph)
+}
+
+/// Writes the graph as edge list with the vertex ids as names (see [read_edge_list]).
+///
+/// Isolated vertices are written on a line of their own, labels and geometry are lost.
+pub fn write_edge_list(graph: &Graph) -> String {
+	let mut connected = vec![false; graph.len()];
+	let mut lines = Vec::new();
+	for edge in graph.edges() {
+		connected[edge.start] = true;
+		connected[edge.end] = true;
+		lines.push(format!("{} {}", edge.start, edge.end));
+	}
+	for (id, _) in connected.iter().enumerate().filter(|(_, connected)| !**connected) {
+		lines.push(id.to_string());
+	}
+	lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn edges(graph: &Graph) -> Vec<(usize, usize)> {
+		graph.edges().iter().map(|e| (e.start, e.end)).collect()
+	}
+
+	#[test]
+	fn uses_integer_names_as_ids() {
+		// as written by NetworkX (with edge data), with an isolated vertex and a gap
+		let graph = read_edge_list("# comment\n0 1 {'weight': 3}\n1,2\n\n5\n2 0 # back\r\n").unwrap();
+		assert_eq!(graph.len(), 6);
+		assert_eq!(edges(&graph), [(0, 1), (1, 2), (2, 0)]);
+		assert!(graph.vertices.iter().all(|v| v.label.is_none()));
+	}
+
+	#[test]
+	fn names_vertices_in_order_of_appearance() {
+		let graph = read_edge_list("a b\nb c\nd\n").unwrap();
+		let labels = graph.vertices.iter().map(|v| v.label.as_deref()).collect::<Vec<_>>();
+		assert_eq!(labels, [Some("a"), Some("b"), Some("c"), Some("d")]);
+		assert_eq!(edges(&graph), [(0, 1), (1, 2)]);
+
+		// integers with large gaps are names (but no labels)
+		let graph = read_edge_list("1000 2000\n").unwrap();
+		assert_eq!(graph.len(), 2);
+		assert!(graph.vertices.iter().all(|v| v.label.is_none()));
+	}
+
+	#[test]
+	fn writes_what_it_reads() {
+		let graph = read_edge_list("0 1\n1 2\n4\n").unwrap();
+		let read = read_edge_list(&write_edge_list(&graph)).unwrap();
+		assert_eq!(read.len(), 5);
+		assert_eq!(edges(&read), edges(&graph));
+	}
+}
@@ -0,0 +1,113 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+	common::{Number, Vector},
+	io::dto::DataDto,
+	model::{Classes, ConnectionOrientation, VertexId},
+};
+
+/// A vertex of a [Graph] with whatever the file knows about it.
+#[derive(Clone, Default)]
+pub struct GraphVertex {
+	pub label: Option<String>,
+	pub position: Option<Vector>,
+}
+
+/// An edge of a [Graph] with the geometry of its connection if the file knows it.
+///
+/// The orientation refers to the direction from start to end.
+#[derive(Clone)]
+pub struct GraphEdge {
+	pub start: VertexId,
+	pub end: VertexId,
+	pub orientation: Option<ConnectionOrientation>,
+	pub radius: Option<Number>,
+}
+impl GraphEdge {
+	pub fn new(start: VertexId, end: VertexId) -> Self {
+		Self { start, end, orientation: None, radius: None }
+	}
+}
+
+/// A plain undirected graph as read from or written to a file.
+///
+/// Loops and duplicate edges (in either direction) are dropped when they are added.
+#[derive(Clone, Default)]
+pub struct Graph {
+	pub vertices: Vec<GraphVertex>,
+	edges: Vec<GraphEdge>,
+	known: HashSet<(VertexId, VertexId)>,
+}
+impl Graph {
+	/// A graph with the given number of (unlabeled) vertices and no edges.
+	pub fn with_vertices(count: usize) -> Self {
+		Self { vertices: vec![GraphVertex::default(); count], ..Default::default() }
+	}
+
+	pub fn len(&self) -> usize {
+		self.vertices.len()
+	}
+	pub fn edges(&self) -> &Vec<GraphEdge> {
+		&self.edges
+	}
+
+	pub fn add_vertex(&mut self, vertex: GraphVertex) -> VertexId {
+		self.vertices.push(vertex);
+		self.vertices.len() - 1
+	}
+	/// Adds the edge unless it is a loop or already exists.
+	///
+	/// Fails if either vertex doesn't exist.
+	pub fn add_edge(&mut self, edge: GraphEdge) -> Result<(), ()> {
+		let (a, b) = (edge.start, edge.end);
+		if a >= self.len() || b >= self.len() {
+			return Err(());
+		}
+		if a != b && self.known.insert((a.min(b), a.max(b))) {
+			self.edges.push(edge);
+		}
+		Ok(())
+	}
+}
+impl From<&DataDto> for Graph {
+	/// The graph of the drawing with all positions, labels and the geometry of all connections.
+	fn from(data: &DataDto) -> Self {
+		let classes = Classes::from(data.sizes.clone());
+		let mut graph = Graph::default();
+		for vertex in data.vertices.iter() {
+			graph.add_vertex(GraphVertex { label: vertex.label.clone(), position: Some(vertex.position) });
+		}
+		for &(start, end, ref connection) in &data.connections {
+			let edge = GraphEdge {
+				start,
+				end,
+				orientation: Some(connection.orientation.into()),
+				radius: Some(classes.get_size(connection.size)),
+			};
+			let _ = graph.add_edge(edge);
+		}
+		graph
+	}
+}
+
+/// Assigns vertex ids to the names vertices have in a file (in order of appearance).
+#[derive(Default)]
+pub(super) struct VertexNames {
+	ids: HashMap<String, VertexId>,
+}
+impl VertexNames {
+	/// The id of the named vertex, which is added to the graph when it is seen for the first time.
+	///
+	/// The name becomes the label of the vertex unless it looks generated (like `12`, `n12` or `v12`).
+	pub fn id(&mut self, graph: &mut Graph, name: &str) -> VertexId {
+		if let Some(&id) = self.ids.get(name) {
+			return id;
+		}
+		let digits = name.strip_prefix(['n', 'v']).unwrap_or(name);
+		let generated = !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit());
+		let label = (!generated).then(|| name.to_owned());
+		let id = graph.add_vertex(GraphVertex { label, position: None });
+		self.ids.insert(name.to_owned(), id);
+		id
+	}
+}
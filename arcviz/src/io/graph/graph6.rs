@@ -0,0 +1,148 @@
+use crate::io::LoadError;
+
+use super::{Graph, GraphEdge};
+
+/// The optional header of graph6 files.
+const HEADER: &str = ">>graph6<<";
+/// The most vertices a graph may have, larger counts are rejected before anything is allocated.
+const MAX_VERTICES: u64 = 100_000;
+
+/// Reads a single graph in the graph6 format (see <https://users.cecs.anu.edu.au/~bdm/data/formats.txt>).
+///
+/// The sparse6 and digraph6 variants are not supported.
+pub fn read_graph6(line: &str) -> Result<Graph, LoadError> {
+	let line = line.trim();
+	let bytes = line.strip_prefix(HEADER).unwrap_or(line).as_bytes();
+	if let [b':' | b'&', ..] = bytes {
+		return Err(LoadError::new("sparse6 and digraph6 are not supported"));
+	}
+	if let Some(b) = bytes.iter().find(|b| !(63..=126).contains(*b)) {
+		return Err(LoadError::new(format!("invalid character {:?}", *b as char)));
+	}
+	let value = |bytes: &[u8]| bytes.iter().fold(0u64, |value, b| value << 6 | (b - 63) as u64);
+	let (count, rest) = match bytes {
+		[126, 126, rest @ ..] if rest.len() >= 6 => (value(&rest[..6]), &rest[6..]),
+		[126, rest @ ..] if rest.len() >= 3 => (value(&rest[..3]), &rest[3..]),
+		[n, rest @ ..] if *n != 126 => (value(&[*n]), rest),
+		_ => return Err(LoadError::new("incomplete number of vertices")),
+	};
+	let too_many = || LoadError::new(format!("too many vertices ({}, at most {})", count, MAX_VERTICES));
+	if count > MAX_VERTICES {
+		return Err(too_many());
+	}
+	let count = usize::try_from(count).map_err(|_| too_many())?;
+	// the upper triangle of the adjacency matrix column by column, 6 bits per byte
+	let bits = count.checked_mul(count.saturating_sub(1)).ok_or_else(too_many)? / 2;
+	if rest.len() != (bits + 5) / 6 {
+		let message =
+			format!("{} characters of edges for {} vertices, expected {}", rest.len(), count, (bits + 5) / 6);
+		return Err(LoadError::new(message));
+	}
+
+	let mut graph = Graph::with_vertices(count);
+	let mut k = 0;
+	for j in 1..count {
+		for i in 0..j {
+			if (rest[k / 6] - 63) >> (5 - k % 6) & 1 == 1 {
+				graph.add_edge(GraphEdge::new(i, j)).map_err(|()| LoadError::new("invalid edge"))?;
+			}
+			k += 1;
+		}
+	}
+	Ok(graph)
+}
+
+/// Reads all graphs of a graph6 file (one per line, like the output of nauty's geng).
+///
+/// Fails with the line of the first invalid graph.
+pub fn read_graph6_all(text: &str) -> Result<Vec<Graph>, LoadError> {
+	let lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+	lines.map(|(i, line)| read_graph6(line).map_err(|e| LoadError { position: Some((i + 1, 1)), ..e })).collect()
+}
+
+/// Writes the graph in the graph6 format (a single line without header, see [read_graph6]).
+pub fn write_graph6(graph: &Graph) -> String {
+	let count = graph.len();
+	let mut bytes = match count {
+		0..=62 => vec![count as u8],
+		// 6 bits per byte, the header bytes (63) become 126
+		63..=258047 => [63].into_iter().chain((0..3).rev().map(|i| (count >> (6 * i)) as u8)).collect(),
+		_ => [63, 63].into_iter().chain((0..6).rev().map(|i| (count >> (6 * i)) as u8)).collect(),
+	};
+	let header = bytes.len();
+
+	let bits = count * count.saturating_sub(1) / 2;
+	bytes.resize(header + (bits + 5) / 6, 0);
+	for edge in graph.edges() {
+		let (i, j) = (edge.start.min(edge.end), edge.start.max(edge.end));
+		let k = j * (j - 1) / 2 + i;
+		bytes[header + k / 6] |= 1 << (5 - k % 6);
+	}
+	bytes.iter().map(|b| ((b & 63) + 63) as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn edges(graph: &Graph) -> Vec<(usize, usize)> {
+		graph.edges().iter().map(|e| (e.start, e.end)).collect()
+	}
+
+	#[test]
+	fn reads_known_graphs() {
+		// the example of the format description
+		let graph = read_graph6("DQc").unwrap();
+		assert_eq!(graph.len(), 5);
+		assert_eq!(edges(&graph), [(0, 2), (1, 3), (0, 4), (3, 4)]);
+
+		// the Petersen graph (3-regular with 15 edges)
+		let graph = read_graph6(">>graph6<<IheA@GUAo").unwrap();
+		assert_eq!(graph.len(), 10);
+		assert_eq!(graph.edges().len(), 15);
+		for vertex in 0..10 {
+			assert_eq!(graph.edges().iter().filter(|e| e.start == vertex || e.end == vertex).count(), 3);
+		}
+	}
+
+	#[test]
+	fn reads_every_graph_of_a_file() {
+		let graphs = read_graph6_all(">>graph6<<Bw\n\n@\nDQc\r\n").unwrap();
+		assert_eq!(graphs.iter().map(|g| (g.len(), g.edges().len())).collect::<Vec<_>>(), [(3, 3), (1, 0), (5, 4)]);
+
+		let error = read_graph6_all("Bw\n\nDQ\n").err().unwrap();
+		assert_eq!(error.position, Some((3, 1)));
+		assert_eq!(error.message, "1 characters of edges for 5 vertices, expected 2");
+	}
+
+	#[test]
+	fn rejects_too_many_vertices_from_the_header() {
+		// 258047 and 2^36 - 1 vertices, without the characters for their edges
+		for line in ["~~~~", "~~~~~~~~"] {
+			assert!(read_graph6(line).err().unwrap().message.starts_with("too many vertices"));
+		}
+		assert!(read_graph6("~").is_err());
+		assert_eq!(read_graph6(":Fa@x^").err().unwrap().message, "sparse6 and digraph6 are not supported");
+	}
+
+	#[test]
+	fn writes_what_it_reads() {
+		assert_eq!(write_graph6(&read_graph6("DQc").unwrap()), "DQc");
+
+		// more than 62 vertices need the long header
+		let mut graph = Graph::with_vertices(100);
+		for i in 0..99 {
+			graph.add_edge(GraphEdge::new(i, (7 * i + 3) % 100)).unwrap();
+		}
+		let text = write_graph6(&graph);
+		assert!(text.starts_with('~'));
+		let read = read_graph6(&text).unwrap();
+		assert_eq!(read.len(), 100);
+		let sorted = |graph: &Graph| {
+			let mut edges = edges(graph).into_iter().map(|(a, b)| (a.min(b), a.max(b))).collect::<Vec<_>>();
+			edges.sort();
+			edges
+		};
+		assert_eq!(sorted(&read), sorted(&graph));
+	}
+}
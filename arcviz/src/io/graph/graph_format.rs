@@ -0,0 +1,76 @@
+use crate::io::LoadError;
+
+use super::{
+	read_dot, read_edge_list, read_graph6_all, read_graphml, write_dot, write_edge_list, write_graph6,
+	write_graphml, Graph,
+};
+
+/// The formats of other graph tools that graphs can be imported from and exported to.
+#[derive(Copy, Clone, PartialEq)]
+pub enum GraphFormat {
+	/// One graph per line (see [super::read_graph6]).
+	Graph6,
+	/// One edge per line (see [read_edge_list]).
+	EdgeList,
+	/// The language of Graphviz (see [read_dot]).
+	Dot,
+	/// The xml format (see [read_graphml]).
+	GraphMl,
+}
+impl GraphFormat {
+	/// Picks the format by the extension of the given file name.
+	pub fn from_path(path: &str) -> Option<Self> {
+		let (_, extension) = path.rsplit_once('.')?;
+		match extension.to_lowercase().as_str() {
+			"g6" | "graph6" => Some(GraphFormat::Graph6),
+			"edges" | "edgelist" | "el" => Some(GraphFormat::EdgeList),
+			"dot" | "gv" => Some(GraphFormat::Dot),
+			"graphml" => Some(GraphFormat::GraphMl),
+			_ => None,
+		}
+	}
+
+	/// Guesses the format from the content of a file (e.g. when the file name is unknown).
+	pub fn detect(text: &str) -> Option<Self> {
+		let start = text.trim_start();
+		let keyword = start.split(|c: char| !c.is_alphabetic()).next().unwrap_or_default().to_lowercase();
+		match keyword.as_str() {
+			_ if start.starts_with('<') => text.contains("<graphml").then_some(GraphFormat::GraphMl),
+			"strict" | "graph" | "digraph" => Some(GraphFormat::Dot),
+			_ if read_graph6_all(text).is_ok_and(|graphs| !graphs.is_empty()) => Some(GraphFormat::Graph6),
+			_ if read_edge_list(text).is_ok_and(|graph| graph.len() > 0) => Some(GraphFormat::EdgeList),
+			_ => None,
+		}
+	}
+
+	/// Reads all graphs of the file (only graph6 files may contain more than one).
+	///
+	/// Fails with the reason the file is invalid (and where, if the format has lines).
+	pub fn read(&self, text: &str) -> Result<Vec<Graph>, LoadError> {
+		match self {
+			GraphFormat::Graph6 => read_graph6_all(text),
+			GraphFormat::EdgeList => Ok(vec![read_edge_list(text)?]),
+			GraphFormat::Dot => Ok(vec![read_dot(text)?]),
+			GraphFormat::GraphMl => Ok(vec![read_graphml(text)?]),
+		}
+	}
+
+	pub fn write(&self, graph: &Graph) -> String {
+		match self {
+			GraphFormat::Graph6 => format!("{}\n", write_graph6(graph)),
+			GraphFormat::EdgeList => write_edge_list(graph),
+			GraphFormat::Dot => write_dot(graph),
+			GraphFormat::GraphMl => write_graphml(graph),
+		}
+	}
+
+	/// The usual file extension.
+	pub fn extension(&self) -> &'static str {
+		match self {
+			GraphFormat::Graph6 => "g6",
+			GraphFormat::EdgeList => "edges",
+			GraphFormat::Dot => "dot",
+			GraphFormat::GraphMl => "graphml",
+		}
+	}
+}
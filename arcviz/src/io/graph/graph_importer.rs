@@ -0,0 +1,164 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::{
+	common::{Number, Vector, XorShift},
+	io::dto::{ConnectionDto, DataDto, VertexDto},
+	model::{Classes, ConnectionOrientation, SizeId},
+};
+
+use super::Graph;
+
+/// Where the vertices of an imported [Graph] are placed.
+#[derive(Copy, Clone, PartialEq)]
+pub enum InitialLayout {
+	/// Evenly spaced on a circle (in the order of the vertices).
+	Circle,
+	/// Row by row on a square grid.
+	Grid,
+	/// Uniformly distributed in a square (with the given seed).
+	Random(u64),
+}
+
+/// Turns [Graph]s into drawings.
+///
+/// If the file contained positions for all vertices those are used, otherwise the vertices are placed by the layout.
+/// Connections get the orientation and the class closest to the radius given in the file, or the defaults.
+pub struct GraphImporter {
+	pub classes: Classes,
+	pub layout: InitialLayout,
+	/// The distance between neighbors on the circle or grid (random layouts are about as dense as the grid).
+	pub spacing: Number,
+	/// The orientation of edges without one.
+	pub orientation: ConnectionOrientation,
+	/// The class of edges without radius.
+	pub class: SizeId,
+}
+impl Default for GraphImporter {
+	fn default() -> Self {
+		Self {
+			classes: Classes::default(),
+			layout: InitialLayout::Circle,
+			spacing: 100.0,
+			orientation: ConnectionOrientation::InnerRight,
+			class: 2,
+		}
+	}
+}
+impl GraphImporter {
+	pub fn import(&self, graph: &Graph) -> DataDto {
+		let mut data = DataDto::new((&self.classes).into());
+		for (vertex, position) in graph.vertices.iter().zip(self.positions(graph)) {
+			data.vertices.items.push(VertexDto { position, label: vertex.label.clone(), pinned: false, id: None });
+		}
+		for edge in graph.edges() {
+			let orientation = edge.orientation.unwrap_or(self.orientation).into();
+			let size = edge.radius.map(|radius| self.classes.closest(radius)).unwrap_or(self.class);
+			data.connections.push((edge.start, edge.end, ConnectionDto { orientation, size }));
+		}
+		data
+	}
+
+	/// The positions of the vertices of the graph.
+	pub fn positions(&self, graph: &Graph) -> Vec<Vector> {
+		if let Some(positions) = graph.vertices.iter().map(|v| v.position).collect::<Option<Vec<_>>>() {
+			return positions;
+		}
+
+		let count = graph.len();
+		let margin = Vector::new(self.spacing, self.spacing);
+		let columns = (count as Number).sqrt().ceil().max(1.0);
+		match self.layout {
+			InitialLayout::Circle => {
+				let radius = match count {
+					0 | 1 => 0.0,
+					_ => self.spacing / (2.0 * (PI / count as Number).sin()),
+				};
+				let center = margin + Vector::new(radius, radius);
+				(0..count)
+					.map(|i| center + radius * Vector::unit_from_angle(-PI / 2.0 + TAU * i as Number / count as Number))
+					.collect()
+			},
+			InitialLayout::Grid => (0..count)
+				.map(|i| {
+					let (row, column) = ((i as Number / columns).floor(), i as Number % columns);
+					margin + self.spacing * Vector::new(column, row)
+				})
+				.collect(),
+			InitialLayout::Random(seed) => {
+				let mut random = XorShift::new(seed);
+				let size = self.spacing * columns;
+				(0..count).map(|_| margin + size * Vector::new(random.unit(), random.unit())).collect()
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::io::graph::{GraphEdge, GraphVertex};
+
+	fn positions(importer: &GraphImporter, graph: &Graph) -> Vec<(Number, Number)> {
+		importer.positions(graph).iter().map(|p| (p.x, p.y)).collect()
+	}
+	fn importer(layout: InitialLayout) -> GraphImporter {
+		GraphImporter { layout, ..Default::default() }
+	}
+
+	#[test]
+	fn places_neighbors_on_the_circle_one_spacing_apart() {
+		let circle = importer(InitialLayout::Circle).positions(&Graph::with_vertices(6));
+		// the first vertex is at the top of the circle with radius 100 (inside the margin)
+		assert!((circle[0] - Vector::new(200.0, 100.0)).length() < 1e-9);
+		let center = Vector::new(200.0, 200.0);
+		for (i, position) in circle.iter().enumerate() {
+			assert!(((*position - center).length() - 100.0).abs() < 1e-9);
+			assert!(((*position - circle[(i + 1) % 6]).length() - 100.0).abs() < 1e-9);
+		}
+		assert_eq!(positions(&importer(InitialLayout::Circle), &Graph::with_vertices(1)), [(100.0, 100.0)]);
+	}
+
+	#[test]
+	fn places_the_grid_row_by_row() {
+		let grid = positions(&importer(InitialLayout::Grid), &Graph::with_vertices(5));
+		assert_eq!(grid, [(100.0, 100.0), (200.0, 100.0), (300.0, 100.0), (100.0, 200.0), (200.0, 200.0)]);
+	}
+
+	#[test]
+	fn places_random_vertices_by_the_seed() {
+		let graph = Graph::with_vertices(9);
+		let random = positions(&importer(InitialLayout::Random(7)), &graph);
+		assert_eq!(random, positions(&importer(InitialLayout::Random(7)), &graph));
+		assert_ne!(random, positions(&importer(InitialLayout::Random(8)), &graph));
+		// in the square of the grid with 3 columns
+		assert!(random.iter().all(|&(x, y)| (100.0..400.0).contains(&x) && (100.0..400.0).contains(&y)));
+	}
+
+	#[test]
+	fn keeps_positions_only_if_all_vertices_have_one() {
+		let mut graph = Graph::default();
+		graph.add_vertex(GraphVertex { label: None, position: Some(Vector::new(-5.0, 7.0)) });
+		graph.add_vertex(GraphVertex { label: None, position: Some(Vector::new(3.0, 4.0)) });
+		let grid = importer(InitialLayout::Grid);
+		assert_eq!(positions(&grid, &graph), [(-5.0, 7.0), (3.0, 4.0)]);
+
+		graph.add_vertex(GraphVertex::default());
+		assert_eq!(positions(&grid, &graph), [(100.0, 100.0), (200.0, 100.0), (100.0, 200.0)]);
+	}
+
+	#[test]
+	fn imports_edges_with_the_closest_class() {
+		let mut graph = Graph::with_vertices(3);
+		let mut edge = GraphEdge::new(0, 1);
+		edge.orientation = Some(ConnectionOrientation::OuterLeft);
+		edge.radius = Some(260.0);
+		graph.add_edge(edge).unwrap();
+		graph.add_edge(GraphEdge::new(1, 2)).unwrap();
+
+		let data = GraphImporter::default().import(&graph);
+		let connections = data.connections.iter();
+		let connections =
+			connections.map(|(a, b, c)| (*a, *b, ConnectionOrientation::from(c.orientation).name(), c.size));
+		assert_eq!(connections.collect::<Vec<_>>(), [(0, 1, "Left", 2), (1, 2, "right", 2)]);
+	}
+}
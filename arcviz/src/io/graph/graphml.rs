@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use crate::{
+	common::Vector,
+	io::{escape_xml, unescape_xml, xml_elements, LoadError, XmlElement},
+	model::ConnectionOrientation,
+};
+
+use super::{Graph, GraphEdge, VertexNames};
+
+/// The keys written by [write_graphml] (id, domain, name, type).
+const KEYS: [(&str, &str, &str, &str); 5] = [
+	("label", "node", "label", "string"),
+	("x", "node", "x", "double"),
+	("y", "node", "y", "double"),
+	("orientation", "edge", "orientation", "string"),
+	("radius", "edge", "radius", "double"),
+];
+
+/// The elements in the xml.
+fn elements(xml: &str) -> Result<Vec<XmlElement>, LoadError> {
+	xml_elements(xml).map_err(|()| LoadError::new("invalid xml"))
+}
+
+/// The value of the attribute the element must have.
+fn required<'a>(element: &'a XmlElement, name: &str) -> Result<&'a str, LoadError> {
+	let message = || LoadError::new(format!("a {} without {}", element.name, name));
+	element.attribute(name).ok_or_else(message)
+}
+
+/// The data of the element by the name of its key.
+fn data(keys: &HashMap<String, String>, element: &XmlElement) -> Result<HashMap<String, String>, LoadError> {
+	let mut data = HashMap::new();
+	for item in elements(element.content)?.into_iter().filter(|e| e.name == "data") {
+		let key = required(&item, "key")?;
+		let name = keys.get(key).cloned().unwrap_or(key.to_owned());
+		data.insert(name, unescape_xml(item.content.trim()));
+	}
+	Ok(data)
+}
+
+/// Reads the first graph of a GraphML file (as written by e.g. NetworkX).
+///
+/// Directed edges are read as undirected ones, nested graphs and hyperedges are ignored.
+/// Node ids become labels (unless they look generated or there is a `label` key), `x` and `y` keys become positions.
+/// The `orientation` and `radius` keys of edges are kept as well.
+pub fn read_graphml(text: &str) -> Result<Graph, LoadError> {
+	let root = elements(text)?.into_iter().find(|e| e.name == "graphml");
+	let root = root.ok_or_else(|| LoadError::new("no graphml element"))?;
+	let children = elements(root.content)?;
+	let keys = children
+		.iter()
+		.filter(|e| e.name == "key")
+		.flat_map(|e| Some((e.attribute("id")?.to_owned(), e.attribute("attr.name")?.to_owned())))
+		.collect::<HashMap<_, _>>();
+	let graph_element = children.iter().find(|e| e.name == "graph");
+	let graph_element = graph_element.ok_or_else(|| LoadError::new("no graph element"))?;
+	let items = elements(graph_element.content)?;
+
+	let mut graph = Graph::default();
+	let mut names = VertexNames::default();
+	// nodes first, since edges may refer to nodes declared later
+	for node in items.iter().filter(|e| e.name == "node") {
+		let vertex = names.id(&mut graph, required(node, "id")?);
+		let data = data(&keys, node)?;
+		if let Some(label) = data.get("label") {
+			graph.vertices[vertex].label = Some(label.clone());
+		}
+		let coordinate = |name: &str| data.get(name)?.parse().ok();
+		if let (Some(x), Some(y)) = (coordinate("x"), coordinate("y")) {
+			graph.vertices[vertex].position = Some(Vector::new(x, y));
+		}
+	}
+	for edge in items.iter().filter(|e| e.name == "edge") {
+		let a = names.id(&mut graph, required(edge, "source")?);
+		let b = names.id(&mut graph, required(edge, "target")?);
+		let data = data(&keys, edge)?;
+		let mut imported = GraphEdge::new(a, b);
+		imported.orientation = data.get("orientation").and_then(|o| ConnectionOrientation::from_name(o));
+		imported.radius = data.get("radius").and_then(|r| r.parse().ok());
+		graph.add_edge(imported).map_err(|()| LoadError::new("invalid edge"))?;
+	}
+	Ok(graph)
+}
+
+/// Writes the graph as GraphML (see [read_graphml]).
+///
+/// Vertices are named `n0`, `n1`, .. and carry their label and position,
+/// edges their `orientation` (as in the text format, from source to target) and `radius`.
+pub fn write_graphml(graph: &Graph) -> String {
+	let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+	xml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+	for (id, domain, name, kind) in KEYS {
+		xml.push_str(&format!(
+			"\t<key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>\n",
+			id, domain, name, kind
+		));
+	}
+	xml.push_str("\t<graph edgedefault=\"undirected\">\n");
+	let item = |key: &str, value: String| format!("<data key=\"{}\">{}</data>", key, escape_xml(&value));
+	for (id, vertex) in graph.vertices.iter().enumerate() {
+		let mut content = String::new();
+		if let Some(label) = &vertex.label {
+			content.push_str(&item("label", label.clone()));
+		}
+		if let Some(position) = vertex.position {
+			content.push_str(&item("x", position.x.to_string()));
+			content.push_str(&item("y", position.y.to_string()));
+		}
+		xml.push_str(&format!("\t\t<node id=\"n{}\">{}</node>\n", id, content));
+	}
+	for edge in graph.edges() {
+		let mut content = String::new();
+		if let Some(orientation) = edge.orientation {
+			content.push_str(&item("orientation", orientation.name().to_owned()));
+		}
+		if let Some(radius) = edge.radius {
+			content.push_str(&item("radius", radius.to_string()));
+		}
+		let (start, end) = (edge.start, edge.end);
+		xml.push_str(&format!("\t\t<edge source=\"n{}\" target=\"n{}\">{}</edge>\n", start, end, content));
+	}
+	xml.push_str("\t</graph>\n</graphml>\n");
+	xml
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::common::Number;
+
+	/// As written by NetworkX (with positions, a label and edge weights).
+	const GRAPHML: &str = r#"<?xml version='1.0' encoding='utf-8'?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">
+  <key id="d3" for="node" attr.name="label" attr.type="string" />
+  <key id="d2" for="edge" attr.name="weight" attr.type="long" />
+  <key id="d1" for="node" attr.name="y" attr.type="double" />
+  <key id="d0" for="node" attr.name="x" attr.type="double" />
+  <graph edgedefault="undirected">
+    <!-- a comment -->
+    <node id="Alice">
+      <data key="d0">1.5</data>
+      <data key="d1">-2.0</data>
+    </node>
+    <node id="Bob" />
+    <node id="0" />
+    <node id="n5">
+      <data key="d3">R&amp;D</data>
+    </node>
+    <edge source="Alice" target="Bob">
+      <data key="d2">3</data>
+    </edge>
+    <edge source="Bob" target="0" />
+    <edge source="0" target="Carol" />
+  </graph>
+</graphml>
+"#;
+
+	fn labels(graph: &Graph) -> Vec<Option<String>> {
+		graph.vertices.iter().map(|v| v.label.clone()).collect()
+	}
+	fn positions(graph: &Graph) -> Vec<Option<(Number, Number)>> {
+		graph.vertices.iter().map(|v| v.position.map(|p| (p.x, p.y))).collect()
+	}
+	fn edges(graph: &Graph) -> Vec<(usize, usize, Option<&'static str>, Option<Number>)> {
+		graph.edges().iter().map(|e| (e.start, e.end, e.orientation.map(|o| o.name()), e.radius)).collect()
+	}
+
+	#[test]
+	fn reads_networkx_files() {
+		let graph = read_graphml(GRAPHML).unwrap();
+		let names = ["Alice", "Bob", "", "R&D", "Carol"].map(|n| (!n.is_empty()).then(|| n.to_owned()));
+		assert_eq!(labels(&graph), names);
+		assert_eq!(positions(&graph), [Some((1.5, -2.0)), None, None, None, None]);
+		assert_eq!(edges(&graph), [(0, 1, None, None), (1, 2, None, None), (2, 4, None, None)]);
+	}
+
+	#[test]
+	fn reports_why_files_are_invalid() {
+		let message = |xml: &str| read_graphml(xml).err().unwrap().message;
+		assert_eq!(message("<graph></graph>"), "no graphml element");
+		assert_eq!(message("<graphml><graph><node/></graph></graphml>"), "a node without id");
+		assert_eq!(message("<graphml><graph><node id=\"a\"></graph></graphml>"), "invalid xml");
+	}
+
+	#[test]
+	fn writes_what_it_reads() {
+		let mut graph = read_graphml(GRAPHML).unwrap();
+		graph.vertices[2].label = Some("<\"quoted\">".to_owned());
+		let mut edge = GraphEdge::new(3, 4);
+		edge.orientation = Some(ConnectionOrientation::OuterRight);
+		edge.radius = Some(120.25);
+		graph.add_edge(edge).unwrap();
+
+		let read = read_graphml(&write_graphml(&graph)).unwrap();
+		assert_eq!(labels(&read), labels(&graph));
+		assert_eq!(positions(&read), positions(&graph));
+		assert_eq!(edges(&read), edges(&graph));
+	}
+}
@@ -0,0 +1,25 @@
+//! Plain graphs in the formats of other graph tools (graph6, edge lists, DOT, GraphML).
+//!
+//! These formats only describe the combinatorial graph (sometimes with labels or positions),
+//! so a [GraphImporter] decides about the geometry when turning a [Graph] into a [crate::io::dto::DataDto].
+
+mod graph;
+pub use graph::*;
+
+mod graph_format;
+pub use graph_format::*;
+
+mod graph_importer;
+pub use graph_importer::*;
+
+mod graph6;
+pub use graph6::*;
+
+mod edge_list;
+pub use edge_list::*;
+
+mod dot;
+pub use dot::*;
+
+mod graphml;
+pub use graphml::*;
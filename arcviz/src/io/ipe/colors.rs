@@ -0,0 +1,44 @@
+use crate::common::Number;
+
+/// The css color keywords used by this application (and some other common ones) as rgb in [0, 1].
+const NAMED_COLORS: &[(&str, (u8, u8, u8))] = &[
+	("black", (0, 0, 0)),
+	("white", (255, 255, 255)),
+	("grey", (128, 128, 128)),
+	("gray", (128, 128, 128)),
+	("red", (255, 0, 0)),
+	("firebrick", (178, 34, 34)),
+	("orange", (255, 165, 0)),
+	("yellow", (255, 255, 0)),
+	("gold", (255, 215, 0)),
+	("green", (0, 128, 0)),
+	("lime", (0, 255, 0)),
+	("teal", (0, 128, 128)),
+	("turquoise", (64, 224, 208)),
+	("cyan", (0, 255, 255)),
+	("blue", (0, 0, 255)),
+	("navy", (0, 0, 128)),
+	("purple", (128, 0, 128)),
+	("magenta", (255, 0, 255)),
+	("pink", (255, 192, 203)),
+	("brown", (165, 42, 42)),
+];
+
+/// Converts a css color (keyword, #rgb or #rrggbb) to rgb values in [0, 1].
+pub fn css_to_rgb(color: &str) -> Option<(Number, Number, Number)> {
+	let (r, g, b) = match color.strip_prefix('#') {
+		// the digits are sliced by bytes below
+		Some(hex) if !hex.is_ascii() => return None,
+		Some(hex) if hex.len() == 3 => {
+			let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|d| d * 17);
+			(digit(0)?, digit(1)?, digit(2)?)
+		},
+		Some(hex) if hex.len() == 6 => {
+			let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+			(byte(0)?, byte(2)?, byte(4)?)
+		},
+		Some(_) => return None,
+		None => NAMED_COLORS.iter().find(|(name, _)| name.eq_ignore_ascii_case(color))?.1,
+	};
+	Some((r as Number / 255.0, g as Number / 255.0, b as Number / 255.0))
+}
@@ -1,13 +1,29 @@
+use std::collections::BTreeSet;
+
 use ncollide2d::na::{self, Affine2, Scale2, Translation2};
 
 use crate::{
 	common::{Number, Vector},
+	io::{escape_latex, escape_xml},
+	model::Classes,
 	render::RenderTarget,
 };
 
+use super::colors::css_to_rgb;
+
 const FILE_TEMPLATE: &str = include_str!("./export_template.ipe");
 const VERTEX_TEMPLATE: &str = include_str!("./vertex_template.ipe");
 const ARC_TEMPLATE: &str = include_str!("./arc_template.ipe");
+const SEGMENT_TEMPLATE: &str = include_str!("./segment_template.ipe");
+const CONFLICT_TEMPLATE: &str = include_str!("./conflict_template.ipe");
+const LABEL_TEMPLATE: &str = include_str!("./label_template.ipe");
+/// The pen used for connections.
+const CONNECTION_PEN: Number = 1.2;
+/// The transformation from Ipe page coordinates to drawing coordinates.
+pub(super) fn page_pose() -> Affine2<Number> {
+	let pose: Affine2<Number> = na::convert(Scale2::new(3.125, -3.125));
+	pose * Translation2::new(0.0, -832.0)
+}
 fn fill_number(original: &str, placeholder: &str, value: Number) -> String {
 	original.replace(placeholder, format!("{:.4}", value).as_str())
 }
@@ -17,45 +33,68 @@ fn fill_str(original: &str, placeholder: &str, value: &str) -> String {
 
 /// A [RenderTarget] used to export the data to a representation in the .ipe format used by the ['Ipe extensible drawing editor'](https://ipe.otfried.org/).
 /// To export render the [crate::model::Data] to an [IpeExporter] and then use the [IpeExporter::to_string] method to get the file.
-/// (Since Ipe is a general purpose drawing editor that supports lots of constructs that have nothing to do with this application, there is no meaningful way to import from that format again.)
+/// (Since Ipe is a general purpose drawing editor that supports lots of constructs that have nothing to do with this application,
+/// only the objects written here can be imported again, see [super::IpeImporter].)
+///
+/// The file contains an additional stylesheet ("arcviz") that defines all colors used (as in the app),
+/// a color and a pen per class (`class0`, `class1`, ..) and puts labels, conflicts and unreachable connections on separate layers.
 pub struct IpeExporter {
 	content: String,
 	pose: Affine2<Number>,
+	class_colors: Vec<String>,
+	/// The (css) colors used so far, that need to be defined in the stylesheet.
+	colors: BTreeSet<String>,
+	/// The colors that couldn't be converted (and were replaced by black).
+	unknown_colors: BTreeSet<String>,
 }
 impl Default for IpeExporter {
 	fn default() -> Self {
-		let pose: Affine2<Number> = na::convert(Scale2::new(3.125, -3.125));
-		let pose = pose * Translation2::new(0.0, -832.0);
-		Self { content: Default::default(), pose }
+		Self::new(&Classes::default())
 	}
 }
 impl IpeExporter {
+	pub fn new(classes: &Classes) -> Self {
+		let pose = page_pose();
+		let class_colors = (0..classes.len()).map(|i| classes.get_color(i).to_owned()).collect::<Vec<_>>();
+		let unknown_colors = class_colors.iter().filter(|color| css_to_rgb(color).is_none()).cloned().collect();
+		Self { content: Default::default(), pose, class_colors, colors: BTreeSet::new(), unknown_colors }
+	}
+
+	/// The colors that are not known css colors (Ipe would reject them, so they are exported as black).
+	pub fn unknown_colors(&self) -> impl Iterator<Item = &String> {
+		self.unknown_colors.iter()
+	}
+
 	fn transform_point(&self, point: Vector) -> Vector {
 		self.pose.inverse_transform_point(&point.into()).into()
 	}
-}
-impl ToString for IpeExporter {
-	fn to_string(&self) -> String {
-		FILE_TEMPLATE.replace("{{{content}}}", &self.content)
-	}
-}
 
-impl RenderTarget for IpeExporter {
-	fn draw_vertex(&mut self, center: crate::common::Vector, color: &str, selected: bool) {
-		let center = self.transform_point(center);
-		let vertex_string = fill_number(VERTEX_TEMPLATE, "{{{x}}}", center.x);
-		let vertex_string = fill_number(&vertex_string, "{{{y}}}", center.y);
-		let vertex_string = fill_str(&vertex_string, "{{{color}}}", color);
-		self.content.push_str(&vertex_string);
+	/// The value to use for the given css color in a color attribute.
+	///
+	/// Named colors are referenced by name (and defined in the stylesheet), others are given as absolute rgb value.
+	/// Unknown colors are replaced by black.
+	fn color(&mut self, color: &str) -> String {
+		match css_to_rgb(color) {
+			Some(_) if !color.starts_with('#') => {
+				self.colors.insert(color.to_owned());
+				color.to_owned()
+			},
+			Some((r, g, b)) => format!("{:.3} {:.3} {:.3}", r, g, b),
+			None => {
+				self.unknown_colors.insert(color.to_owned());
+				"black".to_owned()
+			},
+		}
 	}
-	fn draw_connection_arc(
+
+	fn arc(
 		&mut self,
-		center: crate::common::Vector,
-		radius: crate::common::Number,
-		rotation: crate::common::Number,
-		angle: crate::common::Number,
+		center: Vector,
+		radius: Number,
+		rotation: Number,
+		angle: Number,
 		color: &str,
-		selected: bool,
+		attributes: &str,
 	) {
 		let start = center + radius * Vector::unit_from_angle(rotation);
 		let end = center + radius * Vector::unit_from_angle(rotation + angle);
@@ -77,10 +116,56 @@ impl RenderTarget for IpeExporter {
 		let arc_string = fill_number(&arc_string, "{{{end_x}}}", end.x);
 		let arc_string = fill_number(&arc_string, "{{{end_y}}}", end.y);
 		let arc_string = fill_str(&arc_string, "{{{color}}}", color);
+		let arc_string = fill_str(&arc_string, "{{{attributes}}}", attributes);
 
 		self.content.push_str(&arc_string);
 	}
 
+	fn style(&self) -> String {
+		let mut style = String::new();
+		for color in &self.colors {
+			let Some((r, g, b)) = css_to_rgb(color) else { continue };
+			style.push_str(&format!("<color name=\"{}\" value=\"{:.3} {:.3} {:.3}\"/>\n", color, r, g, b));
+		}
+		for (i, color) in self.class_colors.iter().enumerate() {
+			let (r, g, b) = css_to_rgb(color).unwrap_or((0.0, 0.0, 0.0));
+			style.push_str(&format!("<color name=\"class{}\" value=\"{:.3} {:.3} {:.3}\"/>\n", i, r, g, b));
+			style.push_str(&format!("<pen name=\"class{}\" value=\"{}\"/>\n", i, CONNECTION_PEN));
+		}
+		style
+	}
+}
+impl ToString for IpeExporter {
+	fn to_string(&self) -> String {
+		FILE_TEMPLATE.replace("{{{style}}}", &self.style()).replace("{{{content}}}", &self.content)
+	}
+}
+
+impl RenderTarget for IpeExporter {
+	fn draw_vertex(&mut self, center: crate::common::Vector, color: &str, selected: bool) {
+		let center = self.transform_point(center);
+		let color = self.color(color);
+		let vertex_string = fill_number(VERTEX_TEMPLATE, "{{{x}}}", center.x);
+		let vertex_string = fill_number(&vertex_string, "{{{y}}}", center.y);
+		let vertex_string = fill_str(&vertex_string, "{{{color}}}", &color);
+		self.content.push_str(&vertex_string);
+	}
+	fn draw_connection_arc(
+		&mut self,
+		center: crate::common::Vector,
+		radius: crate::common::Number,
+		rotation: crate::common::Number,
+		angle: crate::common::Number,
+		color: &str,
+		selected: bool,
+	) {
+		let (color, attributes) = match self.class_colors.iter().position(|c| c == color) {
+			Some(class) => (format!("class{}", class), format!(" layer=\"alpha\" pen=\"class{}\"", class)),
+			None => (self.color(color), format!(" layer=\"alpha\" pen=\"{}\"", CONNECTION_PEN)),
+		};
+		self.arc(center, radius, rotation, angle, &color, &attributes);
+	}
+
 	fn draw_grid_line(
 		&mut self,
 		start: &crate::common::Vector,
@@ -89,20 +174,55 @@ impl RenderTarget for IpeExporter {
 		color: &str,
 	) {
 	}
-	fn draw_label(&mut self, anchor: crate::common::Vector, text: &str) {}
-	fn draw_conflict(&mut self, center: crate::common::Vector, color: &str, selected: bool) {}
+	fn draw_connection_highlight(
+		&mut self,
+		center: crate::common::Vector,
+		radius: crate::common::Number,
+		rotation: crate::common::Number,
+		angle: crate::common::Number,
+		color: &str,
+	) {
+		let color = self.color(color);
+		let attributes = " layer=\"conflicts\" pen=\"ultrafat\" opacity=\"50%\"";
+		self.arc(center, radius, rotation, angle, &color, attributes);
+	}
+	fn draw_label(&mut self, anchor: crate::common::Vector, text: &str) {
+		let anchor = self.transform_point(anchor + Vector::new(8.0, -8.0));
+		let label_string = fill_number(LABEL_TEMPLATE, "{{{x}}}", anchor.x);
+		let label_string = fill_number(&label_string, "{{{y}}}", anchor.y);
+		let label_string = fill_str(&label_string, "{{{text}}}", &escape_xml(&escape_latex(text)));
+		self.content.push_str(&label_string);
+	}
+	fn draw_conflict(&mut self, center: crate::common::Vector, color: &str, selected: bool) {
+		let center = self.transform_point(center);
+		let color = self.color(color);
+		let conflict_string = fill_number(CONFLICT_TEMPLATE, "{{{x}}}", center.x);
+		let conflict_string = fill_number(&conflict_string, "{{{y}}}", center.y);
+		let conflict_string = fill_str(&conflict_string, "{{{color}}}", &color);
+		self.content.push_str(&conflict_string);
+	}
 	fn draw_connection_invalid(
 		&mut self,
 		start: crate::common::Vector,
 		end: crate::common::Vector,
 		selected: bool,
 	) {
+		let start = self.transform_point(start);
+		let end = self.transform_point(end);
+		let color = self.color("firebrick");
+		let segment_string = fill_number(SEGMENT_TEMPLATE, "{{{start_x}}}", start.x);
+		let segment_string = fill_number(&segment_string, "{{{start_y}}}", start.y);
+		let segment_string = fill_number(&segment_string, "{{{end_x}}}", end.x);
+		let segment_string = fill_number(&segment_string, "{{{end_y}}}", end.y);
+		let segment_string = fill_str(&segment_string, "{{{color}}}", &color);
+		self.content.push_str(&segment_string);
 	}
 	fn draw_aid_line(&mut self, from: crate::common::Vector, to: crate::common::Vector) {}
 	fn draw_aid_box(&mut self, from: crate::common::Vector, to: crate::common::Vector) {}
 	fn clear_region(&mut self, start: &crate::common::Vector, size: &crate::common::Vector) {}
 	fn clear(&mut self) {
 		self.content = String::new();
+		self.colors.clear();
 	}
 	fn reset(&mut self) {}
 }
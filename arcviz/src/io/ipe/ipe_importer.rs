@@ -0,0 +1,261 @@
+use std::{
+	f64::consts::{PI, TAU},
+	ops::Range,
+};
+
+use ncollide2d::na::{Affine2, Matrix3};
+
+use crate::{
+	common::{Number, Vector},
+	io::{
+		dto::{ConnectionDto, DataDto, VertexDto},
+		unescape_latex, unescape_xml, xml_elements, XmlElement,
+	},
+	model::{Classes, ConnectionOrientation, VertexId},
+};
+
+use super::page_pose;
+
+/// The offset of labels from their vertex (see [super::IpeExporter]).
+const LABEL_OFFSET: Vector = Vector { x: 8.0, y: -8.0 };
+
+/// The result of an [IpeImporter::import].
+pub struct IpeImport {
+	pub data: DataDto,
+	/// Human readable notes about objects that were skipped or only partially understood.
+	pub notes: Vec<String>,
+}
+
+/// Reads drawings back from .ipe files written by the [super::IpeExporter] (and edited in Ipe afterwards).
+///
+/// Only the objects the exporter produces are recognized:
+/// - disk marks become vertices (teal ones pinned)
+/// - text objects next to a vertex become its label
+/// - circular arcs between 2 vertices become connections (the class with the closest radius, the orientation that reproduces the arc)
+/// - dashed segments between 2 vertices become (unreachable) connections
+///
+/// Everything on the "conflicts" layer is derived data and ignored.
+/// Anything else is skipped and reported in [IpeImport::notes].
+pub struct IpeImporter {
+	pub classes: Classes,
+	/// How far (in drawing units) an arc endpoint or label may be from its vertex.
+	pub tolerance: Number,
+}
+impl Default for IpeImporter {
+	fn default() -> Self {
+		Self { classes: Classes::default(), tolerance: 5.0 }
+	}
+}
+
+fn numbers(text: &str) -> Option<Vec<Number>> {
+	text.split_whitespace().map(|n| n.parse().ok()).collect()
+}
+
+/// The path operations this importer understands (in drawing coordinates).
+enum Segment {
+	/// start, end
+	Line(Vector, Vector),
+	/// start, end, center, radius, whether the angle increases from start to end
+	Arc(Vector, Vector, Vector, Number, bool),
+}
+
+fn linear_determinant(transform: &Affine2<Number>) -> Number {
+	let m = transform.matrix();
+	m[(0, 0)] * m[(1, 1)] - m[(0, 1)] * m[(1, 0)]
+}
+
+impl IpeImporter {
+	pub fn new(classes: Classes, tolerance: Number) -> Self {
+		Self { classes, tolerance }
+	}
+
+	/// Reads the first page of the given .ipe file.
+	///
+	/// Fails only if the file is not an Ipe document at all.
+	pub fn import(&self, text: &str) -> Result<IpeImport, ()> {
+		let document = xml_elements(text)?.into_iter().find(|e| e.name == "ipe").ok_or(())?;
+		let pages = xml_elements(document.content)?.into_iter().filter(|e| e.name == "page").collect::<Vec<_>>();
+		let page = pages.first().ok_or(())?;
+
+		let mut data = DataDto::new((&self.classes).into());
+		let mut notes = Vec::new();
+		if pages.len() > 1 {
+			notes.push(format!("only the first of {} pages was imported", pages.len()));
+		}
+
+		// the layer attribute applies to all following objects too
+		let mut layer = None;
+		let mut objects = Vec::new();
+		for element in xml_elements(page.content)? {
+			if let Some(l) = element.attribute("layer") {
+				layer = Some(l.to_owned());
+			}
+			match (element.name, layer.as_deref()) {
+				("layer" | "view", _) => (),
+				(_, Some("conflicts")) => (),
+				_ => objects.push(element),
+			}
+		}
+
+		// vertices first, so everything else can be attached to them
+		let mut rest = Vec::new();
+		for element in objects {
+			match (element.name, element.attribute("name")) {
+				("use", Some("mark/disk(sx)")) => {
+					let Some(position) = self.position(&element) else {
+						notes.push("skipped a mark without valid position".to_owned());
+						continue;
+					};
+					let pinned = element.attribute("stroke") == Some("teal");
+					data.vertices.items.push(VertexDto { position, label: None, pinned, id: None });
+				},
+				_ => rest.push(element),
+			}
+		}
+
+		for element in rest {
+			let result = match element.name {
+				"text" => self.import_label(&element, &mut data).map(|()| None),
+				"path" => self.import_path(&element, &mut data),
+				name => Err(format!("skipped unsupported object <{}>", name)),
+			};
+			match result {
+				Ok(None) => (),
+				Ok(Some(note)) | Err(note) => notes.push(note),
+			}
+		}
+
+		Ok(IpeImport { data, notes })
+	}
+
+	fn matrix(element: &XmlElement) -> Affine2<Number> {
+		let values = element.attribute("matrix").and_then(numbers).unwrap_or_default();
+		match values[..] {
+			[a1, a2, a3, a4, a5, a6] => {
+				Affine2::from_matrix_unchecked(Matrix3::new(a1, a3, a5, a2, a4, a6, 0.0, 0.0, 1.0))
+			},
+			_ => Affine2::identity(),
+		}
+	}
+	/// Converts a point in the coordinates of the element to drawing coordinates.
+	fn to_drawing(element: &XmlElement, point: Vector) -> Vector {
+		let point = Self::matrix(element).transform_point(&point.into());
+		page_pose().transform_point(&point).into()
+	}
+	fn position(&self, element: &XmlElement) -> Option<Vector> {
+		match element.attribute("pos").and_then(numbers)?[..] {
+			[x, y] => Some(Self::to_drawing(element, Vector::new(x, y))),
+			_ => None,
+		}
+	}
+	fn vertex_at(&self, data: &DataDto, position: Vector) -> Option<VertexId> {
+		let distances = data.vertices.items.iter().map(|v| (v.position - position).length()).enumerate();
+		let (id, distance) = distances.min_by(|(_, a), (_, b)| a.total_cmp(b))?;
+		(distance <= self.tolerance).then_some(id)
+	}
+	fn vertex_name(data: &DataDto, id: VertexId) -> String {
+		data.vertices.items.get(id).and_then(|v| v.label.clone()).unwrap_or(format!("{}", id))
+	}
+
+	fn import_label(&self, element: &XmlElement, data: &mut DataDto) -> Result<(), String> {
+		let text = unescape_latex(&unescape_xml(element.content.trim()));
+		let position = self.position(element).ok_or(format!("skipped text \"{}\" without valid position", text))?;
+		let vertex = self
+			.vertex_at(data, position - LABEL_OFFSET)
+			.ok_or(format!("skipped text \"{}\" (not next to a vertex)", text))?;
+		data.vertices.items[vertex].label = Some(text);
+		Ok(())
+	}
+
+	/// Fails if the path was skipped, returns a note if it was only imported approximately.
+	fn import_path(&self, element: &XmlElement, data: &mut DataDto) -> Result<Option<String>, String> {
+		let segment =
+			Self::segment(element).ok_or("skipped a path that is not a single circular arc or segment")?;
+		let (start, end) = match &segment {
+			Segment::Line(start, end) | Segment::Arc(start, end, ..) => (*start, *end),
+		};
+		let (Some(a), Some(b)) = (self.vertex_at(data, start), self.vertex_at(data, end)) else {
+			let position = format!("[{:.2}; {:.2}]", start.x, start.y);
+			return Err(format!("skipped a path from {} that doesn't connect 2 vertices", position));
+		};
+		let (name_a, name_b) = (Self::vertex_name(data, a), Self::vertex_name(data, b));
+		if a == b || data.connections.iter().any(|&(s, e, _)| (s, e) == (a, b) || (s, e) == (b, a)) {
+			return Err(format!("skipped a duplicate connection ({}) ({})", name_a, name_b));
+		}
+
+		match segment {
+			Segment::Line(..) => {
+				// the geometry of unreachable connections is unknown
+				let orientation = ConnectionOrientation::InnerRight.into();
+				data.connections.push((a, b, ConnectionDto { orientation, size: 0 }));
+				Ok(Some(format!(
+					"connection ({}) ({}) is unreachable in the file, imported with the first class",
+					name_a, name_b,
+				)))
+			},
+			Segment::Arc(start, end, center, radius, positive) => {
+				let class = self.classes.closest(radius);
+				let (from, to) = ((start - center).angle(), (end - center).angle());
+				let (span, left) = match positive {
+					true => ((to - from).rem_euclid(TAU), true),
+					false => ((from - to).rem_euclid(TAU), false),
+				};
+				let orientation = match (left, span < PI) {
+					(false, true) => ConnectionOrientation::InnerRight,
+					(false, false) => ConnectionOrientation::OuterRight,
+					(true, true) => ConnectionOrientation::InnerLeft,
+					(true, false) => ConnectionOrientation::OuterLeft,
+				};
+				data.connections.push((a, b, ConnectionDto { orientation: orientation.into(), size: class }));
+				match (self.classes.get_size(class) - radius).abs() > self.tolerance {
+					true => Ok(Some(format!(
+						"connection ({}) ({}) has radius {:.2}, imported with the closest class ({})",
+						name_a,
+						name_b,
+						radius,
+						self.classes.get_size(class),
+					))),
+					false => Ok(None),
+				}
+			},
+		}
+	}
+
+	/// Parses the path if it is a single line or circular arc (in drawing coordinates).
+	fn segment(element: &XmlElement) -> Option<Segment> {
+		let tokens = element.content.split_whitespace().collect::<Vec<_>>();
+		let values = |range: Range<usize>| -> Option<Vec<Number>> {
+			tokens.get(range)?.iter().map(|n| n.parse().ok()).collect()
+		};
+		let point = |x: Number, y: Number| Self::to_drawing(element, Vector::new(x, y));
+		let start = match values(0..2)?[..] {
+			[x, y] if tokens.get(2) == Some(&"m") => point(x, y),
+			_ => return None,
+		};
+
+		match (tokens.len(), tokens.last()) {
+			(6, Some(&"l")) => match values(3..5)?[..] {
+				[x, y] => Some(Segment::Line(start, point(x, y))),
+				_ => None,
+			},
+			(12, Some(&"a")) => match values(3..11)?[..] {
+				// the arc is the image of the unit circle under the matrix [[a1 a3 a5] [a2 a4 a6]]
+				[a1, a2, a3, a4, a5, a6, x, y] => {
+					// a circle is a scaled rotation (possibly mirrored)
+					let epsilon = 1e-6 * (a1.abs() + a2.abs());
+					let rotated = (a1 - a4).abs() < epsilon && (a3 + a2).abs() < epsilon;
+					let mirrored = (a1 + a4).abs() < epsilon && (a3 - a2).abs() < epsilon;
+					if !rotated && !mirrored {
+						return None;
+					}
+					let transform = page_pose() * Self::matrix(element);
+					let determinant = (a1 * a4 - a2 * a3) * linear_determinant(&transform);
+					let radius = determinant.abs().sqrt();
+					Some(Segment::Arc(start, point(x, y), point(a5, a6), radius, determinant > 0.0))
+				},
+				_ => None,
+			},
+			_ => None,
+		}
+	}
+}
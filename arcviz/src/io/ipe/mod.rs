@@ -1,2 +1,7 @@
+pub(crate) mod colors;
+
 mod ipe_exporter;
 pub use ipe_exporter::*;
+
+mod ipe_importer;
+pub use ipe_importer::*;
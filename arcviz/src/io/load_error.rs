@@ -0,0 +1,112 @@
+use std::fmt::{self, Display};
+
+/// Why a file couldn't be loaded, with the location of the problem if it is known.
+#[derive(Clone, Debug)]
+pub struct LoadError {
+	pub message: String,
+	/// The line and column (both starting at 1).
+	pub position: Option<(usize, usize)>,
+	/// Where in the structure of the file the problem is (e.g. `drawing.vertices.items[3].position`).
+	pub section: Option<String>,
+}
+impl LoadError {
+	pub fn new(message: impl Into<String>) -> Self {
+		Self { message: message.into(), position: None, section: None }
+	}
+
+	/// Locates an error of serde_json in the json text it was reading.
+	pub fn json(text: &str, error: &serde_json::Error) -> Self {
+		let message = error.to_string();
+		let message = match message.rfind(" at line ") {
+			Some(end) => message[..end].to_owned(),
+			None => message,
+		};
+		let (line, column) = (error.line(), error.column());
+		if line == 0 {
+			return Self::new(message);
+		}
+		Self { message, position: Some((line, column)), section: json_path_at(text, line, column) }
+	}
+
+	/// Locates a problem at the given byte offset of the text it was found in.
+	pub fn at(text: &str, offset: usize, message: impl Into<String>) -> Self {
+		let before = &text[..offset.min(text.len())];
+		let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+		let position = (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1);
+		Self { position: Some(position), ..Self::new(message) }
+	}
+
+	/// Drops the position (e.g. because it refers to an intermediate text the user has never seen).
+	pub fn without_position(self) -> Self {
+		Self { position: None, ..self }
+	}
+}
+impl Display for LoadError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if let Some(section) = &self.section {
+			write!(f, "in {}: ", section)?;
+		}
+		write!(f, "{}", self.message)?;
+		if let Some((line, column)) = self.position {
+			write!(f, " (line {}, column {})", line, column)?;
+		}
+		Ok(())
+	}
+}
+
+/// The path to the innermost json value containing the given position (e.g. `drawing.vertices.items[3]`).
+fn json_path_at(text: &str, line: usize, column: usize) -> Option<String> {
+	enum Frame {
+		Object(Option<String>),
+		Array(usize),
+	}
+	let mut frames = Vec::new();
+	let (mut string, mut last_string) = (None::<String>, String::new());
+	let mut escaped = false;
+	let (mut current_line, mut current_column) = (1, 0);
+	for c in text.chars() {
+		match c {
+			'\n' => (current_line, current_column) = (current_line + 1, 0),
+			_ => current_column += 1,
+		}
+		if (current_line, current_column) >= (line, column) {
+			break;
+		}
+
+		if let Some(value) = &mut string {
+			match c {
+				_ if escaped => {
+					value.push(c);
+					escaped = false;
+				},
+				'\\' => escaped = true,
+				'"' => last_string = string.take().unwrap_or_default(),
+				_ => value.push(c),
+			}
+			continue;
+		}
+		match (c, frames.last_mut()) {
+			('"', _) => string = Some(String::new()),
+			('{', _) => frames.push(Frame::Object(None)),
+			('[', _) => frames.push(Frame::Array(0)),
+			('}' | ']', _) => {
+				frames.pop();
+			},
+			(':', Some(Frame::Object(key))) => *key = Some(last_string.clone()),
+			(',', Some(Frame::Object(key))) => *key = None,
+			(',', Some(Frame::Array(index))) => *index += 1,
+			_ => (),
+		}
+	}
+
+	let mut path = String::new();
+	for frame in frames {
+		match frame {
+			Frame::Object(Some(key)) if path.is_empty() => path.push_str(&key),
+			Frame::Object(Some(key)) => path.push_str(&format!(".{}", key)),
+			Frame::Object(None) => break,
+			Frame::Array(index) => path.push_str(&format!("[{}]", index)),
+		}
+	}
+	(!path.is_empty()).then_some(path)
+}
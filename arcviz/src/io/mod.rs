@@ -1,2 +1,17 @@
 pub mod dto;
+pub mod graph;
 pub mod ipe;
+pub mod svg;
+pub mod tikz;
+
+mod escape;
+pub use escape::*;
+
+mod format;
+pub use format::*;
+
+mod load_error;
+pub use load_error::*;
+
+mod xml;
+pub(crate) use xml::*;
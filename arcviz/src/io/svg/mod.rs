@@ -0,0 +1,2 @@
+mod svg_exporter;
+pub use svg_exporter::*;
@@ -0,0 +1,181 @@
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::{
+	common::{Bounds, Number, Vector},
+	io::escape_xml,
+	render::RenderTarget,
+};
+
+const VERTEX_RADIUS: Number = 8.0;
+const CONFLICT_SIZE: Number = 4.0;
+const MARGIN: Number = 10.0;
+
+/// The bounds of the arc around the center starting at the rotation and spanning the (signed) angle.
+fn arc_bounds(center: Vector, radius: Number, rotation: Number, angle: Number) -> Bounds {
+	let mut bounds = Bounds::from(center + radius * Vector::unit_from_angle(rotation));
+	bounds.merge(&Bounds::from(center + radius * Vector::unit_from_angle(rotation + angle)));
+
+	let (from, span) = match angle < 0.0 {
+		true => (rotation + angle, -angle),
+		false => (rotation, angle),
+	};
+	for axis in (0..4).map(|i| i as Number * FRAC_PI_2) {
+		if (axis - from).rem_euclid(TAU) <= span {
+			bounds.merge(&Bounds::from(center + radius * Vector::unit_from_angle(axis)));
+		}
+	}
+	bounds
+}
+
+/// A [RenderTarget] used to export the data as a standalone SVG image.
+///
+/// Arcs are exported as true SVG arcs (`A` path commands), so the image can be scaled without loss.
+/// The viewBox is fitted to the things that were drawn.
+/// To export render the [crate::model::Data] to an [SvgExporter] and then use the [SvgExporter::to_string] method to get the file.
+pub struct SvgExporter {
+	content: String,
+	bounds: Option<Bounds>,
+	show_labels: bool,
+	show_conflicts: bool,
+}
+impl Default for SvgExporter {
+	fn default() -> Self {
+		Self::new(true, true)
+	}
+}
+impl SvgExporter {
+	pub fn new(show_labels: bool, show_conflicts: bool) -> Self {
+		Self { content: String::new(), bounds: None, show_labels, show_conflicts }
+	}
+
+	fn include(&mut self, bounds: Bounds) {
+		self.bounds = Bounds::merged(&self.bounds, &Some(bounds));
+	}
+
+	fn line(&mut self, start: Vector, end: Vector, width: Number, color: &str, extra: &str) {
+		self.content.push_str(&format!(
+			"<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"{}\" stroke-width=\"{}\"{}/>\n",
+			start.x,
+			start.y,
+			end.x,
+			end.y,
+			escape_xml(color),
+			width,
+			extra,
+		));
+		let bounds = Bounds::from(start).combined_with(&Bounds::from(end));
+		self.include(bounds.expand(Vector::new_square(width / 2.0)));
+	}
+
+	fn arc(
+		&mut self,
+		center: Vector,
+		radius: Number,
+		rotation: Number,
+		angle: Number,
+		color: &str,
+		extra: &str,
+	) {
+		let start = center + radius * Vector::unit_from_angle(rotation);
+		let end = center + radius * Vector::unit_from_angle(rotation + angle);
+		let large_arc = (angle.abs() > PI) as u8;
+		let sweep = (angle > 0.0) as u8;
+		self.content.push_str(&format!(
+			"<path d=\"M {:.2} {:.2} A {:.2} {:.2} 0 {} {} {:.2} {:.2}\" stroke=\"{}\"{}/>\n",
+			start.x,
+			start.y,
+			radius,
+			radius,
+			large_arc,
+			sweep,
+			end.x,
+			end.y,
+			escape_xml(color),
+			extra,
+		));
+		self.include(arc_bounds(center, radius, rotation, angle));
+	}
+}
+impl ToString for SvgExporter {
+	fn to_string(&self) -> String {
+		let bounds = self.bounds.clone().unwrap_or(Bounds::from(Vector::zero())).expand(Vector::new_square(MARGIN));
+		let (start, size) = (bounds.min(), bounds.size().abs());
+		format!(
+			"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+			<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{:.2} {:.2} {:.2} {:.2}\" width=\"{:.2}\" height=\"{:.2}\">\n\
+			<g fill=\"none\" stroke-linecap=\"round\">\n{}</g>\n</svg>\n",
+			start.x, start.y, size.x, size.y, size.x, size.y, self.content,
+		)
+	}
+}
+
+impl RenderTarget for SvgExporter {
+	fn draw_vertex(&mut self, center: Vector, color: &str, selected: bool) {
+		self.content.push_str(&format!(
+			"<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{}\" fill=\"{}\"/>\n",
+			center.x,
+			center.y,
+			VERTEX_RADIUS,
+			escape_xml(color),
+		));
+		self.include(Bounds::from(center).expand(Vector::new_square(VERTEX_RADIUS)));
+	}
+	fn draw_connection_arc(
+		&mut self,
+		center: Vector,
+		radius: Number,
+		rotation: Number,
+		angle: Number,
+		color: &str,
+		selected: bool,
+	) {
+		self.arc(center, radius, rotation, angle, color, " stroke-width=\"3\"");
+	}
+	fn draw_connection_highlight(
+		&mut self,
+		center: Vector,
+		radius: Number,
+		rotation: Number,
+		angle: Number,
+		color: &str,
+	) {
+		self.arc(center, radius, rotation, angle, color, " stroke-width=\"11\" stroke-opacity=\"0.5\"");
+	}
+	fn draw_connection_invalid(&mut self, start: Vector, end: Vector, selected: bool) {
+		self.line(start, end, 2.0, "firebrick", " stroke-dasharray=\"15 10\"");
+	}
+	fn draw_conflict(&mut self, center: Vector, color: &str, selected: bool) {
+		if !self.show_conflicts {
+			return;
+		}
+		let mut square = Vector::new_square(CONFLICT_SIZE);
+		self.line(center - square, center + square, 2.0, color, "");
+		square.x = -square.x;
+		self.line(center - square, center + square, 2.0, color, "");
+	}
+	fn draw_label(&mut self, anchor: Vector, text: &str) {
+		if !self.show_labels {
+			return;
+		}
+		let anchor = anchor + Vector::new(VERTEX_RADIUS, -VERTEX_RADIUS);
+		self.content.push_str(&format!(
+			"<text x=\"{:.2}\" y=\"{:.2}\" font-family=\"sans-serif\" font-size=\"16\" fill=\"black\">{}</text>\n",
+			anchor.x,
+			anchor.y,
+			escape_xml(text),
+		));
+		// rough estimate of the extent of the text (there is no font metrics here)
+		let size = Vector::new(9.0 * text.chars().count() as Number, 16.0);
+		self.include(Bounds::new_with_end(anchor - Vector::new(0.0, size.y), anchor + Vector::new(size.x, 4.0)));
+	}
+
+	fn draw_grid_line(&mut self, start: &Vector, end: &Vector, width: Number, color: &str) {}
+	fn draw_aid_line(&mut self, from: Vector, to: Vector) {}
+	fn draw_aid_box(&mut self, from: Vector, to: Vector) {}
+	fn clear_region(&mut self, start: &Vector, size: &Vector) {}
+	fn clear(&mut self) {
+		self.content = String::new();
+		self.bounds = None;
+	}
+	fn reset(&mut self) {}
+}
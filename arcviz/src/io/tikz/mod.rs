@@ -0,0 +1,2 @@
+mod tikz_exporter;
+pub use tikz_exporter::*;
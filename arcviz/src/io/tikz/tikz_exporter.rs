@@ -0,0 +1,279 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+	common::{Number, Vector},
+	io::{escape_latex, ipe::colors::css_to_rgb},
+	model::Data,
+	render::RenderTarget,
+};
+
+/// How many centimeters one unit of the drawing is.
+const SCALE: Number = 0.01;
+/// How close a point has to be to a vertex to be expressed by its coordinate name.
+const SNAP_DISTANCE: Number = 1e-6;
+
+/// The label as name of a coordinate, None if nothing is left of it.
+///
+/// Runs of characters other than ascii letters and digits become `-`, since TikZ gives some of them
+/// a meaning in coordinates (like `.` for anchors or `,` for separating components).
+fn sanitized(label: &str) -> Option<String> {
+	let parts = label.split(|c: char| !c.is_ascii_alphanumeric()).filter(|part| !part.is_empty());
+	let name = parts.collect::<Vec<_>>().join("-");
+	(!name.is_empty()).then_some(name)
+}
+
+/// The names of the coordinates of the vertices.
+///
+/// A vertex is named after its label if that is a valid name no other label becomes,
+/// otherwise it is named by its id (`v0`, `v1`, ..). Labels that look like such a name are not used.
+fn coordinate_names(data: &Data) -> Vec<String> {
+	let names = data.vertices.iter().map(|v| v.label.as_deref().and_then(sanitized)).collect::<Vec<_>>();
+	let mut counts = HashMap::new();
+	for name in names.iter().flatten() {
+		*counts.entry(name.as_str()).or_insert(0) += 1;
+	}
+	let generated = |name: &str| {
+		let digits = name.strip_prefix('v').unwrap_or_default();
+		!digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+	};
+	let choose = |(id, name): (usize, &Option<String>)| match name {
+		Some(name) if counts[name.as_str()] == 1 && !generated(name) => name.clone(),
+		_ => format!("v{}", id),
+	};
+	names.iter().enumerate().map(choose).collect()
+}
+
+/// The name a css color is defined with (`#ff8800` becomes `arcviz-ff8800`).
+fn color_name(color: &str) -> String {
+	format!("arcviz-{}", color.trim_start_matches('#').to_lowercase())
+}
+/// Defines the css color for `xcolor` (as black if it is unknown).
+fn define_color(name: &str, color: &str) -> String {
+	let (r, g, b) = css_to_rgb(color).unwrap_or((0.0, 0.0, 0.0));
+	format!("\\definecolor{{{}}}{{rgb}}{{{:.3},{:.3},{:.3}}}\n", name, r, g, b)
+}
+
+/// A [RenderTarget] used to export the data as a `tikzpicture` for LaTeX documents.
+///
+/// Every vertex gets a named coordinate (its label made into a valid name, or `v0`, `v1`, .. if it has none
+/// or the name is not unique).
+/// Connections are drawn as exact circular arcs in the style of their class (`class0`, `class1`, ..).
+/// To export render the [crate::model::Data] to a [TikzExporter] created for it and then use the [TikzExporter::to_string] method to get the file.
+/// (All colors are defined in the picture, since `xcolor` doesn't know most css colors. Unknown colors become black.)
+pub struct TikzExporter {
+	styles: String,
+	coordinates: Vec<(Vector, String)>,
+	content: String,
+	class_colors: Vec<String>,
+	/// The (css) colors used so far, that need to be defined.
+	colors: BTreeSet<String>,
+	/// The colors that couldn't be converted (and were replaced by black).
+	unknown_colors: BTreeSet<String>,
+	show_labels: bool,
+	show_conflicts: bool,
+}
+impl TikzExporter {
+	pub fn new(data: &Data, show_labels: bool, show_conflicts: bool) -> Self {
+		let class_colors =
+			(0..data.classes.len()).map(|i| data.classes.get_color(i).to_owned()).collect::<Vec<_>>();
+		let styles = class_colors
+			.iter()
+			.enumerate()
+			.map(|(i, _)| format!("\tclass{0}/.style={{draw=class{0}, line width=1pt}},\n", i))
+			.collect();
+		let unknown_colors = class_colors.iter().filter(|color| css_to_rgb(color).is_none()).cloned().collect();
+
+		let coordinates = data.vertices.iter().map(|vertex| vertex.position).zip(coordinate_names(data)).collect();
+
+		Self {
+			styles,
+			coordinates,
+			content: String::new(),
+			class_colors,
+			colors: BTreeSet::new(),
+			unknown_colors,
+			show_labels,
+			show_conflicts,
+		}
+	}
+
+	/// The colors that are not known css colors (they are exported as black).
+	pub fn unknown_colors(&self) -> impl Iterator<Item = &String> {
+		self.unknown_colors.iter()
+	}
+	/// The name to use for the given css color (which is defined in the picture), black if it is unknown.
+	fn color(&mut self, color: &str) -> String {
+		match css_to_rgb(color) {
+			Some(_) => {
+				self.colors.insert(color.to_owned());
+				color_name(color)
+			},
+			None => {
+				self.unknown_colors.insert(color.to_owned());
+				"black".to_owned()
+			},
+		}
+	}
+
+	fn transform_point(point: Vector) -> Vector {
+		Vector::new(point.x * SCALE, -point.y * SCALE)
+	}
+	/// The point as TikZ coordinate (the name of the vertex there if there is one).
+	fn point(&self, point: Vector) -> String {
+		match self.coordinates.iter().find(|(position, _)| (*position - point).length() < SNAP_DISTANCE) {
+			Some((_, name)) => format!("({})", name),
+			None => {
+				let point = Self::transform_point(point);
+				format!("({:.4}, {:.4})", point.x, point.y)
+			},
+		}
+	}
+
+	fn arc(&mut self, center: Vector, radius: Number, rotation: Number, angle: Number, style: &str) {
+		let start = self.point(center + radius * Vector::unit_from_angle(rotation));
+		// the y axis is flipped, so the angles are too
+		let (from, to) = (-rotation.to_degrees(), -(rotation + angle).to_degrees());
+		self.content.push_str(&format!(
+			"\\draw[{}] {} arc[start angle={:.4}, end angle={:.4}, radius={:.4}cm];\n",
+			style,
+			start,
+			from,
+			to,
+			radius * SCALE,
+		));
+	}
+}
+impl ToString for TikzExporter {
+	fn to_string(&self) -> String {
+		let coordinates = self
+			.coordinates
+			.iter()
+			.map(|(position, name)| {
+				let position = Self::transform_point(*position);
+				format!("\\coordinate ({}) at ({:.4}, {:.4});\n", name, position.x, position.y)
+			})
+			.collect::<String>();
+		let classes =
+			self.class_colors.iter().enumerate().map(|(i, color)| define_color(&format!("class{}", i), color));
+		let colors = self.colors.iter().map(|color| define_color(&color_name(color), color));
+		format!(
+			"\\begin{{tikzpicture}}[line cap=round]\n{}\\tikzset{{\n{}}}\n{}{}\\end{{tikzpicture}}\n",
+			classes.chain(colors).collect::<String>(),
+			self.styles,
+			coordinates,
+			self.content,
+		)
+	}
+}
+
+impl RenderTarget for TikzExporter {
+	fn draw_vertex(&mut self, center: Vector, color: &str, selected: bool) {
+		let (center, color) = (self.point(center), self.color(color));
+		self.content.push_str(&format!("\\fill[{}] {} circle (2pt);\n", color, center));
+	}
+	fn draw_connection_arc(
+		&mut self,
+		center: Vector,
+		radius: Number,
+		rotation: Number,
+		angle: Number,
+		color: &str,
+		selected: bool,
+	) {
+		let style = match self.class_colors.iter().position(|c| c == color) {
+			Some(class) => format!("class{}", class),
+			None => format!("draw={}, line width=1pt", self.color(color)),
+		};
+		self.arc(center, radius, rotation, angle, &style);
+	}
+	fn draw_connection_highlight(
+		&mut self,
+		center: Vector,
+		radius: Number,
+		rotation: Number,
+		angle: Number,
+		color: &str,
+	) {
+		let style = format!("draw={}, line width=4pt, opacity=0.5", self.color(color));
+		self.arc(center, radius, rotation, angle, &style);
+	}
+	fn draw_connection_invalid(&mut self, start: Vector, end: Vector, selected: bool) {
+		let (start, end) = (self.point(start), self.point(end));
+		self.content.push_str(&format!("\\draw[red!70!black, dashed] {} -- {};\n", start, end));
+	}
+	fn draw_conflict(&mut self, center: Vector, color: &str, selected: bool) {
+		if !self.show_conflicts {
+			return;
+		}
+		let (center, color) = (self.point(center), self.color(color));
+		self.content.push_str(&format!("\\fill[{}] {} circle (1pt);\n", color, center));
+	}
+	fn draw_label(&mut self, anchor: Vector, text: &str) {
+		if !self.show_labels {
+			return;
+		}
+		let anchor = self.point(anchor);
+		self.content.push_str(&format!("\\node[anchor=south west] at {} {{{}}};\n", anchor, escape_latex(text)));
+	}
+
+	fn draw_grid_line(&mut self, start: &Vector, end: &Vector, width: Number, color: &str) {}
+	fn draw_aid_line(&mut self, from: Vector, to: Vector) {}
+	fn draw_aid_box(&mut self, from: Vector, to: Vector) {}
+	fn clear_region(&mut self, start: &Vector, size: &Vector) {}
+	fn clear(&mut self) {
+		self.content = String::new();
+		self.colors.clear();
+	}
+	fn reset(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::{Class, Classes, Settings, Vertex};
+
+	/// Vertices 100 apart with the given labels.
+	fn drawing(labels: &[Option<&str>]) -> Data {
+		let mut data = Data::new(Classes::default());
+		for (i, label) in labels.iter().enumerate() {
+			let mut vertex = Vertex::new(Vector::new(100.0 * i as Number, 50.0));
+			vertex.label = label.map(str::to_owned);
+			data.add_vertex(vertex);
+		}
+		data
+	}
+	fn export(data: &Data) -> String {
+		let mut exporter = TikzExporter::new(data, true, true);
+		data.render_to(&mut exporter, &Settings::default());
+		exporter.to_string()
+	}
+
+	#[test]
+	fn names_coordinates_after_unique_labels() {
+		let labels =
+			[Some("start"), Some("a b"), Some("x_1"), Some("x 1"), Some(""), None, Some("v0"), Some("(1, 2)")];
+		let names = coordinate_names(&drawing(&labels));
+		assert_eq!(names, ["start", "a-b", "v2", "v3", "v4", "v5", "v6", "1-2"]);
+
+		let tikz = export(&drawing(&[Some("start"), Some("end.point")]));
+		assert!(tikz.contains("\\coordinate (start) at (0.0000, -0.5000);"));
+		assert!(tikz.contains("\\coordinate (end-point) at (1.0000, -0.5000);"));
+		assert!(tikz.contains("\\definecolor{arcviz-green}{rgb}{0.000,0.502,0.000}"));
+		assert!(tikz.contains("\\fill[arcviz-green] (end-point) circle (2pt);"));
+	}
+
+	#[test]
+	fn defines_css_colors_and_reports_unknown_ones() {
+		let class = |color: &str| Class::new(20.0, color.to_owned());
+		let classes = Classes::new(vec![class("#ff8800"), class("firebrick"), class("nonsense")], class("black"));
+		let data = Data::new(classes);
+		let mut exporter = TikzExporter::new(&data, true, true);
+		data.render_to(&mut exporter, &Settings::default());
+		let tikz = exporter.to_string();
+		assert!(tikz.contains("\\definecolor{class0}{rgb}{1.000,0.533,0.000}"));
+		assert!(tikz.contains("\\definecolor{class1}{rgb}{0.698,0.133,0.133}"));
+		assert!(tikz.contains("\\definecolor{class2}{rgb}{0.000,0.000,0.000}"));
+		assert!(tikz.contains("\tclass1/.style={draw=class1, line width=1pt},"));
+		assert_eq!(exporter.unknown_colors().collect::<Vec<_>>(), ["nonsense"]);
+	}
+}
@@ -0,0 +1,85 @@
+//! A minimal xml reader, just enough for the files written by this app and similar tools.
+
+use super::unescape_xml;
+
+/// An xml element as it appears in the file.
+pub(crate) struct XmlElement<'a> {
+	pub name: &'a str,
+	pub attributes: Vec<(&'a str, String)>,
+	/// The raw (still escaped) content between the tags.
+	pub content: &'a str,
+}
+impl<'a> XmlElement<'a> {
+	pub fn attribute(&self, name: &str) -> Option<&str> {
+		self.attributes.iter().find(|(key, _)| *key == name).map(|(_, value)| value.as_str())
+	}
+}
+
+/// Splits the xml into its top level elements (ignoring text between them).
+pub(crate) fn xml_elements(xml: &str) -> Result<Vec<XmlElement>, ()> {
+	let mut elements = Vec::new();
+	let mut rest = xml;
+	while let Some(start) = rest.find('<') {
+		rest = &rest[start + 1..];
+		if let Some(comment) = rest.strip_prefix("!--") {
+			let end = comment.find("-->").ok_or(())?;
+			rest = &comment[end + 3..];
+			continue;
+		}
+		if rest.starts_with('?') || rest.starts_with('!') {
+			// declarations and doctype
+			let end = rest.find('>').ok_or(())?;
+			rest = &rest[end + 1..];
+			continue;
+		}
+		let name_end = rest.find(|c: char| c.is_whitespace() || c == '/' || c == '>').ok_or(())?;
+		let name = &rest[..name_end];
+		rest = &rest[name_end..];
+
+		let mut attributes = Vec::new();
+		let self_closing = loop {
+			rest = rest.trim_start();
+			if let Some(r) = rest.strip_prefix("/>") {
+				rest = r;
+				break true;
+			}
+			if let Some(r) = rest.strip_prefix('>') {
+				rest = r;
+				break false;
+			}
+			let (key, r) = rest.split_once("=\"").ok_or(())?;
+			let (value, r) = r.split_once('"').ok_or(())?;
+			attributes.push((key.trim(), unescape_xml(value)));
+			rest = r;
+		};
+		if self_closing {
+			elements.push(XmlElement { name, attributes, content: "" });
+			continue;
+		}
+
+		// find the matching closing tag (elements of the same kind may be nested, e.g. groups)
+		let (open, close) = (format!("<{}", name), format!("</{}>", name));
+		let find_open = |text: &str| {
+			text.match_indices(&open).map(|(i, _)| i).find(|i| {
+				text[i + open.len()..].starts_with(|c: char| c.is_whitespace() || c == '/' || c == '>')
+			})
+		};
+		let (mut depth, mut position) = (1, 0);
+		while depth > 0 {
+			let next_close = rest[position..].find(&close).ok_or(())? + position;
+			match find_open(&rest[position..next_close]) {
+				Some(next_open) => {
+					depth += 1;
+					position += next_open + open.len();
+				},
+				None => {
+					depth -= 1;
+					position = next_close + close.len();
+				},
+			}
+		}
+		elements.push(XmlElement { name, attributes, content: &rest[..position - close.len()] });
+		rest = &rest[position..];
+	}
+	Ok(elements)
+}
@@ -1,12 +1,13 @@
-use std::{f64::consts::PI, ops::Div};
+use std::{cmp::Ordering, f64::consts::PI, ops::Div};
 
 use crate::{
-	common::{Bounds, Number, Vector},
+	common::{Bounds, Number, Precise, Vector},
 	model::Classes,
 };
 
-use super::{Connection, ConnectionOrientation, VertexId, Vertices};
+use super::{Connection, ConnectionOrientation, Tolerance, VertexId, Vertices};
 
+#[derive(Clone, PartialEq)]
 pub struct Arc {
 	pub center: Vector,
 	pub radius: Number,
@@ -14,6 +15,9 @@ pub struct Arc {
 	pub angle: Number,
 	start: Vector,
 	end: Vector,
+	center_is_left: bool,
+	/// A bound on the distance of the center to the exact center of the arc between the vertices.
+	error: Number,
 }
 
 impl Arc {
@@ -24,8 +28,8 @@ impl Arc {
 		connection: &Connection,
 		sizes: &Classes,
 	) -> Result<Self, ()> {
-		let start = vertices.items.get(start).ok_or(())?.position.clone();
-		let end = vertices.items.get(end).ok_or(())?.position.clone();
+		let start = vertices.get(start).ok_or(())?.position.clone();
+		let end = vertices.get(end).ok_or(())?.position.clone();
 		let radius = sizes.get_size(connection.size);
 
 		let straight = end.clone() - start.clone();
@@ -50,7 +54,42 @@ impl Arc {
 				ConnectionOrientation::OuterLeft => PI - alpha,
 			};
 
-		Ok(Self { center, radius: radius, rotation, angle, start, end })
+		let error = Self::center_error(Number::EPSILON, middle.length(), radius, distance_middle_to_center);
+		let center_is_left = connection.orientation.center_is_left();
+		Ok(Self { center, radius: radius, rotation, angle, start, end, center_is_left, error })
+	}
+
+	/// A bound on the error of a center that was computed as above with the given relative rounding error.
+	///
+	/// - middle: the distance of the middle of the vertices to the origin
+	/// - height: the distance of the center to the middle of the vertices
+	///
+	/// The height is the root of a difference of squares, so its error grows when it gets small
+	/// (arcs that are almost half circles).
+	fn center_error(rounding: Number, middle: Number, radius: Number, height: Number) -> Number {
+		let squared = 16.0 * rounding * radius.powi(2);
+		16.0 * rounding * (middle + radius) + (squared / height).min(squared.sqrt())
+	}
+	/// The center computed from the vertices with [Precise] numbers and a bound on its error.
+	fn precise_center(&self) -> (Precise, Precise, Number) {
+		let (start_x, start_y) = (Precise::from(self.start.x), Precise::from(self.start.y));
+		let (end_x, end_y) = (Precise::from(self.end.x), Precise::from(self.end.y));
+		let (straight_x, straight_y) = (end_x - start_x, end_y - start_y);
+		let half = Precise::from(0.5);
+		let (middle_x, middle_y) = ((start_x + end_x) * half, (start_y + end_y) * half);
+
+		let squared_length = straight_x * straight_x + straight_y * straight_y;
+		let radius = Precise::from(self.radius);
+		let distance_middle_to_center = (radius * radius - squared_length * half * half).sqrt();
+		let side = Precise::from(if self.center_is_left { 1.0 } else { -1.0 });
+		// the normal of the straight line has its length, so it is scaled by the distance over the length
+		let scale = distance_middle_to_center / squared_length.sqrt() * side;
+		let (x, y) = (middle_x - scale * straight_y, middle_y + scale * straight_x);
+
+		let middle = Vector::new(middle_x.value(), middle_y.value()).length();
+		let error =
+			Self::center_error(Precise::UNIT_ROUNDOFF, middle, self.radius, distance_middle_to_center.value());
+		(x, y, error)
 	}
 
 	fn normalize_angle_to_360(angle: Number) -> Number {
@@ -135,62 +174,115 @@ impl Arc {
 		return (range1, range2);
 	}
 
+	/// Whether the point (on the circle of the arc) is part of the arc, None if it's too close to an end.
+	fn contains_point(&self, point: Vector, tolerance: &Tolerance) -> Option<bool> {
+		let start = match self.angle.is_sign_negative() {
+			false => self.rotation,
+			true => self.rotation + self.angle,
+		};
+		let offset = Self::normalize_angle_to_360((point - self.center).angle() - start);
+		// the point is computed from the center, so both may be off by the error of the center
+		let margin = tolerance.angle(self.radius) + 2.0 * self.error / self.radius;
+		if offset <= margin || offset >= 2.0 * PI - margin || (offset - self.angle.abs()).abs() <= margin {
+			return None;
+		}
+		Some(offset < self.angle.abs())
+	}
+	/// Whether the point (on both circles) is part of both arcs and whether that is certain.
+	///
+	/// An end both arcs share (the same vertex) is always part of both.
+	fn both_contain(&self, other: &Self, point: Vector, tolerance: &Tolerance) -> (bool, bool) {
+		let same = tolerance.distance(self.radius + other.radius) + 2.0 * (self.error + other.error);
+		let shared = [self.start, self.end]
+			.into_iter()
+			.filter(|end| *end == other.start || *end == other.end)
+			.any(|end| (point - end).length() <= same);
+		if shared {
+			return (true, true);
+		}
+		match (self.contains_point(point, tolerance), other.contains_point(point, tolerance)) {
+			(Some(false), _) | (_, Some(false)) => (false, true),
+			(Some(true), Some(true)) => (true, true),
+			_ => (true, false),
+		}
+	}
+
 	/// Computes the intersection points with the given Arc.
-	pub fn intersection_with(&self, other: &Self) -> ArcIntersection {
+	///
+	/// Borderline cases are decided by the tolerance (see [Tolerance]), the returned flag is false
+	/// if the error of constructing the arcs or of the computation could have changed the result.
+	/// In that case whether the arcs meet is computed again from the vertices with [Precise] numbers.
+	pub fn intersection_with(&self, other: &Self, tolerance: &Tolerance) -> (ArcIntersection, bool) {
 		let self_to_other = other.center.clone() - self.center.clone();
 		let distance = self_to_other.length();
+		let size = self.radius + other.radius;
+		let errors = self.error + other.error;
 
 		// 0: arcs are concentric
-		if !(distance > 0.0) {
-			if self.radius != other.radius {
-				return ArcIntersection::None;
+		let (sum, difference) =
+			(Precise::from(self.radius) + other.radius.into(), Precise::from(self.radius) - other.radius.into());
+		if distance <= tolerance.distance(size) + errors {
+			// the circles can't meet if their radii differ by more than the (possible) distance of the centers
+			let spread = distance + errors;
+			let fast = (self.radius - other.radius, Number::EPSILON * size + spread);
+			let (same_radius, certain) = tolerance.compare(fast, || (difference, spread), size);
+			if same_radius != Ordering::Equal {
+				return (ArcIntersection::None, certain);
 			}
 			let ranges = self.angular_intersection(other);
-			let Some(range1) = ranges.0 else { return ArcIntersection::None };
-			return ArcIntersection::Concentric(range1, ranges.1);
+			let Some(range1) = ranges.0 else { return (ArcIntersection::None, certain) };
+			return (ArcIntersection::Concentric(range1, ranges.1), certain);
 		}
 
+		// the squared distance of the centers compared to the squared sum (outside) and difference (inside),
+		// the precise one is computed from the vertices (not from the rounded centers)
+		let magnitude = distance.powi(2) + size.powi(2);
+		let fast_error = 8.0 * Number::EPSILON * magnitude + squared_error(distance, errors);
+		let squared_distance = || {
+			let ((x1, y1, error1), (x2, y2, error2)) = (self.precise_center(), other.precise_center());
+			let (x, y) = (x2 - x1, y2 - y1);
+			let error = 16.0 * Precise::UNIT_ROUNDOFF * magnitude + squared_error(distance + errors, error1 + error2);
+			(x * x + y * y, error)
+		};
+		let outside = tolerance.compare(
+			(distance.powi(2) - size.powi(2), fast_error),
+			|| {
+				let (squared_distance, error) = squared_distance();
+				(squared_distance - sum * sum, error)
+			},
+			magnitude,
+		);
+		let inside = tolerance.compare(
+			(distance.powi(2) - (self.radius - other.radius).powi(2), fast_error),
+			|| {
+				let (squared_distance, error) = squared_distance();
+				(squared_distance - difference * difference, error)
+			},
+			magnitude,
+		);
+
 		// 1: too far apart to intersect
-		if distance > self.radius + other.radius {
-			return ArcIntersection::None;
-		}
 		// 2: too close => one fully contains the other
-		if self.radius > distance + other.radius {
-			return ArcIntersection::None;
-		}
-		if other.radius > distance + self.radius {
-			return ArcIntersection::None;
+		if outside.0 == Ordering::Greater || inside.0 == Ordering::Less {
+			return (ArcIntersection::None, outside.1 && inside.1);
 		}
 
 		// 3: (possibly) just touching
-		if distance == self.radius + other.radius {
+		let touching = |point: Vector, certain: bool| match self.both_contain(other, point, tolerance) {
+			(true, sure) => (ArcIntersection::One(point), certain && sure),
+			(false, sure) => (ArcIntersection::None, certain && sure),
+		};
+		if outside.0 == Ordering::Equal {
 			// from outside
-			if self.contains_angle(self_to_other.angle()) && other.contains_angle((-self_to_other.clone()).angle()) {
-				let self_to_intersection = self.radius * self_to_other.unit();
-				return ArcIntersection::One(self.center.clone() + self_to_intersection);
-			} else {
-				return ArcIntersection::None;
-			}
-		}
-		if self.radius == distance + other.radius {
-			// other is in self
-			if self.contains_angle(self_to_other.angle()) && other.contains_angle(self_to_other.angle()) {
-				let self_to_intersection = self.radius * self_to_other.unit();
-				return ArcIntersection::One(self.center.clone() + self_to_intersection);
-			} else {
-				return ArcIntersection::None;
-			}
+			return touching(self.center.clone() + self.radius * self_to_other.unit(), outside.1);
 		}
-		if other.radius == distance + self.radius {
-			// self is in other
-			if self.contains_angle((-self_to_other.clone()).angle())
-				&& other.contains_angle((-self_to_other.clone()).angle())
-			{
-				let self_to_intersection = -self.radius * self_to_other.unit();
-				return ArcIntersection::One(self.center.clone() + self_to_intersection);
-			} else {
-				return ArcIntersection::None;
-			}
+		if inside.0 == Ordering::Equal {
+			return match self.radius > other.radius {
+				// other is in self
+				true => touching(self.center.clone() + self.radius * self_to_other.unit(), inside.1),
+				// self is in other
+				false => touching(self.center.clone() - self.radius * self_to_other.unit(), inside.1),
+			};
 		}
 
 		// 4: (possible) intersecting in 2 places
@@ -198,27 +290,19 @@ impl Arc {
 		// that is the midpoint of the chord connecting the 2 possible intersection points
 		let self_to_collision =
 			self_to_other.clone() / 2.0 * (1.0 + ((self.radius.powi(2) - other.radius.powi(2)) / distance.powi(2)));
-		let collision_to_intersection_distance = (self.radius.powi(2) - self_to_collision.length().powi(2)).sqrt();
+		let collision_to_intersection_distance =
+			(self.radius.powi(2) - self_to_collision.length().powi(2)).max(0.0).sqrt();
 		let collision_to_intersection = collision_to_intersection_distance * self_to_other.normal().unit();
 		let left_intersection = self.center.clone() + self_to_collision.clone() + collision_to_intersection.clone();
 		let right_intersection =
 			self.center.clone() + self_to_collision.clone() - collision_to_intersection.clone();
 
-		let self_to_left = left_intersection.clone() - self.center.clone();
-		let self_to_right = right_intersection.clone() - self.center.clone();
-		let other_to_left = left_intersection.clone() - other.center.clone();
-		let other_to_right = right_intersection.clone() - other.center.clone();
+		let (left, left_certain) = self.both_contain(other, left_intersection, tolerance);
+		let (right, right_certain) = self.both_contain(other, right_intersection, tolerance);
+		let left = left.then_some(left_intersection);
+		let right = right.then_some(right_intersection);
 
-		let mut left = None;
-		if self.contains_angle(self_to_left.angle()) && other.contains_angle(other_to_left.angle()) {
-			left = Some(left_intersection);
-		}
-		let mut right = None;
-		if self.contains_angle(self_to_right.angle()) && other.contains_angle(other_to_right.angle()) {
-			right = Some(right_intersection);
-		}
-
-		return ArcIntersection::Two(left, right);
+		return (ArcIntersection::Two(left, right), outside.1 && inside.1 && left_certain && right_certain);
 	}
 
 	pub fn bounds(&self) -> Bounds {
@@ -240,6 +324,12 @@ impl Arc {
 	}
 }
 
+/// A bound on the error of the squared distance of 2 points at about the given distance,
+/// given a bound on the error of their distance.
+fn squared_error(distance: Number, error: Number) -> Number {
+	error * (2.0 * distance + error)
+}
+
 /// The intersection of 2 Arcs.
 pub enum ArcIntersection {
 	/// The Arcs don't intersect.
@@ -253,3 +343,129 @@ pub enum ArcIntersection {
 	Two(Option<Vector>, Option<Vector>),
 	Concentric((Number, Number), Option<(Number, Number)>),
 }
+impl ArcIntersection {
+	/// Whether the arcs cross, just touch or don't meet at all.
+	pub fn contact(&self) -> ArcContact {
+		match self {
+			ArcIntersection::None | ArcIntersection::Two(None, None) => ArcContact::Disjoint,
+			ArcIntersection::One(_) | ArcIntersection::Concentric(_, _) => ArcContact::Touch,
+			ArcIntersection::Two(_, _) => ArcContact::Cross,
+		}
+	}
+}
+
+/// How 2 arcs meet (see [ArcIntersection::contact]).
+#[derive(Copy, Clone, PartialEq)]
+pub enum ArcContact {
+	Disjoint,
+	/// The arcs meet in a point without crossing (or overlap on the same circle), within the [Tolerance].
+	Touch,
+	Cross,
+}
+impl ArcContact {
+	pub fn name(&self) -> &'static str {
+		match self {
+			ArcContact::Disjoint => "disjoint",
+			ArcContact::Touch => "touching",
+			ArcContact::Cross => "crossing",
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::{Class, Vertex};
+
+	/// The arc between the vertices with the given radius.
+	fn arc(
+		start: (Number, Number),
+		end: (Number, Number),
+		radius: Number,
+		orientation: ConnectionOrientation,
+	) -> Arc {
+		let mut vertices = Vertices::default();
+		let start = vertices.add(Vertex::new(Vector::new(start.0, start.1)));
+		let end = vertices.add(Vertex::new(Vector::new(end.0, end.1)));
+		let class = || Class::new(radius, "black".to_owned());
+		let classes = Classes::new(vec![class()], class());
+		Arc::construct(start, end, &vertices, &Connection::new(orientation, 0), &classes).unwrap()
+	}
+	/// Arcs around `(-5, 0)` and `(5 + offset, 0)` that touch in the origin if the offset is 0.
+	fn pair(offset: Number) -> (Arc, Arc) {
+		let first = arc((-2.0, 4.0), (-2.0, -4.0), 5.0, ConnectionOrientation::InnerRight);
+		let second = arc((2.0 + offset, 4.0), (2.0 + offset, -4.0), 5.0, ConnectionOrientation::InnerLeft);
+		(first, second)
+	}
+	fn contact(first: &Arc, second: &Arc, tolerance: Tolerance) -> (ArcContact, bool) {
+		let (intersection, certain) = first.intersection_with(second, &tolerance);
+		(intersection.contact(), certain)
+	}
+
+	#[test]
+	fn tangent_arcs_touch_within_the_tolerance() {
+		let (first, second) = pair(0.0);
+		assert!(contact(&first, &second, Tolerance::default()) == (ArcContact::Touch, false));
+		let (intersection, _) = first.intersection_with(&second, &Tolerance::default());
+		assert!(matches!(intersection, ArcIntersection::One(point) if point.length() < 1e-9));
+	}
+
+	#[test]
+	fn nearly_tangent_arcs_touch_within_the_tolerance() {
+		for offset in [-1e-12, 1e-12] {
+			let (first, second) = pair(offset);
+			assert!(contact(&first, &second, Tolerance::default()) == (ArcContact::Touch, false));
+		}
+	}
+
+	#[test]
+	fn arcs_beyond_the_tolerance_are_certain() {
+		let (first, second) = pair(0.001);
+		assert!(contact(&first, &second, Tolerance::default()) == (ArcContact::Disjoint, true));
+		let (first, second) = pair(-0.001);
+		assert!(contact(&first, &second, Tolerance::default()) == (ArcContact::Cross, true));
+	}
+
+	#[test]
+	fn a_larger_epsilon_decides_more_arcs_as_touching() {
+		let (first, second) = pair(0.001);
+		assert!(contact(&first, &second, Tolerance::new(1e-3).unwrap()).0 == ArcContact::Touch);
+		assert!(Tolerance::new(-1.0).is_err() && Tolerance::new(Number::NAN).is_err());
+	}
+
+	#[test]
+	fn exactly_tangent_vertices_are_not_certified_by_their_rounded_centers() {
+		// the circles around (-5, 0) and (5, 0) touch in the origin, but the centers are rounded apart
+		let first = arc((-5.0, -5.0), (-1.0, 3.0), 5.0, ConnectionOrientation::InnerLeft);
+		let second = arc((1.0, -3.0), (5.0, 5.0), 5.0, ConnectionOrientation::InnerRight);
+		let between = second.center - first.center;
+		assert!(between.x * between.x + between.y * between.y != 100.0);
+
+		assert!(contact(&first, &second, Tolerance::default()) == (ArcContact::Touch, false));
+		assert!(!contact(&first, &second, Tolerance::new(0.0).unwrap()).1);
+	}
+
+	#[test]
+	fn arcs_sharing_an_end_contain_it() {
+		// the second arc starts at the vertex the first one ends at,
+		// their circles also meet in (-5, 0), which is on neither arc
+		let first = arc((5.0, 0.0), (0.0, 5.0), 5.0, ConnectionOrientation::InnerLeft);
+		let second = arc((0.0, 5.0), (-5.0, 10.0), 5.0, ConnectionOrientation::InnerLeft);
+		let (intersection, certain) = first.intersection_with(&second, &Tolerance::default());
+		assert!(certain);
+		let points = match intersection {
+			ArcIntersection::Two(left, right) => [left, right].into_iter().flatten().collect::<Vec<_>>(),
+			_ => Vec::new(),
+		};
+		assert!(points.len() == 1 && (points[0] - Vector::new(0.0, 5.0)).length() < 1e-9);
+	}
+
+	#[test]
+	fn the_margin_at_the_ends_is_a_distance() {
+		// a point 1e-6 along a small arc from its end is 1e-8 away from it, which is within the tolerance
+		let small = arc((0.01, 0.0), (0.0, 0.01), 0.01, ConnectionOrientation::InnerLeft);
+		let point = small.center + Vector::unit_from_angle(PI / 2.0 - 1e-6) * 0.01;
+		assert!(small.contains_point(point, &Tolerance::new(1e-7).unwrap()).is_none());
+		assert!(small.contains_point(point, &Tolerance::new(1e-9).unwrap()) == Some(true));
+	}
+}
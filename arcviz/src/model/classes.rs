@@ -1,47 +1,31 @@
-use std::{iter::once, str::FromStr};
-
-use result_or_err::ResultOrErr;
-use serde::{Deserialize, Serialize};
-
 use crate::common::Number;
 
+use super::Stamp;
+
 pub type SizeId = usize;
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
 pub struct Class {
 	size: Number,
-	#[serde(default = "generate_color")]
 	color: String,
 }
 impl Class {
 	pub fn new(size: Number, color: String) -> Self {
 		Self { size, color }
 	}
-}
-impl ToString for Class {
-	fn to_string(&self) -> String {
-		format!("{} {}", self.size, self.color)
+	pub fn size(&self) -> Number {
+		self.size
 	}
-}
-impl FromStr for Class {
-	type Err = ();
-	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let (size, color) = match s.split_once(" ") {
-			Some((size, color)) => (size.parse().or_err(())?, color.to_string()),
-			None => (s.parse().or_err(())?, generate_color()),
-			_ => return Err(()),
-		};
-		Ok(Self::new(size, color))
+	pub fn color(&self) -> &str {
+		&self.color
 	}
 }
-pub fn generate_color() -> String {
-	"white".to_string()
-}
-
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
 pub struct Classes {
 	items: Vec<Class>,
 	default: Class,
+	/// Classes can't be changed, so this only identifies them (see [Self::stamp]).
+	stamp: Stamp,
 }
 impl Default for Classes {
 	fn default() -> Self {
@@ -53,16 +37,42 @@ impl Default for Classes {
 				Class::new(400.0, "yellow".to_string()),
 			],
 			default: Class::new(300.0, "white".to_string()),
+			stamp: Stamp::new(),
 		}
 	}
 }
 impl Classes {
+	/// The given classes with the given default for sizes without class.
+	pub fn new(items: Vec<Class>, default: Class) -> Self {
+		Self { items, default, stamp: Stamp::new() }
+	}
+	/// Identifies these classes (see [Stamp]).
+	pub fn stamp(&self) -> Stamp {
+		self.stamp
+	}
+	pub fn iter(&self) -> impl Iterator<Item = &Class> {
+		self.items.iter()
+	}
+	/// The class used for sizes without class.
+	pub fn default_class(&self) -> &Class {
+		&self.default
+	}
 	pub fn get_size(&self, id: SizeId) -> Number {
 		self.items.get(id).unwrap_or(&self.default).size.clone()
 	}
 	pub fn get_color(&self, id: SizeId) -> &str {
 		&self.items.get(id).unwrap_or(&self.default).color
 	}
+	/// The class whose size is closest to the given radius (the default if there are no classes).
+	pub fn closest(&self, radius: Number) -> SizeId {
+		(0..self.items.len())
+			.min_by(|a, b| (self.get_size(*a) - radius).abs().total_cmp(&(self.get_size(*b) - radius).abs()))
+			.unwrap_or(usize::MAX)
+	}
+	/// The number of classes (not counting the default).
+	pub fn len(&self) -> usize {
+		self.items.len()
+	}
 	pub fn previous(&self, id: SizeId) -> SizeId {
 		if id <= 0 {
 			return id;
@@ -76,16 +86,3 @@ impl Classes {
 		return id + 1;
 	}
 }
-impl ToString for Classes {
-	fn to_string(&self) -> String {
-		self.items.iter().chain(once(&self.default)).map(ToString::to_string).collect::<Vec<_>>().join("\n")
-	}
-}
-impl FromStr for Classes {
-	type Err = ();
-	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let mut sizes = s.split("\n").map(|p| p.parse::<Class>()).collect::<Result<Vec<_>, _>>().or_err(())?;
-		let default = sizes.pop().ok_or(())?;
-		Ok(Self { items: sizes, default })
-	}
-}
@@ -0,0 +1,149 @@
+use std::{
+	cell::RefCell,
+	collections::{BTreeSet, HashMap, HashSet},
+};
+
+use super::{Arc, DynamicSpatialIndex, Intersection, Stamp, Tolerance, VertexKey};
+
+/// A connection by the keys of its start and end vertex (which don't change when other vertices are removed).
+pub type ConnectionKey = (VertexKey, VertexKey);
+
+/// What the arc of a connection is constructed from: the [Stamp]s of the connection and its vertices.
+pub type ArcSource = [Stamp; 3];
+
+/// What all arcs are constructed from: the [Stamp]s of the vertices, the connections and the classes
+/// and the tolerance the intersections are decided with.
+pub type ConflictSource = ([Stamp; 3], Tolerance);
+
+/// The intersections between pairs of arcs, so [super::Data::conflicts] only has to test the pairs
+/// with an arc that changed (because one of its vertices moved or its class or orientation changed).
+///
+/// The arcs and the index of their bounds are kept between calls. A call only constructs the arcs whose
+/// [ArcSource] changed, moves them in the index and tests them against the arcs they overlap now.
+/// If nothing changed at all (see [ConflictSource]) the cached intersections are returned as they are.
+///
+/// Connections are identified by the keys of their vertices, so whether 2 connections share a vertex
+/// (which decides how their intersections are counted) can't change while their pair is cached.
+/// A clone starts out empty (so the snapshots of the history don't copy it).
+#[derive(Default)]
+pub struct ConflictCache {
+	inner: RefCell<CachedIntersections>,
+}
+impl Clone for ConflictCache {
+	fn clone(&self) -> Self {
+		Self::default()
+	}
+}
+
+#[derive(Default)]
+struct CachedIntersections {
+	/// What the cached intersections were computed from.
+	source: Option<ConflictSource>,
+	/// The arc of each connection (None if it can't be constructed) with what it was constructed from.
+	arcs: HashMap<ConnectionKey, (ArcSource, Option<Arc>)>,
+	/// The bounds of the arcs (of the connections that have one).
+	index: DynamicSpatialIndex<ConnectionKey>,
+	/// The intersections of each tested pair (the lower connection first).
+	pairs: HashMap<(ConnectionKey, ConnectionKey), Vec<Intersection>>,
+	/// The tested pairs that intersect.
+	intersecting: BTreeSet<(ConnectionKey, ConnectionKey)>,
+	/// The connections each connection has a tested pair with.
+	partners: HashMap<ConnectionKey, HashSet<ConnectionKey>>,
+}
+impl CachedIntersections {
+	/// Drops the arc and all pairs of the connection.
+	fn invalidate(&mut self, key: ConnectionKey) {
+		self.arcs.remove(&key);
+		self.index.remove(key);
+		for partner in self.partners.remove(&key).unwrap_or_default() {
+			let pair = (key.min(partner), key.max(partner));
+			self.pairs.remove(&pair);
+			self.intersecting.remove(&pair);
+			if let Some(partners) = self.partners.get_mut(&partner) {
+				partners.remove(&key);
+			}
+		}
+	}
+
+	/// Brings the arcs up to date and tests the pairs of the changed ones.
+	fn update(
+		&mut self,
+		connections: impl Iterator<Item = (ConnectionKey, ArcSource)>,
+		construct: impl Fn(ConnectionKey) -> Option<Arc>,
+		compute: impl Fn((ConnectionKey, &Arc), (ConnectionKey, &Arc)) -> Vec<Intersection>,
+	) {
+		let mut existing = HashSet::new();
+		let mut changed = Vec::new();
+		for (key, source) in connections {
+			existing.insert(key);
+			if self.arcs.get(&key).is_some_and(|(cached, _)| *cached == source) {
+				continue;
+			}
+			self.invalidate(key);
+			let arc = construct(key);
+			if let Some(arc) = &arc {
+				self.index.insert(key, &arc.bounds());
+				changed.push(key);
+			}
+			self.arcs.insert(key, (source, arc));
+		}
+		let removed = self.arcs.keys().copied().filter(|key| !existing.contains(key)).collect::<Vec<_>>();
+		for key in removed {
+			self.invalidate(key);
+		}
+
+		for key in changed {
+			let Some((_, Some(arc))) = self.arcs.get(&key) else { continue };
+			for other in self.index.query(&arc.bounds()) {
+				let pair = (key.min(other), key.max(other));
+				if other == key || self.pairs.contains_key(&pair) {
+					continue;
+				}
+				let (Some((_, Some(first))), Some((_, Some(second)))) = (self.arcs.get(&pair.0), self.arcs.get(&pair.1))
+				else {
+					continue;
+				};
+				let intersections = compute((pair.0, first), (pair.1, second));
+				if !intersections.is_empty() {
+					self.intersecting.insert(pair);
+				}
+				self.pairs.insert(pair, intersections);
+				self.partners.entry(pair.0).or_default().insert(pair.1);
+				self.partners.entry(pair.1).or_default().insert(pair.0);
+			}
+		}
+	}
+}
+
+impl ConflictCache {
+	/// The intersections of all pairs of arcs that intersect (the lower connection first).
+	///
+	/// - source: what the arcs are constructed from (if it didn't change the arguments below are not used,
+	///   if the classes or the tolerance changed the cache is dropped)
+	/// - connections: all connections with what their arc is constructed from
+	/// - construct: the arc of a connection that changed (None if it can't be constructed)
+	/// - compute: the intersections of a pair that is not cached
+	pub fn intersections(
+		&self,
+		source: ConflictSource,
+		connections: impl Iterator<Item = (ConnectionKey, ArcSource)>,
+		construct: impl Fn(ConnectionKey) -> Option<Arc>,
+		compute: impl Fn((ConnectionKey, &Arc), (ConnectionKey, &Arc)) -> Vec<Intersection>,
+	) -> Vec<(ConnectionKey, ConnectionKey, Vec<Intersection>)> {
+		let mut cache = self.inner.borrow_mut();
+		if cache.source != Some(source) {
+			// the arcs of all connections depend on the classes and all intersections on the tolerance
+			let basis = |([_, _, classes], tolerance): ConflictSource| (classes, tolerance);
+			if cache.source.map(basis) != Some(basis(source)) {
+				*cache = CachedIntersections::default();
+			}
+			cache.update(connections, construct, compute);
+			cache.source = Some(source);
+		}
+		cache
+			.intersecting
+			.iter()
+			.map(|&(first, second)| (first, second, cache.pairs[&(first, second)].clone()))
+			.collect()
+	}
+}
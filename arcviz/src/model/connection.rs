@@ -1,5 +1,3 @@
-use serde::{Deserialize, Serialize};
-
 use crate::model::SizeId;
 
 /// With which arc to form the connection.
@@ -7,7 +5,7 @@ use crate::model::SizeId;
 /// Left and right here refer to whether the connections curves left(+) or right(-) in a standard cartesian coordinate system.
 /// On a screen this is likely reversed.
 /// Meaning left or right would determine whether the arc is on the left or right of the straight line connecting the points.
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Copy, Clone)]
 pub enum ConnectionOrientation {
 	InnerRight,
 	InnerLeft,
@@ -27,6 +25,7 @@ impl ConnectionOrientation {
 			ConnectionOrientation::OuterLeft => ConnectionOrientation::InnerRight,
 		}
 	}
+	/// Returns the orientation that yields the same arc when start and end are swapped.
 	pub fn flipped(self) -> Self {
 		match self {
 			ConnectionOrientation::InnerRight => ConnectionOrientation::InnerLeft,
@@ -56,6 +55,24 @@ impl ConnectionOrientation {
 			ConnectionOrientation::OuterRight | ConnectionOrientation::OuterLeft => false,
 		}
 	}
+	/// The name used in files (`right`, `left` for inner and `Right`, `Left` for outer arcs).
+	pub fn name(&self) -> &'static str {
+		match self {
+			ConnectionOrientation::InnerRight => "right",
+			ConnectionOrientation::InnerLeft => "left",
+			ConnectionOrientation::OuterRight => "Right",
+			ConnectionOrientation::OuterLeft => "Left",
+		}
+	}
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"right" => Some(ConnectionOrientation::InnerRight),
+			"left" => Some(ConnectionOrientation::InnerLeft),
+			"Right" => Some(ConnectionOrientation::OuterRight),
+			"Left" => Some(ConnectionOrientation::OuterLeft),
+			_ => None,
+		}
+	}
 	/// Whether the center of the arc is to the left of the straight line connecting the points.
 	pub fn center_is_left(&self) -> bool {
 		match self {
@@ -65,7 +82,7 @@ impl ConnectionOrientation {
 	}
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
 pub struct Connection {
 	pub orientation: ConnectionOrientation,
 	// pub radius: Number, // TODO: make this a reference to a ConnectionSize and store that as Resources in the Stage
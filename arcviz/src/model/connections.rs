@@ -1,142 +1,157 @@
-use std::str::FromStr;
+use std::collections::{btree_map, BTreeMap, BTreeSet};
 
-use serde::{Deserialize, Serialize};
+use super::{Connection, Stamp, VertexKey};
 
-use crate::model::{ConnectionOrientation, SizeId};
-
-use super::{Connection, VertexId};
-
-/// Connections is split into layers.
-/// This is because the number of layers corresponds to the number of vertices.
-/// And this way adding/removing a vertex corresponds to just adding/removing a layer.
-/// So only the end of the vector is modified without shifting the remaining data.
+/// Connections is stored as sparse adjacency lists.
 ///
-/// row/column index:   in-layer index:
-/// 0 | 0 | 0 | ...     0 | 2 | 4 | ...
-/// --/   |   |         --/   |   |
-/// 0   1 | 1           0   1 | 3
-/// ------/             ------/
-/// 0   1   2           0   1   2
+/// Each vertex has a row with its outgoing connections (by end vertex) and a column with the start vertices
+/// of its incoming connections. So memory and iteration only grow with the number of connections,
+/// and removing a vertex only touches the rows and columns of its neighbours.
 ///
-/// previous layer size:
-/// 0 | 1 | 4 | ...
-/// --/   |   |
-/// 1   1 | 4
-/// ------/
-/// 4   4   4
-///
-/// global index = in_layer_index + previous layer size:
-/// 0 | 3 | 8 | ...
-/// --/   |   |
-/// 1   2 | 7
-/// ------/
-/// 4   5   6
-#[derive(Clone, Serialize, Deserialize)]
+/// The vertices are identified by their [VertexKey] (see [super::Vertices::slot] for their current position),
+/// so removing a vertex doesn't renumber anything.
+/// Only entries of vertices that were added (and not removed again) exist.
+#[derive(Clone, Default)]
 pub struct Connections {
-	items: Vec<Option<Connection>>,
-	size: VertexId,
+	rows: BTreeMap<VertexKey, BTreeMap<VertexKey, Entry>>,
+	columns: BTreeMap<VertexKey, BTreeSet<VertexKey>>,
+	/// Renewed whenever a connection may change (see [Self::stamp]).
+	stamp: Stamp,
+}
+
+/// The entry of vertex pairs without connection.
+const NO_CONNECTION: &Option<Connection> = &None;
+
+#[derive(Clone)]
+struct Entry {
+	/// None if the entry was accessed through [Connections::entry_mut] but holds no connection.
+	connection: Option<Connection>,
+	/// Renewed whenever the connection may change.
+	stamp: Stamp,
+}
+impl Entry {
+	fn new(connection: Option<Connection>) -> Self {
+		Self { connection, stamp: Stamp::new() }
+	}
 }
 
 impl Connections {
-	pub fn new(size: VertexId) -> Self {
-		Self { items: vec![None; size.pow(2)], size }
+	pub fn new() -> Self {
+		Self::default()
 	}
-	fn yx_to_global(y: VertexId, x: VertexId) -> usize {
-		let (layer_id, in_layer_index) = match (y, x) {
-			// y: start; x: end
-			(s, e) if e > s => (e, 2 * e - s),
-			(s, e) => (s, e),
-		};
-		let layer_offset = layer_id.pow(2);
-		layer_offset + in_layer_index
+	fn contains(&self, start_vertex: VertexKey, end_vertex: VertexKey) -> bool {
+		self.rows.contains_key(&start_vertex) && self.rows.contains_key(&end_vertex)
 	}
-	// TODO: implement global_to_yx
 
 	/// Gets the specified connection entry if the entry exists.
-	pub fn entry(&self, start_vertex: VertexId, end_vertex: VertexId) -> Result<&Option<Connection>, ()> {
-		let global_index = Self::yx_to_global(start_vertex, end_vertex);
-		self.items.get(global_index).ok_or(())
+	pub fn entry(&self, start_vertex: VertexKey, end_vertex: VertexKey) -> Result<&Option<Connection>, ()> {
+		match self.contains(start_vertex, end_vertex) {
+			true => Ok(self.rows[&start_vertex].get(&end_vertex).map_or(NO_CONNECTION, |entry| &entry.connection)),
+			false => Err(()),
+		}
 	}
 	/// Gets the specified connection entry if the entry exists.
 	pub fn entry_mut(
 		&mut self,
-		start_vertex: VertexId,
-		end_vertex: VertexId,
+		start_vertex: VertexKey,
+		end_vertex: VertexKey,
 	) -> Result<&mut Option<Connection>, ()> {
-		let global_index = Self::yx_to_global(start_vertex, end_vertex);
-		self.items.get_mut(global_index).ok_or(())
+		if !self.contains(start_vertex, end_vertex) {
+			return Err(());
+		}
+		self.stamp = Stamp::new();
+		// the column has to know the entry in case a connection is put into it
+		self.columns.entry(end_vertex).or_default().insert(start_vertex);
+		let entry =
+			self.rows.entry(start_vertex).or_default().entry(end_vertex).or_insert_with(|| Entry::new(None));
+		entry.stamp = Stamp::new();
+		Ok(&mut entry.connection)
+	}
+	/// Replaces the specified connection entry (None removes the connection) if the entry exists.
+	///
+	/// Returns the previous connection.
+	pub fn set(
+		&mut self,
+		start_vertex: VertexKey,
+		end_vertex: VertexKey,
+		connection: Option<Connection>,
+	) -> Result<Option<Connection>, ()> {
+		if !self.contains(start_vertex, end_vertex) {
+			return Err(());
+		}
+		self.stamp = Stamp::new();
+		let row = self.rows.entry(start_vertex).or_default();
+		Ok(match connection {
+			Some(connection) => {
+				self.columns.entry(end_vertex).or_default().insert(start_vertex);
+				row.insert(end_vertex, Entry::new(Some(connection))).and_then(|entry| entry.connection)
+			},
+			None => {
+				self.columns.entry(end_vertex).or_default().remove(&start_vertex);
+				row.remove(&end_vertex).and_then(|entry| entry.connection)
+			},
+		})
 	}
 	/// Gets the specified connection if the connection exists.
-	pub fn get(&self, start_vertex: VertexId, end_vertex: VertexId) -> Option<&Connection> {
-		self.entry(start_vertex, end_vertex).ok()?.as_ref()
+	pub fn get(&self, start_vertex: VertexKey, end_vertex: VertexKey) -> Option<&Connection> {
+		self.rows.get(&start_vertex)?.get(&end_vertex)?.connection.as_ref()
 	}
 	/// Gets the specified connection if the connection exists.
-	pub fn get_mut(&mut self, start_vertex: VertexId, end_vertex: VertexId) -> Option<&mut Connection> {
-		self.entry_mut(start_vertex, end_vertex).ok()?.as_mut()
-	}
-	/// Adds or removes rows and columns at the end to meet the specified size.
-	pub fn resize(&mut self, size: VertexId) {
-		self.size = size;
-		let target_length = self.size.pow(2);
-		self.items.resize(target_length, None);
-	}
-	/// Removes the row and column that contains the specified vertex.
-	/// Thus reducing the size of the connection matrix by 1.
-	pub fn shrink_by_vertex(&mut self, vertex: VertexId) -> Result<(), ()> {
-		// OPTIMIZE: this is O(n^2) and could be O(n)
-		if vertex >= self.size {
+	pub fn get_mut(&mut self, start_vertex: VertexKey, end_vertex: VertexKey) -> Option<&mut Connection> {
+		self.stamp = Stamp::new();
+		let entry = self.rows.get_mut(&start_vertex)?.get_mut(&end_vertex)?;
+		entry.stamp = Stamp::new();
+		entry.connection.as_mut()
+	}
+	/// Adds the entries of a new vertex (without connections).
+	pub fn add_vertex(&mut self, vertex: VertexKey) {
+		self.rows.entry(vertex).or_default();
+		self.columns.entry(vertex).or_default();
+	}
+	/// Removes the specified vertex with all its connections.
+	///
+	/// This is O(d log d) for the neighbours of the removed vertex, the other vertices keep their keys.
+	pub fn remove_vertex(&mut self, vertex: VertexKey) -> Result<(), ()> {
+		let (Some(row), Some(column)) = (self.rows.remove(&vertex), self.columns.remove(&vertex)) else {
 			return Err(());
+		};
+		self.stamp = Stamp::new();
+		for end in row.into_keys() {
+			if let Some(column) = self.columns.get_mut(&end) {
+				column.remove(&vertex);
+			}
 		}
-		for layer in (vertex + 1..self.size).rev() {
-			self.remove_row_from_layer(vertex, layer);
-			self.remove_col_from_layer(vertex, layer);
+		for start in column {
+			if let Some(row) = self.rows.get_mut(&start) {
+				row.remove(&vertex);
+			}
 		}
-		self.remove_layer(vertex);
-		self.size -= 1;
 		Ok(())
 	}
-	fn remove_layer(&mut self, layer: VertexId) {
-		let layer_offset = layer.pow(2);
-		let layer_size = 2 * layer + 1;
-		self.items.drain(layer_offset..layer_offset + layer_size);
-	}
-	fn remove_col_from_layer(&mut self, col: VertexId, layer: VertexId) {
-		let layer_offset = layer.pow(2);
-		let in_layer_index = col;
-		let global_index = layer_offset + in_layer_index;
-		self.items.remove(global_index);
+
+	/// Identifies the current connections (it changes whenever they might have, see [Stamp]).
+	pub fn stamp(&self) -> Stamp {
+		self.stamp
 	}
-	fn remove_row_from_layer(&mut self, row: VertexId, layer: VertexId) {
-		let layer_offset = layer.pow(2);
-		let in_layer_index = 2 * layer - row;
-		let global_index = layer_offset + in_layer_index;
-		self.items.remove(global_index);
+	/// Identifies each current connection (like [Self::stamp] but per connection).
+	pub fn stamps(&self) -> impl Iterator<Item = (VertexKey, VertexKey, Stamp)> + '_ {
+		self.rows.iter().flat_map(|(start, row)| {
+			let connections = row.iter().filter(|(_, entry)| entry.connection.is_some());
+			connections.map(|(end, entry)| (*start, *end, entry.stamp))
+		})
 	}
 
-	pub fn foreach(&self, mut action: impl FnMut(VertexId, VertexId, &Connection)) {
-		// TODO: check correctness
-		let mut layer_id = 0;
-		let mut in_layer_index = 0;
-		for connection in self.items.iter() {
-			let layer_size = 2 * layer_id + 1;
-			let (start, end) = match in_layer_index {
-				i if i <= layer_id => (layer_id, i),
-				i => (layer_size - i - 1, layer_id),
-			};
-
-			in_layer_index += 1;
-			if in_layer_index >= layer_size {
-				layer_id += 1;
-				in_layer_index = 0;
+	pub fn foreach(&self, mut action: impl FnMut(VertexKey, VertexKey, &Connection)) {
+		self.fast_iter().for_each(|(start, end, connection)| {
+			if let Some(connection) = connection {
+				action(start, end, connection);
 			}
-
-			let Some(connection) = connection else {
-				continue;
-			};
-			action(start, end, connection);
-		}
+		});
 	}
 
+	/// All entries that were set, ordered by start and then end vertex.
+	///
+	/// This skips the vertex pairs without connection, unless they were accessed through [Self::entry_mut].
 	pub fn fast_iter(&self) -> FastConnectionsIterator {
 		FastConnectionsIterator::new(&self)
 	}
@@ -144,122 +159,26 @@ impl Connections {
 
 #[derive(Clone)]
 pub struct FastConnectionsIterator<'a> {
-	connections: &'a Connections,
-	current: usize,
-	layer_id: usize,
-	in_layer_index: usize,
+	rows: btree_map::Iter<'a, VertexKey, BTreeMap<VertexKey, Entry>>,
+	current: Option<(VertexKey, btree_map::Iter<'a, VertexKey, Entry>)>,
 }
 impl<'a> FastConnectionsIterator<'a> {
 	fn new(connections: &'a Connections) -> Self {
-		Self { connections, current: 0, layer_id: 0, in_layer_index: 0 }
+		Self { rows: connections.rows.iter(), current: None }
 	}
 }
 impl<'a> Iterator for FastConnectionsIterator<'a> {
-	type Item = (VertexId, VertexId, &'a Option<Connection>);
+	type Item = (VertexKey, VertexKey, &'a Option<Connection>);
 
 	fn next(&mut self) -> Option<Self::Item> {
-		let connection = self.connections.items.get(self.current)?;
-
-		let layer_size = 2 * self.layer_id + 1;
-		let (start, end) = match self.in_layer_index {
-			i if i <= self.layer_id => (self.layer_id, i),
-			i => (layer_size - i - 1, self.layer_id),
-		};
-
-		self.current += 1;
-		self.in_layer_index += 1;
-		if self.in_layer_index >= layer_size {
-			self.layer_id += 1;
-			self.in_layer_index = 0;
-		}
-
-		return Some((start, end, connection));
-	}
-}
-
-struct ConnectionRepresentation {
-	pub start: VertexId,
-	pub end: VertexId,
-	pub orientation: ConnectionOrientation,
-	pub size: SizeId,
-}
-
-impl ToString for ConnectionRepresentation {
-	fn to_string(&self) -> String {
-		let orientation = match &self.orientation {
-			ConnectionOrientation::InnerRight => "right",
-			ConnectionOrientation::InnerLeft => "left",
-			ConnectionOrientation::OuterRight => "Right",
-			ConnectionOrientation::OuterLeft => "Left",
-		};
-		format!("{} {} {} {}", self.start + 1, self.end + 1, orientation, self.size + 1)
-	}
-}
-impl FromStr for ConnectionRepresentation {
-	type Err = ();
-	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let parts = s.split_whitespace().collect::<Vec<_>>();
-		let (Some(&start), Some(&end), Some(&orientation), Some(&size)) =
-			(parts.get(0), parts.get(1), parts.get(2), parts.get(3))
-		else {
-			return Err(());
-		};
-		let (Ok(start), Ok(end), orientation, Ok(size)) =
-			(start.parse::<VertexId>(), end.parse::<VertexId>(), orientation, size.parse::<SizeId>())
-		else {
-			return Err(());
-		};
-		let orientation = match orientation {
-			"right" => ConnectionOrientation::InnerRight,
-			"left" => ConnectionOrientation::InnerLeft,
-			"Right" => ConnectionOrientation::OuterRight,
-			"Left" => ConnectionOrientation::OuterLeft,
-			_ => return Err(()),
-		};
-		Ok(Self { start: start - 1, end: end - 1, orientation, size: size - 1 })
-	}
-}
-
-impl ToString for Connections {
-	fn to_string(&self) -> String {
-		self
-			.fast_iter()
-			.map(|(start, end, connection)| {
-				Some(ConnectionRepresentation {
-					start,
-					end,
-					orientation: connection.as_ref()?.orientation,
-					size: connection.as_ref()?.size,
-				})
-			})
-			.flatten()
-			.map(|c| c.to_string())
-			.collect::<Vec<_>>()
-			.join("\n")
-	}
-}
-impl FromStr for Connections {
-	type Err = ();
-	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let mut size = 0;
-		let items: Vec<ConnectionRepresentation> = s
-			.lines()
-			.map(|line| {
-				let connection = ConnectionRepresentation::from_str(line);
-				if let Ok(connection) = &connection {
-					size = size.max(connection.start + 1);
-					size = size.max(connection.end + 1);
+		loop {
+			if let Some((start, row)) = &mut self.current {
+				if let Some((end, entry)) = row.next() {
+					return Some((*start, *end, &entry.connection));
 				}
-				connection
-			})
-			.collect::<Result<_, _>>()?;
-		let mut connections = Connections::new(size);
-		for item in items {
-			if let Ok(entry) = connections.entry_mut(item.start, item.end) {
-				let connection = Connection { orientation: item.orientation, size: item.size };
-				*entry = Some(connection);
 			}
+			let (start, row) = self.rows.next()?;
+			self.current = Some((*start, row.iter()));
 		}
-		Ok(connections)
 	}
 }
@@ -0,0 +1,125 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use super::{Stamp, VertexId, VertexKey};
+
+/// An undirected edge given by its end vertices (lower index first).
+pub type Edge = (VertexId, VertexId);
+/// An undirected edge given by the keys of its end vertices (lower key first).
+pub type KeyEdge = (VertexKey, VertexKey);
+
+/// Creates the [Edge] (or [KeyEdge]) between the given vertices regardless of their order.
+pub fn edge<T: Ord + Copy>(a: T, b: T) -> (T, T) {
+	(a.min(b), a.max(b))
+}
+
+/// Whether 2 edges share an end vertex.
+pub fn edges_are_adjacent<T: PartialEq>(a: &(T, T), b: &(T, T)) -> bool {
+	a.0 == b.0 || a.0 == b.1 || a.1 == b.0 || a.1 == b.1
+}
+
+/// How a pair of edges is supposed to intersect.
+#[derive(Copy, Clone, PartialEq)]
+pub enum IntersectionConstraint {
+	/// The edges need to intersect in exactly 1 place.
+	Necessary,
+	/// The edges may not intersect in any place.
+	Forbidden,
+	/// The edges may or may not intersect in 1 place.
+	Irrelevant,
+}
+impl IntersectionConstraint {
+	pub fn name(&self) -> &'static str {
+		match self {
+			IntersectionConstraint::Necessary => "necessary",
+			IntersectionConstraint::Forbidden => "forbidden",
+			IntersectionConstraint::Irrelevant => "irrelevant",
+		}
+	}
+}
+impl FromStr for IntersectionConstraint {
+	type Err = ();
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"necessary" => Ok(IntersectionConstraint::Necessary),
+			"forbidden" => Ok(IntersectionConstraint::Forbidden),
+			"irrelevant" => Ok(IntersectionConstraint::Irrelevant),
+			_ => Err(()),
+		}
+	}
+}
+
+/// The intersection constraints between pairs of edges.
+///
+/// Conceptually this is a matrix with one entry per edge pair (like [super::Connections] has one per vertex pair).
+/// Since the number of edge pairs grows with the 4th power of the number of vertices it is stored sparsely though:
+/// Only entries that differ from the default ([IntersectionConstraint::Irrelevant]) are kept.
+/// The edges are given by the keys of their vertices, so removing a vertex doesn't renumber the entries
+/// (see [super::Data::constraint] for the lookup by [Edge]).
+///
+/// The base constraints can not be overridden:
+/// - edges that share a vertex must not intersect (they are always [IntersectionConstraint::Forbidden])
+/// - no edge pair may intersect twice (this is enforced when classifying conflicts)
+#[derive(Clone, Default)]
+pub struct Constraints {
+	items: BTreeMap<(KeyEdge, KeyEdge), IntersectionConstraint>,
+	/// Renewed whenever a constraint may change (see [Self::stamp]).
+	stamp: Stamp,
+}
+
+impl Constraints {
+	/// The key of the entry for the given edge pair, if the pair can be constrained at all.
+	fn key(a: KeyEdge, b: KeyEdge) -> Result<(KeyEdge, KeyEdge), ()> {
+		let (a, b) = (edge(a.0, a.1), edge(b.0, b.1));
+		if a == b || edges_are_adjacent(&a, &b) {
+			return Err(());
+		}
+		Ok((a.min(b), a.max(b)))
+	}
+
+	/// Gets the constraint for the given edge pair (including base constraints).
+	pub fn get(&self, a: KeyEdge, b: KeyEdge) -> IntersectionConstraint {
+		match Self::key(a, b) {
+			Ok(key) => self.items.get(&key).copied().unwrap_or(IntersectionConstraint::Irrelevant),
+			Err(()) => IntersectionConstraint::Forbidden,
+		}
+	}
+	/// Sets the constraint for the given edge pair.
+	///
+	/// Fails if the pair is governed by a base constraint, in which case nothing is stored.
+	pub fn set(&mut self, a: KeyEdge, b: KeyEdge, constraint: IntersectionConstraint) -> Result<(), ()> {
+		let key = Self::key(a, b)?;
+		self.stamp = Stamp::new();
+		match constraint {
+			IntersectionConstraint::Irrelevant => self.items.remove(&key),
+			constraint => self.items.insert(key, constraint),
+		};
+		Ok(())
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (KeyEdge, KeyEdge, IntersectionConstraint)> + '_ {
+		self.items.iter().map(|(&(a, b), &constraint)| (a, b, constraint))
+	}
+	pub fn is_empty(&self) -> bool {
+		self.items.is_empty()
+	}
+	/// Identifies the current constraints (it changes whenever they might have, see [Stamp]).
+	pub fn stamp(&self) -> Stamp {
+		self.stamp
+	}
+
+	/// Removes all entries for which the predicate returns false.
+	pub fn retain(&mut self, mut predicate: impl FnMut(&KeyEdge, &KeyEdge) -> bool) {
+		self.stamp = Stamp::new();
+		self.items.retain(|(a, b), _| predicate(a, b));
+	}
+	/// Removes all entries involving the given edge.
+	pub fn remove_edge(&mut self, removed: KeyEdge) {
+		let removed = edge(removed.0, removed.1);
+		self.retain(|a, b| *a != removed && *b != removed);
+	}
+	/// Removes all entries involving the given vertex (the others are not affected).
+	pub fn remove_vertex(&mut self, vertex: VertexKey) {
+		let contains = |e: &KeyEdge| e.0 == vertex || e.1 == vertex;
+		self.retain(|a, b| !contains(a) && !contains(b));
+	}
+}
@@ -1,29 +1,47 @@
-use std::{str::FromStr, usize};
-
-use serde::{Deserialize, Serialize};
+use std::{
+	collections::{HashMap, HashSet},
+	usize,
+};
 
 use crate::{
 	common::{Bounds, Number, Vector},
-	model::{Classes, SizeId},
+	model::{Classes, Settings, SizeId},
 	render::RenderTarget,
 };
 
 use super::{
-	Arc, ArcIntersection, Connection, ConnectionOrientation, Connections, Vertex, VertexId, Vertices,
+	edge, edges_are_adjacent, Arc, ArcContact, ArcIntersection, Cached, ConflictCache, Connection,
+	ConnectionKey, ConnectionOrientation, Connections, Constraints, Edge, IntersectionConstraint, KeyEdge,
+	SpatialIndex, Stamp, Tolerance, Vertex, VertexId, VertexKey, Vertices,
 };
 
-#[derive(Clone, Serialize, Deserialize)]
-#[serde(from = "DataRepresentation", into = "DataRepresentation")]
+/// All connections with their bounds and the index of those bounds.
+type ConnectionIndex = (Vec<(VertexId, VertexId, ConnectionKind, Bounds)>, SpatialIndex);
+/// All conflicts and the index of their positions.
+type ConflictIndex = (Vec<Conflict>, SpatialIndex);
+
+/// A drawing.
+///
+/// The connections and constraints refer to their vertices by [VertexKey], so they don't change when
+/// a vertex is removed. The methods take and return [VertexId]s and translate them (see [Vertices::slot]).
+#[derive(Clone)]
 pub struct Data {
 	pub vertices: Vertices,
 	connections: Connections,
+	constraints: Constraints,
 	pub classes: Classes,
+	/// How borderline intersections are decided (see [Arc::intersection_with]).
+	pub tolerance: Tolerance,
+	conflict_cache: ConflictCache,
+	/// The indices for picking, built again only when their content changes (see [Stamp]).
+	vertex_index: Cached<Stamp, SpatialIndex>,
+	connection_index: Cached<[Stamp; 3], ConnectionIndex>,
+	conflict_index: Cached<([Stamp; 4], Tolerance), ConflictIndex>,
 }
 
 impl Default for Data {
 	fn default() -> Self {
-		let mut this =
-			Self { vertices: Vertices::default(), connections: Connections::new(0), classes: Classes::default() };
+		let mut this = Self::new(Classes::default());
 
 		this.add_vertex(Vertex::new(Vector::new(50.0, 50.0)));
 		this.add_vertex(Vertex::new(Vector::new(50.0, 150.0)));
@@ -40,10 +58,23 @@ impl Default for Data {
 }
 
 impl Data {
+	/// An empty drawing with the given classes.
+	pub fn new(classes: Classes) -> Self {
+		Self {
+			vertices: Vertices::default(),
+			connections: Connections::new(),
+			constraints: Constraints::default(),
+			classes,
+			tolerance: Tolerance::default(),
+			conflict_cache: ConflictCache::default(),
+			vertex_index: Cached::default(),
+			connection_index: Cached::default(),
+			conflict_index: Cached::default(),
+		}
+	}
 	pub fn vertices_in(&self, region: Bounds) -> Vec<VertexId> {
 		self
 			.vertices
-			.items
 			.iter()
 			.enumerate()
 			.filter(|&(_, v)| region.contains(v.position))
@@ -52,49 +83,96 @@ impl Data {
 	}
 	pub fn closest_vertex_to(&self, position: &Vector) -> Option<(VertexId, Number)> {
 		// DESIGN: put cutoff distance as param here?
-		let (i, d) = self
-			.vertices
-			.items
-			.iter()
-			.map(|v| (v.position.clone() - position.clone()).length())
-			.enumerate()
-			.min_by(|(_, a), (_, b)| (*a).total_cmp(b))?;
-		Some((i, d))
+		let index = self
+			.vertex_index
+			.get(self.vertices.stamp(), || SpatialIndex::new(self.vertices.iter().map(|v| Bounds::from(v.position))));
+		index.nearest(*position, |i| (self.vertices[i].position - *position).length())
 	}
 	pub fn closest_connection_to(&self, position: &Vector) -> Option<(VertexId, VertexId, Number)> {
 		// DESIGN: put cutoff distance as param here?
-		let (start, end, distance) = self
-			.connections()
-			.flat_map(|(start, end, conn)| {
-				let distance = match conn {
-					ConnectionKind::Arc(arc) => arc.distance_to(*position),
-					ConnectionKind::Unreachable => {
-						let start = self.vertices.items.get(start)?.position;
-						let end = self.vertices.items.get(end)?.position;
-
-						let start_end = end - start;
-						let start_point = *position - start;
-						let end_point = *position - end;
-
-						let distance = start_point.length().min(end_point.length());
-
-						if start_end.is_ahead(&start_point) && start_end.is_behind(&end_point) {
-							distance.min(start_point.rejection_on(&start_end).length())
-						} else {
-							distance
-						}
-					},
-				};
-				Some((start, end, distance))
-			})
-			.min_by(|(_, _, a), (_, _, b)| (*a).total_cmp(b))?;
+		let stamps = [self.vertices.stamp(), self.connections.stamp(), self.classes.stamp()];
+		let connections = self.connection_index.get(stamps, || {
+			let connections = self
+				.connections()
+				.flat_map(|(start, end, conn)| {
+					let bounds = match &conn {
+						ConnectionKind::Arc(arc) => arc.bounds(),
+						ConnectionKind::Unreachable => {
+							let start = self.vertices.get(start)?.position;
+							let end = self.vertices.get(end)?.position;
+							Bounds::from(start).combined_with(&end.into())
+						},
+					};
+					Some((start, end, conn, bounds))
+				})
+				.collect::<Vec<_>>();
+			let index = SpatialIndex::new(connections.iter().map(|(_, _, _, bounds)| bounds.clone()));
+			(connections, index)
+		});
+		let (connections, index) = &*connections;
+		let (i, distance) = index.nearest(*position, |i| {
+			let (start, end, conn, _) = &connections[i];
+			match conn {
+				ConnectionKind::Arc(arc) => arc.distance_to(*position),
+				ConnectionKind::Unreachable => {
+					let start = self.vertices[*start].position;
+					let end = self.vertices[*end].position;
+
+					let start_end = end - start;
+					let start_point = *position - start;
+					let end_point = *position - end;
+
+					let distance = start_point.length().min(end_point.length());
+
+					if start_end.is_ahead(&start_point) && start_end.is_behind(&end_point) {
+						distance.min(start_point.rejection_on(&start_end).length())
+					} else {
+						distance
+					}
+				},
+			}
+		})?;
+		let (start, end, _, _) = connections[i];
 		Some((start, end, distance))
 	}
+	pub fn closest_conflict_to(&self, position: &Vector) -> Option<(Conflict, Number)> {
+		// DESIGN: put cutoff distance as param here?
+		let stamps =
+			[self.vertices.stamp(), self.connections.stamp(), self.constraints.stamp(), self.classes.stamp()];
+		let conflicts = self.conflict_index.get((stamps, self.tolerance), || {
+			let conflicts = self.conflicts();
+			let index = SpatialIndex::new(conflicts.iter().map(|conflict| Bounds::from(conflict.position)));
+			(conflicts, index)
+		});
+		let (conflicts, index) = &*conflicts;
+		let (i, distance) = index.nearest(*position, |i| (conflicts[i].position - *position).length())?;
+		Some((conflicts[i].clone(), distance))
+	}
 	pub fn add_vertex(&mut self, vertex: Vertex) -> VertexId {
-		self.vertices.add(vertex);
-		self.connections.resize(self.vertices.len());
-		return self.vertices.len() - 1;
+		self.add_vertex_with_key(vertex, None)
+	}
+	/// Adds the vertex with the given key (see [Vertices::add_with_key]).
+	pub fn add_vertex_with_key(&mut self, vertex: Vertex, key: Option<VertexKey>) -> VertexId {
+		let id = self.vertices.add_with_key(vertex, key);
+		self.connections.add_vertex(self.vertices.key(id).expect("the vertex was just added"));
+		return id;
+	}
+	/// The keys of the vertices (see [Vertices::key]).
+	fn keys(&self, start: VertexId, end: VertexId) -> Result<(VertexKey, VertexKey), ()> {
+		Ok((self.vertices.key(start).ok_or(())?, self.vertices.key(end).ok_or(())?))
 	}
+	/// The edge between the vertices in keys (see [Constraints]).
+	fn key_edge(&self, (a, b): Edge) -> Result<KeyEdge, ()> {
+		let (a, b) = self.keys(a, b)?;
+		Ok(edge(a, b))
+	}
+	/// The edge between the vertices with the given keys in ids.
+	fn id_edge(&self, (a, b): KeyEdge) -> Option<Edge> {
+		Some(edge(self.vertices.slot(a)?, self.vertices.slot(b)?))
+	}
+	/// Connects the vertices, replacing any connection between them.
+	///
+	/// (a, b) and (b, a) are the same edge, so a connection in the other direction is replaced as well.
 	pub fn add_connection(
 		&mut self,
 		start: VertexId,
@@ -102,20 +180,62 @@ impl Data {
 		orientation: ConnectionOrientation,
 		size: SizeId,
 	) -> Result<(), ()> {
-		let entry = self.connections.entry_mut(start, end)?;
-		*entry = Some(Connection::new(orientation, size));
+		let (start, end) = self.keys(start, end)?;
+		if start != end {
+			self.connections.set(end, start, None)?;
+		}
+		self.connections.set(start, end, Some(Connection::new(orientation, size)))?;
 		Ok(())
 	}
+	/// Removes the vertex with its connections and constraints, every later vertex moves down by 1.
 	pub fn remove_vertex(&mut self, id: VertexId) -> Result<(), ()> {
-		self.connections.shrink_by_vertex(id)?;
+		let key = self.vertices.key(id).ok_or(())?;
+		self.connections.remove_vertex(key)?;
 		self.vertices.remove(id);
+		self.constraints.remove_vertex(key);
 		Ok(())
 	}
 	pub fn remove_connection(&mut self, start: VertexId, end: VertexId) -> Result<(), ()> {
-		let Ok(entry) = self.connections.entry_mut(start, end) else { return Err(()) };
-		*entry = None;
+		let (start_key, end_key) = self.keys(start, end)?;
+		self.connections.set(start_key, end_key, None)?;
+		if !self.has_connection(start, end) {
+			self.constraints.remove_edge(edge(start_key, end_key));
+		}
 		Ok(())
 	}
+	/// Whether the vertices are connected (in any direction).
+	pub fn has_connection(&self, a: VertexId, b: VertexId) -> bool {
+		self.connection_between(a, b).is_some() || self.connection_between(b, a).is_some()
+	}
+	/// The connection from start to end (without constructing its arc).
+	fn connection_between(&self, start: VertexId, end: VertexId) -> Option<&Connection> {
+		let (start, end) = self.keys(start, end).ok()?;
+		self.connections.get(start, end)
+	}
+	/// The intersection constraint for the given edge pair (including base constraints).
+	pub fn constraint(&self, a: Edge, b: Edge) -> IntersectionConstraint {
+		match (self.key_edge(a), self.key_edge(b)) {
+			(Ok(a), Ok(b)) => self.constraints.get(a, b),
+			_ if edge(a.0, a.1) == edge(b.0, b.1) || edges_are_adjacent(&a, &b) => IntersectionConstraint::Forbidden,
+			_ => IntersectionConstraint::Irrelevant,
+		}
+	}
+	/// Sets the intersection constraint for the given edge pair.
+	///
+	/// Fails if either edge does not exist or if the pair is governed by a base constraint.
+	pub fn set_constraint(&mut self, a: Edge, b: Edge, constraint: IntersectionConstraint) -> Result<(), ()> {
+		if !self.has_connection(a.0, a.1) || !self.has_connection(b.0, b.1) {
+			return Err(());
+		}
+		self.constraints.set(self.key_edge(a)?, self.key_edge(b)?, constraint)
+	}
+	/// All constraints that differ from the default, each edge and each pair with the lower vertex first.
+	pub fn constraints(&self) -> impl Iterator<Item = (Edge, Edge, IntersectionConstraint)> + '_ {
+		self.constraints.iter().flat_map(|(a, b, constraint)| {
+			let (a, b) = (self.id_edge(a)?, self.id_edge(b)?);
+			Some((a.min(b), a.max(b), constraint))
+		})
+	}
 	/// Duplicates the specified subgraph.
 	///
 	/// That includes all the specified vertices and all the connections bewtween exclusively those vertices.
@@ -128,314 +248,396 @@ impl Data {
 		let first = self.vertices.len();
 
 		let clones =
-			vertices.iter().map(|id| Some((*id, self.vertices.items.get(*id)?.clone()))).flatten().collect::<Vec<_>>();
+			vertices.iter().map(|id| Some((*id, self.vertices.get(*id)?.clone()))).flatten().collect::<Vec<_>>();
 		let count = clones.len();
 		let old_ids = clones
 			.into_iter()
 			.map(|(old_id, clone)| {
-				self.vertices.add(clone);
+				self.add_vertex(clone);
 				old_id
 			})
 			.collect::<Vec<_>>();
-		self.connections.resize(self.vertices.len());
 
 		for (start_offset, start_old) in old_ids.iter().enumerate() {
 			let start_new = first + start_offset;
 			for (end_offset, end_old) in old_ids.iter().enumerate() {
 				let end_new = first + end_offset;
 
-				let Some(connection) = self.connections.get(*start_old, *end_old) else { continue };
+				let Some(connection) = self.connection_between(*start_old, *end_old) else { continue };
 				let clone = connection.clone();
-				let Ok(entry) = self.connections.entry_mut(start_new, end_new) else {
+				let Ok(_) = self.add_connection(start_new, end_new, clone.orientation, clone.size) else {
 					panic!("duplicate_subgraph(): trying to access non-existant connections [ALGORITHMIC ERROR]")
 				};
-				*entry = Some(clone);
 			}
 		}
 
+		let new_id = |old: VertexId| Some(first + old_ids.iter().position(|&id| id == old)?);
+		let new_edge = |old: Edge| Some(edge(new_id(old.0)?, new_id(old.1)?));
+		let clones = self
+			.constraints()
+			.flat_map(|(a, b, constraint)| Some((new_edge(a)?, new_edge(b)?, constraint)))
+			.collect::<Vec<_>>();
+		for (a, b, constraint) in clones {
+			let _ = self.set_constraint(a, b, constraint);
+		}
+
 		(first, count)
 	}
+	/// Copies the specified subgraph into a drawing of its own (with the same classes).
+	///
+	/// That includes the specified vertices, all connections between exclusively those vertices and their constraints.
+	/// The vertices keep their order (so the subgraph of sorted vertices keeps the relative order of their ids).
+	pub fn subgraph(&self, vertices: &[VertexId]) -> Data {
+		let vertices = vertices.iter().copied().filter(|&id| id < self.vertices.len()).collect::<Vec<_>>();
+		let new_id = |old: VertexId| vertices.iter().position(|&id| id == old);
+		let new_edge = |old: Edge| Some(edge(new_id(old.0)?, new_id(old.1)?));
+
+		let mut result = Data::new(self.classes.clone());
+		for &id in &vertices {
+			result.add_vertex(self.vertices[id].clone());
+		}
+		for (start, end, connection) in self.edges() {
+			let (Some(start), Some(end)) = (new_id(start), new_id(end)) else { continue };
+			let _ = result.add_connection(start, end, connection.orientation, connection.size);
+		}
+		for (a, b, constraint) in self.constraints() {
+			let (Some(a), Some(b)) = (new_edge(a), new_edge(b)) else { continue };
+			let _ = result.set_constraint(a, b, constraint);
+		}
+		result
+	}
+	/// Appends all vertices, connections and constraints of the other drawing.
+	///
+	/// The connections keep their radius as far as possible: they get the class with the closest radius
+	/// (the same class id if it has the same radius, so inserting a drawing with the same classes keeps them).
+	///
+	/// Returns the index of the first inserted vertex and the number of inserted vertices.
+	pub fn insert(&mut self, other: &Data) -> (VertexId, usize) {
+		let first = self.vertices.len();
+		for vertex in other.vertices.iter() {
+			self.add_vertex(vertex.clone());
+		}
+		for (start, end, connection) in other.edges() {
+			let radius = other.classes.get_size(connection.size);
+			let size = match self.classes.get_size(connection.size) == radius {
+				true => connection.size,
+				false => self.classes.closest(radius),
+			};
+			let _ = self.add_connection(first + start, first + end, connection.orientation, size);
+		}
+		let shift = |e: Edge| edge(first + e.0, first + e.1);
+		for (a, b, constraint) in other.constraints() {
+			let _ = self.set_constraint(shift(a), shift(b), constraint);
+		}
+		(first, other.vertices.len())
+	}
 	pub fn label_vertex(&mut self, vertex: VertexId, label: String) {
-		if let Some(vertex) = self.vertices.items.get_mut(vertex) {
+		if let Some(vertex) = self.vertices.get_mut(vertex) {
 			vertex.label = Some(label);
 		}
 	}
+	/// The label of the vertex or its key if it has none (so it keeps its name when other vertices are removed).
+	pub fn vertex_name(&self, id: VertexId) -> String {
+		let key = self.vertices.key(id).map(|key| key.to_string()).unwrap_or_else(|| id.to_string());
+		self.vertices.get(id).map(|v| v.label.clone()).flatten().unwrap_or(key)
+	}
 	pub fn connections_subset<'a>(
 		&'a self,
 		vertices: &'a Vec<VertexId>,
 	) -> impl Iterator<Item = (VertexId, VertexId, ConnectionKind)> + 'a {
 		let arcs = self
-			.connections
-			.fast_iter()
+			.edges()
 			.filter(|(a, b, _)| vertices.binary_search(a).is_ok() && vertices.binary_search(b).is_ok())
-			.flat_map(|(start, end, connection)| {
-				let conn = match Arc::construct(start, end, &self.vertices, connection.as_ref()?, &self.classes) {
+			.map(|(start, end, connection)| {
+				let conn = match Arc::construct(start, end, &self.vertices, connection, &self.classes) {
 					Ok(arc) => ConnectionKind::Arc(arc),
 					Err(()) => ConnectionKind::Unreachable,
 				};
-				Some((start, end, conn))
+				(start, end, conn)
 			});
 		arcs
 	}
 	fn connections(&self) -> impl Iterator<Item = (VertexId, VertexId, ConnectionKind)> + '_ {
-		let arcs = self.connections.fast_iter().flat_map(|(start, end, connection)| {
-			let conn = match Arc::construct(start, end, &self.vertices, connection.as_ref()?, &self.classes) {
+		let arcs = self.edges().map(|(start, end, connection)| {
+			let conn = match Arc::construct(start, end, &self.vertices, connection, &self.classes) {
 				Ok(arc) => ConnectionKind::Arc(arc),
 				Err(()) => ConnectionKind::Unreachable,
 			};
-			Some((start, end, conn))
+			(start, end, conn)
 		});
 		arcs
 	}
+	/// The connections whose arc can't be constructed (e.g. because the vertices are too far apart for its class).
+	pub fn unreachable(&self) -> impl Iterator<Item = (VertexId, VertexId)> + '_ {
+		self.connections().flat_map(|(start, end, connection)| match connection {
+			ConnectionKind::Arc(_) => None,
+			ConnectionKind::Unreachable => Some((start, end)),
+		})
+	}
 	pub fn arcs(&self) -> impl Iterator<Item = (VertexId, VertexId, Arc)> + '_ {
 		self.connections().flat_map(|(start, end, connection)| match connection {
 			ConnectionKind::Arc(arc) => Some((start, end, arc)),
 			ConnectionKind::Unreachable => None,
 		})
 	}
-	pub fn conflicts(
+	/// Computes the points where the first arc crosses the second one.
+	///
+	/// Intersections that are just the shared end vertex of adjacent connections are not counted.
+	pub fn intersections_between(
 		&self,
-		arcs: Vec<(VertexId, VertexId, Arc)>,
-	) -> Vec<((VertexId, VertexId), (VertexId, VertexId), Vector)> {
-		let mut conflicts = Vec::new();
-
-		for (i, (a, b, arc)) in arcs.iter().enumerate() {
-			for (j, (c, d, other)) in arcs.iter().enumerate() {
-				if i == j {
-					continue;
-				}
+		(a, b, arc): (&VertexId, &VertexId, &Arc),
+		(c, d, other): (&VertexId, &VertexId, &Arc),
+	) -> Vec<Intersection> {
+		let collision_is_connection = |collision_is_right: bool| {
+			let Some(connection) = (match (c, d) {
+				(c, d) if (c == a && d == b) || (c == b && d == a) => return true,
+				(c, d) if c == a || d == a => self.vertices.get(*a),
+				(c, d) if c == b || d == b => self.vertices.get(*b),
+				_ => None,
+			}) else {
+				return false;
+			};
 
-				let collision_is_connection = |collision_is_right: bool| {
-					let Some(connection) = (match (c, d) {
-						(c, d) if (c == a && d == b) || (c == b && d == a) => return true,
-						(c, d) if c == a || d == a => self.vertices.items.get(*a),
-						(c, d) if c == b || d == b => self.vertices.items.get(*b),
-						_ => None,
-					}) else {
-						return false;
-					};
+			let center_to_other = other.center.clone() - arc.center.clone();
+			let center_to_connection = connection.position.clone() - arc.center.clone();
+			let connection_is_right = center_to_other.is_right(&center_to_connection);
 
-					let center_to_other = other.center.clone() - arc.center.clone();
-					let center_to_connection = connection.position.clone() - arc.center.clone();
-					let connection_is_right = center_to_other.is_right(&center_to_connection);
+			let result = !(connection_is_right ^ collision_is_right);
+			return result;
+		};
 
-					let result = !(connection_is_right ^ collision_is_right);
-					return result;
-				};
+		let (intersection, certain) = arc.intersection_with(&other, &self.tolerance);
+		let contact = intersection.contact();
+		let mut intersections = Vec::new();
+		match intersection {
+			ArcIntersection::None => (),
+			ArcIntersection::One(intersection) => {
+				if !(a == c || a == d || b == c || b == d) {
+					intersections.push(intersection);
+				}
+			},
+			ArcIntersection::Two(vector1, vector2) => match (vector1, vector2) {
+				(None, None) => (),
+				(Some(intersection1), Some(intersection2)) => {
+					if !collision_is_connection(false) {
+						intersections.push(intersection1);
+					}
+					if !collision_is_connection(true) {
+						intersections.push(intersection2);
+					}
+				},
+				(Some(intersection), None) => {
+					if !collision_is_connection(false) {
+						intersections.push(intersection);
+					}
+				},
+				(None, Some(intersection)) => {
+					if !collision_is_connection(true) {
+						intersections.push(intersection);
+					}
+				},
+			},
+			ArcIntersection::Concentric(_, _) => (),
+		};
+		intersections.into_iter().map(|position| Intersection { position, contact, certain }).collect()
+	}
 
-				match arc.intersection_with(&other) {
-					ArcIntersection::None => (),
-					ArcIntersection::One(intersection) => {
-						if !(a == c || a == d || b == c || b == d) {
-							conflicts.push(((*a, *b), (*c, *d), intersection));
-						}
-					},
-					ArcIntersection::Two(vector1, vector2) => match (vector1, vector2) {
-						(None, None) => (),
-						(Some(intersection1), Some(intersection2)) => {
-							if !collision_is_connection(false) {
-								conflicts.push(((*a, *b), (*c, *d), intersection1));
-							}
-							if !collision_is_connection(true) {
-								conflicts.push(((*a, *b), (*c, *d), intersection2));
-							}
-						},
-						(Some(intersection), None) => {
-							if !collision_is_connection(false) {
-								conflicts.push(((*a, *b), (*c, *d), intersection));
-							}
-						},
-						(None, Some(intersection)) => {
-							if !collision_is_connection(true) {
-								conflicts.push(((*a, *b), (*c, *d), intersection));
-							}
-						},
-					},
-					ArcIntersection::Concentric(_, _) => (),
-				};
+	/// Detects all intersections between the arcs of the drawing and classifies them according to the
+	/// intersection constraints.
+	///
+	/// Only arcs with overlapping bounds are tested and every pair only once.
+	/// Only the arcs that changed since the last call are constructed and tested again (see [ConflictCache]).
+	pub fn conflicts(&self) -> Vec<Conflict> {
+		let source = ([self.vertices.stamp(), self.connections.stamp(), self.classes.stamp()], self.tolerance);
+		let vertex = |key| self.vertices.slot(key);
+		let vertex_stamp = |key| self.vertices.stamp_of(vertex(key)?);
+		let connections = self
+			.connections
+			.stamps()
+			.flat_map(|(start, end, stamp)| Some(((start, end), [stamp, vertex_stamp(start)?, vertex_stamp(end)?])));
+		let construct = |(start, end): ConnectionKey| {
+			let connection = self.connections.get(start, end)?;
+			Arc::construct(vertex(start)?, vertex(end)?, &self.vertices, connection, &self.classes).ok()
+		};
+		let compute = |((a, b), arc): (ConnectionKey, &Arc), ((c, d), other): (ConnectionKey, &Arc)| {
+			let (Some(a), Some(b), Some(c), Some(d)) = (vertex(a), vertex(b), vertex(c), vertex(d)) else {
+				return Vec::new();
+			};
+			self.intersections_between((&a, &b, arc), (&c, &d, other))
+		};
+
+		let mut intersections = Vec::new();
+		for ((a, b), (c, d), pair) in self.conflict_cache.intersections(source, connections, construct, compute) {
+			let (Some(a), Some(b), Some(c), Some(d)) = (vertex(a), vertex(b), vertex(c), vertex(d)) else { continue };
+			intersections.extend(pair.into_iter().map(|intersection| ((a, b), (c, d), intersection)));
+		}
+		self.classify(intersections)
+	}
+	/// Detects all intersections between the given arcs (like [Self::conflicts] but without cache).
+	///
+	/// Only arcs with overlapping bounds are tested and every pair only once (the earlier arc is the first).
+	pub fn conflicts_of(&self, arcs: Vec<(VertexId, VertexId, Arc)>) -> Vec<Conflict> {
+		let index = SpatialIndex::new(arcs.iter().map(|(_, _, arc)| arc.bounds()));
+		let mut intersections = Vec::new();
+		for (i, j) in index.overlapping_pairs() {
+			let ((a, b, arc), (c, d, other)) = (&arcs[i], &arcs[j]);
+			for intersection in self.intersections_between((a, b, arc), (c, d, other)) {
+				intersections.push(((*a, *b), (*c, *d), intersection));
 			}
 		}
+		self.classify(intersections)
+	}
+	/// Classifies the intersections between pairs of connections according to the intersection constraints.
+	fn classify(
+		&self,
+		intersections: Vec<((VertexId, VertexId), (VertexId, VertexId), Intersection)>,
+	) -> Vec<Conflict> {
+		let mut counts = HashMap::<_, usize>::new();
+		for (first, second, _) in &intersections {
+			*counts.entry((*first, *second)).or_default() += 1;
+		}
 
-		return conflicts;
+		return intersections
+			.into_iter()
+			.map(|(first, second, Intersection { position, contact, certain })| {
+				let (a, b) = (edge(first.0, first.1), edge(second.0, second.1));
+				let status = match self.constraint(a, b) {
+					_ if edges_are_adjacent(&a, &b) => ConflictStatus::Violated,
+					_ if counts.get(&(first, second)).is_some_and(|&n| n > 1) => ConflictStatus::Violated,
+					IntersectionConstraint::Necessary => ConflictStatus::Satisfied,
+					IntersectionConstraint::Irrelevant => ConflictStatus::Tolerated,
+					IntersectionConstraint::Forbidden => ConflictStatus::Violated,
+				};
+				Conflict { first, second, position, status, contact, certain }
+			})
+			.collect();
+	}
+	/// All connections as they are stored (without constructing their arcs).
+	pub fn edges(&self) -> impl Iterator<Item = (VertexId, VertexId, &Connection)> + '_ {
+		self.connections.fast_iter().flat_map(|(start, end, connection)| {
+			Some((self.vertices.slot(start)?, self.vertices.slot(end)?, connection.as_ref()?))
+		})
+	}
+	pub fn edge(&self, from: &VertexId, to: &VertexId) -> Option<&Connection> {
+		self.connection_between(*from, *to)
 	}
 	pub fn edge_mut(&mut self, from: &VertexId, to: &VertexId) -> Option<&mut Connection> {
-		self.connections.get_mut(*from, *to)
+		let (from, to) = self.keys(*from, *to).ok()?;
+		self.connections.get_mut(from, to)
 	}
 	pub fn connection(&self, from: &VertexId, to: &VertexId) -> Option<ConnectionKind> {
-		let connection = self.connections.get(*from, *to)?;
+		let connection = self.connection_between(*from, *to)?;
 		let arc = Arc::construct(*from, *to, &self.vertices, connection, &self.classes);
 		Some(match arc {
 			Ok(arc) => ConnectionKind::Arc(arc),
 			Err(()) => ConnectionKind::Unreachable,
 		})
 	}
-	pub fn render_to(&self, renderer: &mut impl RenderTarget) {
+	pub fn render_to(&self, renderer: &mut impl RenderTarget, settings: &Settings) {
 		let connections = self.connections().collect::<Vec<_>>();
-		connections.iter().for_each(|(start, end, connection)| match connection {
-			ConnectionKind::Arc(arc) => renderer.draw_connection_arc(
-				arc.center.clone(),
-				arc.radius,
-				arc.rotation,
-				arc.angle,
-				self.classes.get_color(self.connections.get(*start, *end).map_or(usize::MAX, |c| c.size)),
-				false,
-			),
-			ConnectionKind::Unreachable => match (self.vertices.items.get(*start), self.vertices.items.get(*end)) {
-				(Some(start), Some(end)) => renderer.draw_connection_invalid(start.position, end.position, false),
-				_ => (),
-			},
-		});
+		let conflicts = self.conflicts();
+
+		if settings.show_missing_intersections {
+			let missing = self
+				.missing_intersections(&conflicts)
+				.into_iter()
+				.flat_map(|(a, b)| [a, b])
+				.collect::<HashSet<_>>();
+			connections.iter().filter(|(start, end, _)| missing.contains(&edge(*start, *end))).for_each(
+				|(start, end, connection)| match connection {
+					ConnectionKind::Arc(arc) => renderer.draw_connection_highlight(
+						arc.center.clone(),
+						arc.radius,
+						arc.rotation,
+						arc.angle,
+						MISSING_INTERSECTION_COLOR,
+					),
+					ConnectionKind::Unreachable => match (self.vertices.get(*start), self.vertices.get(*end)) {
+						(Some(start), Some(end)) => renderer.draw_connection_invalid(start.position, end.position, true),
+						_ => (),
+					},
+				},
+			);
+		}
+
+		self.render_connections_to(renderer, &connections);
 		self.vertices.render(renderer);
+		Self::render_conflicts_to(renderer, &conflicts, settings);
+	}
 
-		let arcs = connections
-			.into_iter()
+	/// Renders all specified vertices and all between only! those vertices
+	pub fn render_subset_to(
+		&self,
+		renderer: &mut impl RenderTarget,
+		vertices: &Vec<VertexId>,
+		settings: &Settings,
+	) {
+		let connections = self.connections_subset(vertices).collect::<Vec<_>>();
+		let conflicts = self.conflicts_of(Self::arcs_of(&connections));
+
+		self.render_connections_to(renderer, &connections);
+		self.vertices.render_subset(renderer, vertices);
+		Self::render_conflicts_to(renderer, &conflicts, settings);
+	}
+
+	fn arcs_of(connections: &[(VertexId, VertexId, ConnectionKind)]) -> Vec<(VertexId, VertexId, Arc)> {
+		connections
+			.iter()
 			.flat_map(|(start, end, connection)| match connection {
-				ConnectionKind::Arc(arc) => Some((start, end, arc)),
+				ConnectionKind::Arc(arc) => Some((*start, *end, arc.clone())),
 				ConnectionKind::Unreachable => None,
 			})
-			.collect::<Vec<_>>();
-
-		for conflict in self.conflicts(arcs) {
-			renderer.draw_conflict(conflict.2, "orange", false);
-		}
-		// for (i, (a, b, arc)) in arcs.iter().enumerate() {
-		// 	for (j, (c, d, other)) in arcs.iter().enumerate() {
-		// 		if i == j {
-		// 			continue;
-		// 		}
-		//
-		// 		let collision_is_connection = |collision_is_right: bool| {
-		// 			let Some(connection) = (match (c, d) {
-		// 				(c, d) if (c == a && d == b) || (c == b && d == a) => return true,
-		// 				(c, d) if c == a || d == a => self.vertices.items.get(*a),
-		// 				(c, d) if c == b || d == b => self.vertices.items.get(*b),
-		// 				_ => None,
-		// 			}) else {
-		// 				return false;
-		// 			};
-		//
-		// 			let center_to_other = other.center.clone() - arc.center.clone();
-		// 			let center_to_connection = connection.position.clone() - arc.center.clone();
-		// 			let connection_is_right = center_to_other.is_right(&center_to_connection);
-		//
-		// 			let result = !(connection_is_right ^ collision_is_right);
-		// 			return result;
-		// 		};
-		//
-		// 		match arc.intersection_with(&other) {
-		// 			ArcIntersection::None => (),
-		// 			ArcIntersection::One(vector) => {
-		// 				renderer.draw_conflict(vector, "orange", false);
-		// 			},
-		// 			ArcIntersection::Two(vector1, vector2) => match (vector1, vector2) {
-		// 				(None, None) => (),
-		// 				(Some(c1), Some(c2)) => {
-		// 					if !collision_is_connection(false) {
-		// 						renderer.draw_conflict(c1, "orange", false);
-		// 					}
-		// 					if !collision_is_connection(true) {
-		// 						renderer.draw_conflict(c2, "orange", false);
-		// 					}
-		// 				},
-		// 				(Some(c), None) => {
-		// 					if !collision_is_connection(false) {
-		// 						renderer.draw_conflict(c, "orange", false);
-		// 					}
-		// 				},
-		// 				(None, Some(c)) => {
-		// 					if !collision_is_connection(true) {
-		// 						renderer.draw_conflict(c, "orange", false);
-		// 					}
-		// 				},
-		// 			},
-		// 			ArcIntersection::Concentric(_, _) => (),
-		// 		};
-		// 	}
-		// }
+			.collect()
 	}
 
-	/// Renders all specified vertices and all between only! those vertices
-	pub fn render_subset_to(&self, renderer: &mut impl RenderTarget, vertices: &Vec<VertexId>) {
-		let connections = self.connections_subset(vertices).collect::<Vec<_>>();
+	fn render_connections_to(
+		&self,
+		renderer: &mut impl RenderTarget,
+		connections: &[(VertexId, VertexId, ConnectionKind)],
+	) {
 		connections.iter().for_each(|(start, end, connection)| match connection {
 			ConnectionKind::Arc(arc) => renderer.draw_connection_arc(
 				arc.center.clone(),
 				arc.radius,
 				arc.rotation,
 				arc.angle,
-				self.classes.get_color(self.connections.get(*start, *end).map_or(usize::MAX, |c| c.size)),
+				self.classes.get_color(self.connection_between(*start, *end).map_or(usize::MAX, |c| c.size)),
 				false,
 			),
-			ConnectionKind::Unreachable => match (self.vertices.items.get(*start), self.vertices.items.get(*end)) {
+			ConnectionKind::Unreachable => match (self.vertices.get(*start), self.vertices.get(*end)) {
 				(Some(start), Some(end)) => renderer.draw_connection_invalid(start.position, end.position, false),
 				_ => (),
 			},
 		});
-		self.vertices.render_subset(renderer, vertices);
-
-		let arcs = connections
-			.into_iter()
-			.flat_map(|(start, end, connection)| match connection {
-				ConnectionKind::Arc(arc) => Some((start, end, arc)),
-				ConnectionKind::Unreachable => None,
-			})
-			.collect::<Vec<_>>();
-
-		for (i, (a, b, arc)) in arcs.iter().enumerate() {
-			for (j, (c, d, other)) in arcs.iter().enumerate() {
-				if i == j {
-					continue;
-				}
-
-				let collision_is_connection = |collision_is_right: bool| {
-					let Some(connection) = (match (c, d) {
-						(c, d) if (c == a && d == b) || (c == b && d == a) => return true,
-						(c, d) if c == a || d == a => self.vertices.items.get(*a),
-						(c, d) if c == b || d == b => self.vertices.items.get(*b),
-						_ => None,
-					}) else {
-						return false;
-					};
-
-					let center_to_other = other.center.clone() - arc.center.clone();
-					let center_to_connection = connection.position.clone() - arc.center.clone();
-					let connection_is_right = center_to_other.is_right(&center_to_connection);
-
-					let result = !(connection_is_right ^ collision_is_right);
-					return result;
-				};
+	}
 
-				match arc.intersection_with(&other) {
-					ArcIntersection::None => (),
-					ArcIntersection::One(vector) => {
-						renderer.draw_conflict(vector, "orange", false);
-					},
-					ArcIntersection::Two(vector1, vector2) => match (vector1, vector2) {
-						(None, None) => (),
-						(Some(c1), Some(c2)) => {
-							if !collision_is_connection(false) {
-								renderer.draw_conflict(c1, "orange", false);
-							}
-							if !collision_is_connection(true) {
-								renderer.draw_conflict(c2, "orange", false);
-							}
-						},
-						(Some(c), None) => {
-							if !collision_is_connection(false) {
-								renderer.draw_conflict(c, "orange", false);
-							}
-						},
-						(None, Some(c)) => {
-							if !collision_is_connection(true) {
-								renderer.draw_conflict(c, "orange", false);
-							}
-						},
-					},
-					ArcIntersection::Concentric(_, _) => (),
-				};
-			}
+	fn render_conflicts_to(renderer: &mut impl RenderTarget, conflicts: &[Conflict], settings: &Settings) {
+		for conflict in conflicts {
+			let color = match settings.color_conflicts {
+				_ if !conflict.certain => UNCERTAIN_CONFLICT_COLOR,
+				true => conflict.status.color(),
+				false => "orange",
+			};
+			renderer.draw_conflict(conflict.position, color, false);
 		}
 	}
+
+	/// The edge pairs that are required to intersect but don't.
+	pub fn missing_intersections(&self, conflicts: &[Conflict]) -> Vec<(Edge, Edge)> {
+		let intersecting = conflicts
+			.iter()
+			.map(|c| {
+				let (a, b) = (edge(c.first.0, c.first.1), edge(c.second.0, c.second.1));
+				(a.min(b), a.max(b))
+			})
+			.collect::<HashSet<_>>();
+		self
+			.constraints()
+			.filter(|(_, _, constraint)| *constraint == IntersectionConstraint::Necessary)
+			.filter(|(a, b, _)| !intersecting.contains(&(*a, *b)))
+			.map(|(a, b, _)| (a, b))
+			.collect()
+	}
 	// pub fn render_vertex_to(&self, renderer: &mut impl RenderTarget, vertex: VertexId) {
 	// 	if let Some(vertex) = self.vertices.vertices.get(vertex) {
 	// 		renderer.draw_point(vertex.position, "green", true);
@@ -455,64 +657,253 @@ impl Data {
 	// }
 }
 
-#[derive(Serialize, Deserialize)]
-struct DataRepresentation {
-	vertices: Vertices,
-	connections: Vec<(VertexId, VertexId, Connection)>,
-	sizes: Classes,
+/// How a detected intersection relates to the intersection constraints of its edge pair.
+#[derive(Copy, Clone, PartialEq)]
+pub enum ConflictStatus {
+	/// The edges are required to intersect.
+	Satisfied,
+	/// The edges may intersect.
+	Tolerated,
+	/// The edges must not intersect (or intersect more than once).
+	Violated,
 }
-impl From<DataRepresentation> for Data {
-	fn from(value: DataRepresentation) -> Self {
-		let mut connections = Connections::new(value.vertices.len());
-		for (a, b, connection) in value.connections {
-			let Ok(entry) = connections.entry_mut(a, b) else { continue };
-			*entry = Some(connection);
+impl ConflictStatus {
+	pub fn name(&self) -> &'static str {
+		match self {
+			ConflictStatus::Satisfied => "required",
+			ConflictStatus::Tolerated => "tolerated",
+			ConflictStatus::Violated => "forbidden",
 		}
-		Data { vertices: value.vertices, connections, classes: value.sizes }
 	}
-}
-impl From<Data> for DataRepresentation {
-	fn from(value: Data) -> Self {
-		let mut connections = Vec::new();
-		value.connections.foreach(|a, b, connection| {
-			connections.push((a, b, connection.clone()));
-		});
-		DataRepresentation { vertices: value.vertices, connections, sizes: value.classes }
+	pub fn color(&self) -> &'static str {
+		match self {
+			ConflictStatus::Satisfied => "green",
+			ConflictStatus::Tolerated => "blue",
+			ConflictStatus::Violated => "red",
+		}
 	}
 }
 
-impl ToString for Data {
-	fn to_string(&self) -> String {
-		let mut result = String::new();
-		result.push_str(&self.classes.to_string());
-		result.push_str("\n\n");
-		result.push_str(&self.vertices.to_string());
-		result.push_str("\n\n");
-		result.push_str(&self.connections.to_string());
-		result
-	}
+/// The color used to highlight edges that are required to intersect but don't.
+pub const MISSING_INTERSECTION_COLOR: &str = "magenta";
+/// The color of conflicts that may be an artifact of rounding (see [Conflict::certain]).
+pub const UNCERTAIN_CONFLICT_COLOR: &str = "gray";
+
+/// A point where 2 arcs meet (see [Data::intersections_between]).
+#[derive(Copy, Clone, PartialEq)]
+pub struct Intersection {
+	pub position: Vector,
+	/// Whether the arcs cross or just touch in the position.
+	pub contact: ArcContact,
+	/// False if the error of constructing or intersecting the arcs could have changed whether (or where)
+	/// they meet. Always false for arcs that touch, which is only decided within the [Tolerance].
+	pub certain: bool,
 }
-impl FromStr for Data {
-	type Err = ();
-	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let sections = s.split("\n\n").collect::<Vec<_>>();
-		let (Some(&sizes), Some(&vertices), Some(&connections)) =
-			(sections.get(0), sections.get(1), sections.get(2))
-		else {
-			return Err(());
-		};
-		let (Ok(sizes), Ok(vertices), Ok(connections)) = (sizes.parse(), vertices.parse(), connections.parse())
-		else {
-			return Err(());
-		};
-		let mut this = Self { vertices, connections, classes: sizes };
-		this.connections.resize(this.vertices.len());
-		Ok(this)
-	}
+
+/// An intersection between 2 connections.
+#[derive(Clone)]
+pub struct Conflict {
+	pub first: (VertexId, VertexId),
+	pub second: (VertexId, VertexId),
+	pub position: Vector,
+	pub status: ConflictStatus,
+	/// See [Intersection::contact].
+	pub contact: ArcContact,
+	/// See [Intersection::certain].
+	pub certain: bool,
 }
 
+#[derive(Clone)]
 pub enum ConnectionKind {
 	Arc(Arc),
 	Unreachable,
 	// BUG: handle self-referential connections (a-a)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::common::XorShift;
+
+	const ORIENTATIONS: [ConnectionOrientation; 4] = [
+		ConnectionOrientation::InnerRight,
+		ConnectionOrientation::InnerLeft,
+		ConnectionOrientation::OuterRight,
+		ConnectionOrientation::OuterLeft,
+	];
+
+	fn square() -> Data {
+		let mut data = Data::new(Classes::default());
+		for (x, y) in [(0.0, 0.0), (0.0, 100.0), (100.0, 100.0), (100.0, 0.0), (200.0, 0.0)] {
+			data.add_vertex(Vertex::new(Vector::new(x, y)));
+		}
+		for (start, end) in [(0, 1), (1, 2), (2, 3), (3, 0), (0, 2), (1, 3), (3, 4)] {
+			data.add_connection(start, end, ConnectionOrientation::InnerRight, 2).unwrap();
+		}
+		data
+	}
+
+	#[test]
+	fn removing_a_vertex_keeps_the_other_connections_and_constraints() {
+		let mut data = square();
+		data.set_constraint((0, 2), (1, 3), IntersectionConstraint::Necessary).unwrap();
+		data.set_constraint((1, 2), (3, 4), IntersectionConstraint::Forbidden).unwrap();
+		data.set_constraint((0, 1), (2, 3), IntersectionConstraint::Forbidden).unwrap();
+		let key = data.vertices.key(2).unwrap();
+
+		data.remove_vertex(0).unwrap();
+
+		assert_eq!(data.vertices.slot(key), Some(1));
+		let mut edges = data.edges().map(|(start, end, _)| (start, end)).collect::<Vec<_>>();
+		edges.sort();
+		assert_eq!(edges, vec![(0, 1), (0, 2), (1, 2), (2, 3)]);
+		let constraints = data.constraints().map(|(a, b, _)| (a, b)).collect::<Vec<_>>();
+		assert_eq!(constraints, vec![((0, 1), (2, 3))]);
+		assert!(data.constraint((0, 1), (2, 3)) == IntersectionConstraint::Forbidden);
+		assert!(data.constraint((0, 2), (1, 2)) == IntersectionConstraint::Forbidden);
+		assert!(data.constraint((0, 1), (0, 2)) == IntersectionConstraint::Forbidden);
+	}
+
+	#[test]
+	fn removing_a_connection_removes_its_constraints() {
+		let mut data = square();
+		data.set_constraint((0, 2), (1, 3), IntersectionConstraint::Necessary).unwrap();
+
+		data.remove_connection(2, 0).unwrap();
+		assert!(data.constraints().next().is_some(), "the connection is stored from 0 to 2");
+		data.remove_connection(0, 2).unwrap();
+
+		assert!(data.constraints().next().is_none());
+		assert!(data.set_constraint((0, 2), (1, 3), IntersectionConstraint::Necessary).is_err());
+	}
+
+	/// The conflicts in a comparable form (independent of which connection of a pair is the first).
+	fn comparable(
+		conflicts: Vec<Conflict>,
+	) -> Vec<((Edge, Edge), &'static str, &'static str, bool, (i64, i64))> {
+		let mut result = conflicts
+			.into_iter()
+			.map(|conflict| {
+				let (a, b) = (edge(conflict.first.0, conflict.first.1), edge(conflict.second.0, conflict.second.1));
+				let position = ((conflict.position.x * 1e6).round() as i64, (conflict.position.y * 1e6).round() as i64);
+				((a.min(b), a.max(b)), conflict.status.name(), conflict.contact.name(), conflict.certain, position)
+			})
+			.collect::<Vec<_>>();
+		result.sort();
+		result
+	}
+
+	fn random_connection(data: &mut Data, random: &mut XorShift) {
+		let count = data.vertices.len();
+		let (start, end) = (random.below(count), random.below(count));
+		if start != end {
+			let orientation = ORIENTATIONS[random.below(ORIENTATIONS.len())];
+			let _ = data.add_connection(start, end, orientation, random.below(Classes::default().len()));
+		}
+	}
+
+	#[test]
+	fn cached_conflicts_match_uncached_ones_after_every_edit() {
+		for seed in 1..8 {
+			let mut random = XorShift::new(seed);
+			let mut data = Data::new(Classes::default());
+			for _ in 0..12 {
+				data.add_vertex(Vertex::new(Vector::new(random.unit() * 400.0, random.unit() * 400.0)));
+			}
+			for _ in 0..24 {
+				random_connection(&mut data, &mut random);
+			}
+			for step in 0..150 {
+				let count = data.vertices.len();
+				let vertex = random.below(count);
+				match random.below(10) {
+					0..=3 => {
+						let offset = Vector::new(random.signed_unit() * 40.0, random.signed_unit() * 40.0);
+						data.vertices[vertex].position = data.vertices[vertex].position + offset;
+					},
+					4 => {
+						let Some((start, end, _)) = data.edges().nth(random.below(data.edges().count().max(1))) else {
+							continue;
+						};
+						if let Some(connection) = data.edge_mut(&start, &end) {
+							connection.orientation = connection.orientation.inverse();
+						}
+					},
+					5 | 6 => random_connection(&mut data, &mut random),
+					7 => {
+						let Some((start, end, _)) = data.edges().nth(random.below(data.edges().count().max(1))) else {
+							continue;
+						};
+						data.remove_connection(start, end).unwrap();
+					},
+					8 if count > 4 => data.remove_vertex(vertex).unwrap(),
+					_ => {
+						data.add_vertex(Vertex::new(Vector::new(random.unit() * 400.0, random.unit() * 400.0)));
+						random_connection(&mut data, &mut random);
+					},
+				}
+				if step % 50 == 49 {
+					data.tolerance = Tolerance { epsilon: data.tolerance.epsilon * 10.0 };
+				}
+
+				let uncached = comparable(data.conflicts_of(data.arcs().collect()));
+				assert_eq!(comparable(data.conflicts()), uncached, "seed {} step {}", seed, step);
+				assert_eq!(comparable(data.conflicts()), uncached, "seed {} step {} (unchanged)", seed, step);
+			}
+		}
+	}
+
+	#[test]
+	fn moving_a_vertex_keeps_the_others_cached() {
+		let mut data = square();
+		let before = (0..data.vertices.len()).map(|id| data.vertices.stamp_of(id)).collect::<Vec<_>>();
+		data.vertices[2].position = Vector::new(110.0, 90.0);
+		data.add_vertex(Vertex::new(Vector::new(300.0, 0.0)));
+		for (id, stamp) in before.into_iter().enumerate() {
+			assert_eq!(data.vertices.stamp_of(id) == stamp, id != 2, "vertex {}", id);
+		}
+	}
+
+	#[test]
+	fn cached_conflicts_follow_removed_vertices() {
+		// the diagonals cross, the other arcs meet at shared vertices (which are not counted)
+		let mut data = square();
+		let before = comparable(data.conflicts());
+		assert_eq!(before, comparable(data.conflicts_of(data.arcs().collect())));
+
+		// the later vertices move down, so the cached pairs now have other ids
+		data.remove_vertex(1).unwrap();
+		assert_eq!(comparable(data.conflicts()), comparable(data.conflicts_of(data.arcs().collect())));
+		data.remove_vertex(0).unwrap();
+		assert_eq!(comparable(data.conflicts()), comparable(data.conflicts_of(data.arcs().collect())));
+	}
+
+	#[test]
+	fn arcs_sharing_a_vertex_dont_conflict_there() {
+		let shared = Vector::new(100.0, 0.0);
+		let mut tangent = 0;
+		for first in ORIENTATIONS {
+			for second in ORIENTATIONS {
+				let mut data = Data::new(Classes::default());
+				for x in [0.0, 100.0, 200.0] {
+					data.add_vertex(Vertex::new(Vector::new(x, 0.0)));
+				}
+				data.add_connection(0, 1, first, 2).unwrap();
+				data.add_connection(1, 2, second, 2).unwrap();
+				let conflicts = data.conflicts();
+				assert!(conflicts.iter().all(|conflict| (conflict.position - shared).length() > 1e-6));
+
+				// with the centers on opposite sides the circles only touch in the shared vertex
+				let center = |start, end| match data.connection(&start, &end) {
+					Some(ConnectionKind::Arc(arc)) => arc.center,
+					_ => panic!("the connections are reachable"),
+				};
+				if center(0, 1).y * center(1, 2).y < 0.0 {
+					assert!(conflicts.is_empty());
+					tangent += 1;
+				}
+			}
+		}
+		assert!(tangent > 0);
+	}
+}
@@ -0,0 +1,320 @@
+//! Undo and redo by snapshots.
+//!
+//! Every step keeps a copy of the whole [Data] instead of a command that inverts the edit.
+//! Most edits would be cheap to invert, but solving, layouts, deleting vertices (with their connections and
+//! constraints) and pasting change large parts of the drawing, so their inverse would hold about as much.
+//! A copy also restores the [super::VertexKey]s exactly, which connections, constraints and the ids of the
+//! file formats refer to, without every edit having to get its inverse right.
+//! Copies don't include the caches (see [super::Cached]), and the steps are limited by number and by memory.
+
+use std::{collections::VecDeque, mem};
+
+use super::{Connection, Data, Edge, IntersectionConstraint, Stamp, Vertex, VertexKey};
+
+/// The kind of edit a [Revision] was recorded for.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Edit {
+	AddVertex,
+	Connect,
+	Move,
+	Delete,
+	Duplicate,
+	Paste,
+	Reorient,
+	Reclassify,
+	Label,
+	Constrain,
+	Solve,
+	Pin,
+	Layout,
+	Tolerance,
+}
+impl Edit {
+	/// Whether consecutive edits of this kind should be merged into a single step.
+	///
+	/// This is the case for edits that are applied continuously (e.g. while dragging).
+	pub fn coalesces(&self) -> bool {
+		matches!(self, Edit::Move)
+	}
+}
+
+/// A single step in the [History].
+///
+/// Holds the state of the [Data] on the other side of the edit.
+/// I.e. the state before the edit while on the undo stack and the state after the edit while on the redo stack.
+pub struct Revision {
+	pub edit: Edit,
+	data: Data,
+	/// The [History::revision] of the data.
+	revision: u64,
+	/// The memory the data takes (see [memory]).
+	memory: usize,
+}
+
+/// Roughly the number of bytes a copy of the data takes.
+fn memory(data: &Data) -> usize {
+	// every vertex has a key, a slot and a stamp besides itself, every connection an entry in 2 maps
+	let vertex = mem::size_of::<Vertex>() + 3 * mem::size_of::<VertexKey>() + mem::size_of::<Stamp>();
+	let connection = mem::size_of::<Connection>() + 4 * mem::size_of::<VertexKey>() + mem::size_of::<Stamp>();
+	let constraint = mem::size_of::<(Edge, Edge, IntersectionConstraint)>();
+	let labels = data.vertices.iter().flat_map(|v| v.label.as_ref()).map(|label| label.len()).sum::<usize>();
+	let items = data.vertices.len() * vertex + data.edges().count() * connection;
+	items + data.constraints().count() * constraint + labels
+}
+
+/// Undo/redo stack over [Data].
+///
+/// Every edit is recorded right before it is applied.
+/// Undoing swaps the current data with the recorded state and moves the [Revision] to the redo stack (and vice versa).
+/// When there are more steps than the limit or they take more memory than the budget, the oldest ones are dropped
+/// (the last step is kept even if it exceeds the budget on its own).
+pub struct History {
+	undo: VecDeque<Revision>,
+	redo: Vec<Revision>,
+	limit: usize,
+	/// The most bytes the recorded states should take.
+	budget: usize,
+	sealed: bool,
+	/// The revision of the current data.
+	current: u64,
+	/// The last revision that was handed out.
+	latest: u64,
+}
+impl Default for History {
+	fn default() -> Self {
+		Self::new(100, 64 << 20)
+	}
+}
+impl History {
+	/// - limit: the maximum number of steps that can be undone
+	/// - budget: the maximum number of bytes the recorded states should take (estimated)
+	pub fn new(limit: usize, budget: usize) -> Self {
+		Self { undo: VecDeque::new(), redo: Vec::new(), limit, budget, sealed: true, current: 0, latest: 0 }
+	}
+
+	/// Identifies the state of the data: undoing or redoing back to a state restores its revision,
+	/// every recorded edit (even one that coalesces) leads to a new one.
+	pub fn revision(&self) -> u64 {
+		self.current
+	}
+
+	/// Records the state of the data before the specified edit is applied.
+	///
+	/// If the edit [coalesces](Edit::coalesces) with the last recorded one and the history was not [sealed](Self::seal) since,
+	/// nothing is recorded and the edit becomes part of the last step.
+	pub fn record(&mut self, edit: Edit, before: &Data) {
+		self.redo.clear();
+		let revision = self.current;
+		self.latest += 1;
+		self.current = self.latest;
+
+		let coalesce = !self.sealed && edit.coalesces() && self.undo.back().is_some_and(|last| last.edit == edit);
+		self.sealed = false;
+		if coalesce {
+			return;
+		}
+
+		self.undo.push_back(Revision { edit, data: before.clone(), revision, memory: memory(before) });
+		while self.undo.len() > self.limit || (self.undo.len() > 1 && self.memory() > self.budget) {
+			self.undo.pop_front();
+		}
+	}
+	/// Roughly the number of bytes all recorded states take.
+	pub fn memory(&self) -> usize {
+		self.undo.iter().chain(&self.redo).map(|revision| revision.memory).sum()
+	}
+	/// Ends the current step. The next edit will be recorded as a new step even if it would coalesce.
+	pub fn seal(&mut self) {
+		self.sealed = true;
+	}
+	/// Drops all steps, the data is considered replaced (so it gets a new [Self::revision]).
+	pub fn clear(&mut self) {
+		self.undo.clear();
+		self.redo.clear();
+		self.sealed = true;
+		self.latest += 1;
+		self.current = self.latest;
+	}
+
+	/// Reverts the last step on the given data.
+	///
+	/// Returns the edit that was undone or Err(()) if there is nothing to undo.
+	pub fn undo(&mut self, current: &mut Data) -> Result<Edit, ()> {
+		let mut revision = self.undo.pop_back().ok_or(())?;
+		mem::swap(&mut revision.data, current);
+		mem::swap(&mut revision.revision, &mut self.current);
+		revision.memory = memory(&revision.data);
+		let edit = revision.edit;
+		self.redo.push(revision);
+		self.sealed = true;
+		Ok(edit)
+	}
+	/// Reapplies the last undone step on the given data.
+	///
+	/// Returns the edit that was redone or Err(()) if there is nothing to redo.
+	pub fn redo(&mut self, current: &mut Data) -> Result<Edit, ()> {
+		let mut revision = self.redo.pop().ok_or(())?;
+		mem::swap(&mut revision.data, current);
+		mem::swap(&mut revision.revision, &mut self.current);
+		revision.memory = memory(&revision.data);
+		let edit = revision.edit;
+		self.undo.push_back(revision);
+		self.sealed = true;
+		Ok(edit)
+	}
+
+	pub fn can_undo(&self) -> bool {
+		!self.undo.is_empty()
+	}
+	pub fn can_redo(&self) -> bool {
+		!self.redo.is_empty()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		common::{Number, Vector},
+		model::Classes,
+	};
+
+	/// The x coordinates of the vertices.
+	fn xs(data: &Data) -> Vec<Number> {
+		data.vertices.iter().map(|v| v.position.x).collect()
+	}
+	/// Records the edit and adds a vertex at the given x coordinate.
+	fn add(history: &mut History, data: &mut Data, x: Number) {
+		history.record(Edit::AddVertex, data);
+		data.add_vertex(Vertex::new(Vector::new(x, 0.0)));
+	}
+	/// Records the edit and moves the first vertex by the given amount.
+	fn drag(history: &mut History, data: &mut Data, dx: Number) {
+		history.record(Edit::Move, data);
+		data.vertices[0].position.x += dx;
+	}
+
+	#[test]
+	fn undo_and_redo_swap_the_recorded_states() {
+		let (mut history, mut data) = (History::default(), Data::new(Classes::default()));
+		assert!(!history.can_undo() && history.undo(&mut data).is_err());
+		add(&mut history, &mut data, 1.0);
+		add(&mut history, &mut data, 2.0);
+
+		assert!(history.undo(&mut data) == Ok(Edit::AddVertex));
+		assert_eq!(xs(&data), [1.0]);
+		assert!(history.undo(&mut data).is_ok());
+		assert!(xs(&data).is_empty());
+		assert!(!history.can_undo() && history.can_redo());
+
+		assert!(history.redo(&mut data).is_ok() && history.redo(&mut data).is_ok());
+		assert_eq!(xs(&data), [1.0, 2.0]);
+		assert!(history.redo(&mut data).is_err());
+
+		// a new edit drops what could be redone
+		history.undo(&mut data).unwrap();
+		add(&mut history, &mut data, 3.0);
+		assert!(!history.can_redo());
+		assert_eq!(xs(&data), [1.0, 3.0]);
+	}
+
+	#[test]
+	fn continuous_edits_coalesce_until_sealed() {
+		let (mut history, mut data) = (History::default(), Data::new(Classes::default()));
+		add(&mut history, &mut data, 0.0);
+		for _ in 0..10 {
+			drag(&mut history, &mut data, 1.0);
+		}
+		history.seal();
+		drag(&mut history, &mut data, 5.0);
+		assert_eq!(xs(&data), [15.0]);
+
+		history.undo(&mut data).unwrap();
+		assert_eq!(xs(&data), [10.0]);
+		history.undo(&mut data).unwrap();
+		assert_eq!(xs(&data), [0.0]);
+
+		// undoing and redoing seal as well
+		drag(&mut history, &mut data, 1.0);
+		history.undo(&mut data).unwrap();
+		history.redo(&mut data).unwrap();
+		drag(&mut history, &mut data, 2.0);
+		history.undo(&mut data).unwrap();
+		assert_eq!(xs(&data), [1.0]);
+	}
+
+	#[test]
+	fn other_edits_never_coalesce() {
+		let (mut history, mut data) = (History::default(), Data::new(Classes::default()));
+		add(&mut history, &mut data, 1.0);
+		add(&mut history, &mut data, 2.0);
+		history.undo(&mut data).unwrap();
+		assert_eq!(xs(&data), [1.0]);
+	}
+
+	#[test]
+	fn drops_the_oldest_steps_beyond_the_limit() {
+		let (mut history, mut data) = (History::new(2, usize::MAX), Data::new(Classes::default()));
+		for x in 1..=4 {
+			add(&mut history, &mut data, x as Number);
+		}
+		while history.undo(&mut data).is_ok() {}
+		assert_eq!(xs(&data), [1.0, 2.0]);
+	}
+
+	#[test]
+	fn drops_the_oldest_steps_beyond_the_budget() {
+		let mut data = Data::new(Classes::default());
+		for x in 0..100 {
+			data.add_vertex(Vertex::new(Vector::new(x as Number, 0.0)));
+		}
+		let size = memory(&data);
+		// room for 2 copies of the drawing (with 100 or 101 vertices), but not 3
+		let mut history = History::new(100, 2 * size + size / 2);
+		for x in 100..104 {
+			add(&mut history, &mut data, x as Number);
+			assert!(history.memory() <= 2 * size + size / 2);
+		}
+		let mut steps = 0;
+		while history.undo(&mut data).is_ok() {
+			steps += 1;
+		}
+		assert_eq!((steps, data.vertices.len()), (2, 102));
+
+		// the last step is kept even if it doesn't fit
+		let mut history = History::new(100, 0);
+		add(&mut history, &mut data, 0.0);
+		add(&mut history, &mut data, 0.0);
+		assert!(history.undo(&mut data).is_ok() && history.undo(&mut data).is_err());
+	}
+
+	#[test]
+	fn revisions_identify_the_states() {
+		let (mut history, mut data) = (History::default(), Data::new(Classes::default()));
+		let initial = history.revision();
+		add(&mut history, &mut data, 0.0);
+		let added = history.revision();
+		drag(&mut history, &mut data, 1.0);
+		let dragged = history.revision();
+		// every recorded edit is a new state, even if it coalesces
+		drag(&mut history, &mut data, 1.0);
+		let coalesced = history.revision();
+		assert!(initial != added && added != dragged && dragged != coalesced);
+
+		history.undo(&mut data).unwrap();
+		assert_eq!(history.revision(), added);
+		history.undo(&mut data).unwrap();
+		assert_eq!(history.revision(), initial);
+		history.redo(&mut data).unwrap();
+		history.redo(&mut data).unwrap();
+		assert_eq!(history.revision(), coalesced);
+
+		// a new edit after undoing is a new state, and so is replacing the data
+		history.undo(&mut data).unwrap();
+		drag(&mut history, &mut data, 1.0);
+		assert!(![initial, added, dragged, coalesced].contains(&history.revision()));
+		let before = history.revision();
+		history.clear();
+		assert!(history.revision() != before && !history.can_undo());
+	}
+}
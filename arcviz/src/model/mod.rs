@@ -1,4 +1,7 @@
+/// The position of a vertex in [Vertices] (changes when an earlier vertex is removed).
 pub type VertexId = usize;
+/// The persistent identifier of a vertex (see [Vertices::key]), kept when other vertices are removed and saved.
+pub type VertexKey = u64;
 
 mod data;
 pub use data::*;
@@ -18,8 +21,26 @@ pub use connections::*;
 mod arc;
 pub use arc::*;
 
+mod tolerance;
+pub use tolerance::*;
+
 mod classes;
 pub use classes::*;
 
 mod settings;
 pub use settings::*;
+
+mod history;
+pub use history::*;
+
+mod constraints;
+pub use constraints::*;
+
+mod spatial_index;
+pub use spatial_index::*;
+
+mod conflict_cache;
+pub use conflict_cache::*;
+
+mod stamp;
+pub use stamp::*;
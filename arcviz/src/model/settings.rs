@@ -2,6 +2,8 @@
 pub struct Settings {
 	pub show_grid: bool,
 	pub show_conflicts: bool,
+	pub color_conflicts: bool,
+	pub show_missing_intersections: bool,
 	pub show_labels: bool,
 	pub snap_to_grid: bool,
 	pub integrate_on_move: bool,
@@ -12,6 +14,8 @@ impl Default for Settings {
 		Self {
 			show_grid: true,
 			show_conflicts: true,
+			color_conflicts: true,
+			show_missing_intersections: true,
 			show_labels: true,
 			snap_to_grid: false,
 			integrate_on_move: true,
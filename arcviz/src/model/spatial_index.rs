@@ -0,0 +1,172 @@
+use std::{collections::HashMap, hash::Hash};
+
+use ncollide2d::{
+	bounding_volume::AABB,
+	partitioning::{DBVTLeaf, DBVTLeafId, BVH, BVT, DBVT},
+	query::visitors::BoundingVolumeInterferencesCollector,
+};
+
+use crate::common::{Bounds, Number, Vector};
+
+/// Half the side of the first square searched by [SpatialIndex::nearest] (doubled until it finds something).
+const INITIAL_SEARCH_RADIUS: Number = 16.0;
+/// How often [SpatialIndex::nearest] doubles the searched square before it tests all items
+/// (only reached if the items are very far away or their bounds are not finite).
+const MAX_SEARCH_STEPS: usize = 64;
+
+/// A bounding volume tree over the bounds of items (like vertices or arcs), identified by their index.
+///
+/// Queries only return the items whose bounds overlap the searched region, the caller does the exact test.
+/// The index is a snapshot, it has to be built again when the items move.
+pub struct SpatialIndex {
+	tree: Option<BVT<usize, AABB<Number>>>,
+	/// The bounds of each item.
+	bounds: Vec<Bounds>,
+	/// The bounds of all items together.
+	extent: Option<Bounds>,
+}
+
+impl SpatialIndex {
+	/// The index of the given bounds (the item at index `i` has the bounds at index `i`).
+	pub fn new(bounds: impl IntoIterator<Item = Bounds>) -> Self {
+		// the tree needs the lower corner first (bounds with a negative size have it last)
+		let bounds = bounds.into_iter().map(|b| Bounds::new_with_end(b.min(), b.max())).collect::<Vec<_>>();
+		let extent = bounds.iter().fold(None, |extent, b| Bounds::merged(&extent, &Some(b.clone())));
+		let leaves = bounds.iter().cloned().map(AABB::from).enumerate().collect::<Vec<_>>();
+		let tree = (!leaves.is_empty()).then(|| BVT::new_balanced(leaves));
+		Self { tree, bounds, extent }
+	}
+
+	/// The items whose bounds overlap the region.
+	pub fn query(&self, region: &Bounds) -> Vec<usize> {
+		let mut result = Vec::new();
+		if let Some(tree) = &self.tree {
+			let region = AABB::from(Bounds::new_with_end(region.min(), region.max()));
+			tree.visit(&mut BoundingVolumeInterferencesCollector::new(&region, &mut result));
+		}
+		result
+	}
+	/// The items whose bounds overlap the square with the given half side length around the position.
+	pub fn around(&self, position: Vector, radius: Number) -> Vec<usize> {
+		let by = Vector::new_square(radius);
+		self.query(&Bounds::new_with_end(position - by, position + by))
+	}
+
+	/// The item closest to the position with its distance.
+	///
+	/// Returns None if there are no items or the position is not finite.
+	///
+	/// - distance: the exact distance of the item to the position,
+	///   which may not be less than the distance of the bounds of the item
+	pub fn nearest(&self, position: Vector, distance: impl Fn(usize) -> Number) -> Option<(usize, Number)> {
+		let extent = self.extent.as_ref()?;
+		if !position.x.is_finite() || !position.y.is_finite() {
+			return None;
+		}
+		let closest =
+			|items: Vec<usize>| items.into_iter().map(|i| (i, distance(i))).min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+		// grow the searched square until it contains a candidate
+		let mut radius = INITIAL_SEARCH_RADIUS;
+		let mut candidate = None;
+		for _ in 0..MAX_SEARCH_STEPS {
+			candidate = closest(self.around(position, radius));
+			let by = Vector::new_square(radius);
+			let region = Bounds::new_with_end(position - by, position + by);
+			if candidate.is_some() || (region.contains(extent.min()) && region.contains(extent.max())) {
+				break;
+			}
+			radius *= 2.0;
+		}
+		let candidate = match candidate {
+			Some(candidate) => candidate,
+			None => closest((0..self.bounds.len()).collect())?,
+		};
+		if !candidate.1.is_finite() {
+			return Some(candidate);
+		}
+		// the closest item is at most as far as the candidate (but may be in a corner outside the square)
+		closest(self.around(position, candidate.1)).or(Some(candidate))
+	}
+
+	/// All pairs `(i, j)` with `i < j` whose bounds overlap (sorted).
+	pub fn overlapping_pairs(&self) -> Vec<(usize, usize)> {
+		let mut pairs = self
+			.bounds
+			.iter()
+			.enumerate()
+			.flat_map(|(i, bounds)| self.query(bounds).into_iter().filter(move |&j| j > i).map(move |j| (i, j)))
+			.collect::<Vec<_>>();
+		pairs.sort_unstable();
+		pairs
+	}
+}
+
+/// A bounding volume tree over the bounds of items that can be added and removed one by one.
+///
+/// Unlike [SpatialIndex] it can be kept while the items change (by removing and adding the changed ones).
+pub struct DynamicSpatialIndex<T> {
+	tree: DBVT<Number, T, AABB<Number>>,
+	leaves: HashMap<T, DBVTLeafId>,
+}
+impl<T> Default for DynamicSpatialIndex<T> {
+	fn default() -> Self {
+		Self { tree: DBVT::new(), leaves: HashMap::new() }
+	}
+}
+impl<T: Copy + Eq + Hash> DynamicSpatialIndex<T> {
+	/// Adds the item with the given bounds (replacing the bounds if it is already in the index).
+	pub fn insert(&mut self, item: T, bounds: &Bounds) {
+		self.remove(item);
+		let bounds = AABB::from(Bounds::new_with_end(bounds.min(), bounds.max()));
+		let leaf = self.tree.insert(DBVTLeaf::new(bounds, item));
+		self.leaves.insert(item, leaf);
+	}
+	pub fn remove(&mut self, item: T) {
+		if let Some(leaf) = self.leaves.remove(&item) {
+			self.tree.remove(leaf);
+		}
+	}
+	/// The items whose bounds overlap the region.
+	pub fn query(&self, region: &Bounds) -> Vec<T> {
+		let mut result = Vec::new();
+		let region = AABB::from(Bounds::new_with_end(region.min(), region.max()));
+		self.tree.visit(&mut BoundingVolumeInterferencesCollector::new(&region, &mut result));
+		result
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn points(points: &[(Number, Number)]) -> (SpatialIndex, Vec<Vector>) {
+		let points = points.iter().map(|&(x, y)| Vector::new(x, y)).collect::<Vec<_>>();
+		(SpatialIndex::new(points.iter().map(|&p| Bounds::from(p))), points)
+	}
+
+	#[test]
+	fn finds_the_nearest_point() {
+		let (index, points) = points(&[(0.0, 0.0), (100.0, 0.0), (1e6, 1e6), (-3.0, 40.0)]);
+		let nearest =
+			|position: Vector| index.nearest(position, |i| (points[i] - position).length()).map(|(i, _)| i);
+		assert_eq!(nearest(Vector::new(1.0, 1.0)), Some(0));
+		assert_eq!(nearest(Vector::new(-10.0, 30.0)), Some(3));
+		assert_eq!(nearest(Vector::new(1e6, 0.9e6)), Some(2));
+		assert_eq!(nearest(Vector::new(-1e9, 1e9)), Some(3));
+	}
+
+	#[test]
+	fn returns_none_for_positions_that_are_not_finite() {
+		let (index, points) = points(&[(0.0, 0.0), (100.0, 0.0)]);
+		for position in [Vector::new(Number::NAN, 0.0), Vector::new(0.0, Number::INFINITY)] {
+			assert!(index.nearest(position, |i| (points[i] - position).length()).is_none());
+		}
+	}
+
+	#[test]
+	fn returns_none_without_items() {
+		let (index, _) = points(&[]);
+		assert!(index.nearest(Vector::new(0.0, 0.0), |_| 0.0).is_none());
+	}
+}
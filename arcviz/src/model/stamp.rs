@@ -0,0 +1,54 @@
+use std::{
+	cell::RefCell,
+	rc::Rc,
+	sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Identifies the content of a part of the [super::Data] (like its vertices or connections).
+///
+/// Every (possible) change gets a new stamp, while clones keep it.
+/// So equal stamps mean equal content, even for parts of different clones (like the snapshots of the history).
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Stamp(u64);
+impl Stamp {
+	pub fn new() -> Self {
+		static NEXT: AtomicU64 = AtomicU64::new(0);
+		Self(NEXT.fetch_add(1, Ordering::Relaxed))
+	}
+}
+impl Default for Stamp {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// A value computed from the data that is kept until the key (usually made of [Stamp]s) changes.
+///
+/// A clone starts out empty (so the snapshots of the history don't copy it).
+pub struct Cached<K, T> {
+	inner: RefCell<Option<(K, Rc<T>)>>,
+}
+impl<K, T> Default for Cached<K, T> {
+	fn default() -> Self {
+		Self { inner: RefCell::new(None) }
+	}
+}
+impl<K, T> Clone for Cached<K, T> {
+	fn clone(&self) -> Self {
+		Self::default()
+	}
+}
+impl<K: PartialEq, T> Cached<K, T> {
+	/// The value for the key, computed only if the cached value has a different key.
+	pub fn get(&self, key: K, compute: impl FnOnce() -> T) -> Rc<T> {
+		let mut inner = self.inner.borrow_mut();
+		match &*inner {
+			Some((cached, value)) if *cached == key => value.clone(),
+			_ => {
+				let value = Rc::new(compute());
+				*inner = Some((key, value.clone()));
+				value
+			},
+		}
+	}
+}
@@ -0,0 +1,75 @@
+use std::cmp::Ordering;
+
+use crate::common::{Number, Precise};
+
+/// How [super::Arc::intersection_with] decides borderline cases,
+/// like arcs that (almost) touch or intersections at (or near) the end of an arc.
+///
+/// A comparison is first done with [Number]s and, if their error could change the result, again with
+/// [Precise] numbers computed from the vertex positions and radii (see [super::Arc::intersection_with]).
+/// Both come with a bound on their error, which includes the error of constructing the arcs.
+/// A decision is only reported as certain if the value is further from 0 than that bound,
+/// so it holds for the drawing itself and not just for its rounded arcs.
+///
+/// Values within `epsilon` (relative to the size of the arcs) of 0 are treated as 0 (arcs that touch).
+/// That decision is never certain, since deciding exact equality would need exact arithmetic
+/// (the arcs are constructed with square roots), it only means that the arcs touch within the tolerance.
+///
+/// The tolerance belongs to the drawing (it is stored in the JSON format and can be changed in the settings
+/// or with `--epsilon` on the command line).
+#[derive(Copy, Clone, PartialEq)]
+pub struct Tolerance {
+	pub epsilon: Number,
+}
+impl Default for Tolerance {
+	fn default() -> Self {
+		Self { epsilon: DEFAULT_EPSILON }
+	}
+}
+
+/// The epsilon of the default [Tolerance].
+pub const DEFAULT_EPSILON: Number = 1e-9;
+
+impl Tolerance {
+	/// The tolerance with the given epsilon, which has to be finite and in `[0, 1)`.
+	pub fn new(epsilon: Number) -> Result<Self, ()> {
+		match epsilon.is_finite() && (0.0..1.0).contains(&epsilon) {
+			true => Ok(Self { epsilon }),
+			false => Err(()),
+		}
+	}
+	/// Compares a value to 0.
+	///
+	/// - fast: the value computed with [Number]s and a bound on its error
+	/// - precise: the value computed with [Precise] numbers and a bound on its error (only called if needed)
+	/// - magnitude: the size of the terms the value was computed from
+	///
+	/// Returns the ordering and whether it is certain (only if the error can't change it).
+	pub fn compare(
+		&self,
+		fast: (Number, Number),
+		precise: impl FnOnce() -> (Precise, Number),
+		magnitude: Number,
+	) -> (Ordering, bool) {
+		let band = self.epsilon * magnitude;
+		let (value, error) = fast;
+		if value.abs() > band + error {
+			return (value.total_cmp(&0.0), true);
+		}
+		let (value, error) = precise();
+		match value.abs() {
+			_ if value.is_zero() && error == 0.0 => (Ordering::Equal, true),
+			difference if difference > band => (value.ordering(), difference > band + error),
+			_ => (Ordering::Equal, false),
+		}
+	}
+	/// The distance up to which points of the given size are considered the same.
+	pub fn distance(&self, size: Number) -> Number {
+		self.epsilon * size.max(1.0)
+	}
+	/// The angle up to which points on a circle with the given radius are considered the same
+	/// (the angle that spans [Self::distance] on the circle).
+	pub fn angle(&self, radius: Number) -> Number {
+		self.distance(radius) / radius
+	}
+}
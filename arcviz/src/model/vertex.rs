@@ -1,19 +1,16 @@
-use std::str::FromStr;
-
-use result_or_err::ResultOrErr;
-use serde::{Deserialize, Serialize};
-
 use crate::common::Vector;
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
 pub struct Vertex {
 	pub position: Vector,
 	pub label: Option<String>,
+	/// Pinned vertices are not moved by the layout optimizer.
+	pub pinned: bool,
 }
 
 impl Vertex {
 	pub fn new(position: Vector) -> Self {
-		Self { position, label: None }
+		Self { position, label: None, pinned: false }
 	}
 
 	pub fn set_label(&mut self, label: String) {
@@ -22,27 +19,11 @@ impl Vertex {
 	pub fn remove_label(&mut self) {
 		self.label = None;
 	}
-}
 
-impl ToString for Vertex {
-	fn to_string(&self) -> String {
-		match &self.label {
-			None => format!("{} {}", self.position.x, self.position.y),
-			Some(label) => format!("{} {} {}", self.position.x, self.position.y, label),
-		}
-	}
-}
-impl FromStr for Vertex {
-	type Err = ();
-	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let (x, rest) = s.split_once(" ").ok_or(())?;
-		if let Some((y, label)) = rest.split_once(" ") {
-			Ok(Self {
-				position: Vector::new(x.parse().or_err(())?, y.parse().or_err(())?),
-				label: Some(label.to_owned()),
-			})
-		} else {
-			Ok(Self { position: Vector::new(x.parse().or_err(())?, rest.parse().or_err(())?), label: None })
+	pub fn color(&self) -> &'static str {
+		match self.pinned {
+			true => "teal",
+			false => "green",
 		}
 	}
 }
@@ -1,34 +1,107 @@
-use std::str::FromStr;
-
-use serde::{Deserialize, Serialize};
+use std::{
+	collections::HashMap,
+	ops::{Index, IndexMut},
+	slice,
+};
 
 use crate::render::RenderTarget;
 
-use super::{Vertex, VertexId};
+use super::{Stamp, Vertex, VertexId, VertexKey};
 
-#[derive(Clone, Default, Serialize, Deserialize)]
+/// The vertices in their order (their [VertexId]) with their persistent [VertexKey]s.
+///
+/// Everything that refers to vertices persistently (connections, constraints) uses their keys
+/// and finds their current position through [Self::slot].
+#[derive(Clone, Default)]
 pub struct Vertices {
-	pub items: Vec<Vertex>,
+	/// Vertices are only added and removed through [Self::add] and [Self::remove] (which keep the keys in sync).
+	items: Vec<Vertex>,
+	/// The key of each item.
+	keys: Vec<VertexKey>,
+	/// The position of each key in the items.
+	slots: HashMap<VertexKey, VertexId>,
+	/// All keys below are (or were) in use.
+	next_key: VertexKey,
+	/// Renewed whenever a vertex may change (see [Self::stamp]).
+	stamp: Stamp,
+	/// The stamp of each item (see [Self::stamp_of]).
+	stamps: Vec<Stamp>,
 }
 
 impl Vertices {
+	/// Adds the vertex with a new key.
 	pub fn add(&mut self, vertex: Vertex) -> VertexId {
+		self.add_with_key(vertex, None)
+	}
+	/// Adds the vertex with the given key (or a new one if it is None or already taken).
+	pub fn add_with_key(&mut self, vertex: Vertex, key: Option<VertexKey>) -> VertexId {
+		let key = match key {
+			Some(key) if !self.slots.contains_key(&key) => key,
+			_ => self.next_key,
+		};
+		self.next_key = self.next_key.max(key.saturating_add(1));
+		self.stamp = Stamp::new();
 		self.items.push(vertex);
+		self.stamps.push(self.stamp);
+		self.keys.push(key);
+		self.slots.insert(key, self.items.len() - 1);
 		self.items.len() - 1
 	}
+	/// Makes sure new keys are at least the given one (so the keys that are added later don't collide).
+	pub fn reserve_keys(&mut self, below: VertexKey) {
+		self.next_key = self.next_key.max(below);
+	}
+	/// Removes the vertex, every later vertex moves down by 1 (but keeps its key).
 	pub fn remove(&mut self, vertex: VertexId) -> Option<Vertex> {
 		if vertex >= self.items.len() {
 			return None;
 		}
+		self.stamp = Stamp::new();
+		self.stamps.remove(vertex);
+		self.slots.remove(&self.keys.remove(vertex));
+		for (slot, key) in self.keys.iter().enumerate().skip(vertex) {
+			self.slots.insert(*key, slot);
+		}
 		Some(self.items.remove(vertex))
 	}
 	pub fn len(&self) -> VertexId {
 		self.items.len()
 	}
+	pub fn is_empty(&self) -> bool {
+		self.items.is_empty()
+	}
+	pub fn get(&self, vertex: VertexId) -> Option<&Vertex> {
+		self.items.get(vertex)
+	}
+	pub fn get_mut(&mut self, vertex: VertexId) -> Option<&mut Vertex> {
+		self.stamp = Stamp::new();
+		*self.stamps.get_mut(vertex)? = self.stamp;
+		self.items.get_mut(vertex)
+	}
+	/// All vertices in the order of their ids.
+	pub fn iter(&self) -> slice::Iter<'_, Vertex> {
+		self.items.iter()
+	}
+	/// The persistent key of the vertex.
+	pub fn key(&self, vertex: VertexId) -> Option<VertexKey> {
+		self.keys.get(vertex).copied()
+	}
+	/// The current position of the vertex with the given key.
+	pub fn slot(&self, key: VertexKey) -> Option<VertexId> {
+		self.slots.get(&key).copied()
+	}
+	/// Identifies the current vertices (it changes whenever they might have, see [Stamp]).
+	pub fn stamp(&self) -> Stamp {
+		self.stamp
+	}
+	/// Identifies the current state of the vertex (like [Self::stamp] but per vertex).
+	pub fn stamp_of(&self, vertex: VertexId) -> Option<Stamp> {
+		self.stamps.get(vertex).copied()
+	}
 
 	pub fn render(&self, renderer: &mut impl RenderTarget) {
 		for vertex in &self.items {
-			renderer.draw_vertex(vertex.position.clone(), "green", false);
+			renderer.draw_vertex(vertex.position.clone(), vertex.color(), false);
 			if let Some(label) = &vertex.label {
 				renderer.draw_label(vertex.position.clone(), label);
 			}
@@ -37,19 +110,21 @@ impl Vertices {
 	pub fn render_subset(&self, renderer: &mut impl RenderTarget, vertices: &Vec<VertexId>) {
 		for id in vertices {
 			let Some(vertex) = self.items.get(*id) else { continue };
-			renderer.draw_vertex(vertex.position.clone(), "green", false);
+			renderer.draw_vertex(vertex.position.clone(), vertex.color(), false);
 		}
 	}
 }
+impl Index<VertexId> for Vertices {
+	type Output = Vertex;
 
-impl ToString for Vertices {
-	fn to_string(&self) -> String {
-		self.items.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n")
+	fn index(&self, vertex: VertexId) -> &Vertex {
+		&self.items[vertex]
 	}
 }
-impl FromStr for Vertices {
-	type Err = ();
-	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		Ok(Self { items: s.lines().map(FromStr::from_str).collect::<Result<_, _>>()? })
+impl IndexMut<VertexId> for Vertices {
+	fn index_mut(&mut self, vertex: VertexId) -> &mut Vertex {
+		self.stamp = Stamp::new();
+		self.stamps[vertex] = self.stamp;
+		&mut self.items[vertex]
 	}
 }
@@ -18,6 +18,15 @@ pub trait RenderTarget {
 		color: &str,
 		selected: bool,
 	);
+	/// Emphasizes a connection arc (drawn underneath the arc itself).
+	fn draw_connection_highlight(
+		&mut self,
+		center: Vector,
+		radius: Number,
+		rotation: Number,
+		angle: Number,
+		color: &str,
+	);
 	fn draw_aid_line(&mut self, from: Vector, to: Vector);
 	fn draw_aid_box(&mut self, from: Vector, to: Vector);
 
@@ -0,0 +1,439 @@
+use crate::model::{
+	edge, Arc, Connection, ConnectionOrientation, Data, Edge, IntersectionConstraint, SizeId, VertexId,
+};
+
+const ORIENTATIONS: [ConnectionOrientation; 4] = [
+	ConnectionOrientation::InnerRight,
+	ConnectionOrientation::InnerLeft,
+	ConnectionOrientation::OuterRight,
+	ConnectionOrientation::OuterLeft,
+];
+
+#[derive(Clone)]
+pub struct AssignmentOptions {
+	/// Whether the orientation of connections may be changed.
+	pub vary_orientations: bool,
+	/// Whether the class (radius) of connections may be changed.
+	pub vary_classes: bool,
+	/// After how many steps the search gives up. (This keeps the UI responsive for larger graphs.)
+	pub step_limit: usize,
+}
+impl Default for AssignmentOptions {
+	fn default() -> Self {
+		Self { vary_orientations: true, vary_classes: true, step_limit: 1_000_000 }
+	}
+}
+
+/// An orientation and class for every connection.
+pub struct Assignment {
+	pub connections: Vec<(VertexId, VertexId, Connection)>,
+	/// The number of edge pairs that violate their intersection constraint with this assignment.
+	pub violations: usize,
+}
+impl Assignment {
+	/// Writes the assignment into the given data.
+	pub fn apply_to(&self, data: &mut Data) {
+		for (start, end, connection) in &self.connections {
+			if let Some(entry) = data.edge_mut(start, end) {
+				*entry = connection.clone();
+			}
+		}
+	}
+}
+
+pub enum AssignmentResult {
+	/// An assignment that satisfies all intersection constraints.
+	Solved(Assignment),
+	/// There is no assignment that satisfies all intersection constraints.
+	///
+	/// - best: the assignment with the fewest violations
+	/// - witness: a subset of the connections that already can't be drawn without violations
+	Infeasible { best: Option<Assignment>, witness: Vec<Edge> },
+	/// The step limit was reached before the search could be completed.
+	Undecided { best: Option<Assignment> },
+}
+
+/// Searches an assignment of orientation and class per connection that satisfies all intersection constraints.
+///
+/// This is a backtracking search (branch and bound) over the connections:
+/// Every time a connection gets assigned its arc is checked against the arcs of all connections assigned before.
+/// Partial assignments that can't beat the best complete assignment found so far are discarded.
+///
+/// The search can be interrupted after any number of steps (see [Self::step]), so it can run alongside the UI.
+pub struct AssignmentSolver {
+	problem: Problem,
+	options: AssignmentOptions,
+	steps: usize,
+	search: Search,
+	/// The best assignment of the search over all connections, once it is finished.
+	best: Option<Assignment>,
+	/// While shrinking the witness: the infeasible variables so far and the one whose removal is tested.
+	witness: Option<(Vec<usize>, usize)>,
+}
+
+/// The variables (connections) and their possible values.
+struct Problem {
+	data: Data,
+	variables: Vec<(VertexId, VertexId)>,
+	/// The possible values of every variable (with their arcs, so they don't have to be reconstructed).
+	candidates: Vec<Vec<(Connection, Arc)>>,
+}
+
+/// The state of one search over a subset of the variables.
+///
+/// The search is a depth first traversal that is kept on explicit stacks, so it can be paused at any step.
+struct Search {
+	subset: Vec<usize>,
+	/// The chosen candidate for every assigned variable of the subset (in subset order).
+	choices: Vec<usize>,
+	/// The number of violations after every choice.
+	violations: Vec<usize>,
+	/// The next candidate to try for every variable that is being assigned.
+	next: Vec<usize>,
+	best: Option<(Vec<usize>, usize)>,
+	finished: bool,
+}
+
+impl Search {
+	fn new(subset: Vec<usize>) -> Self {
+		Self { subset, choices: Vec::new(), violations: Vec::new(), next: Vec::new(), best: None, finished: false }
+	}
+
+	/// Continues the search until it is finished or the budget is used up (one step per tried candidate).
+	///
+	/// Returns whether the search is finished.
+	fn advance(&mut self, problem: &Problem, budget: &mut usize) -> bool {
+		while !self.finished {
+			let depth = self.choices.len();
+			let violations = self.violations.last().copied().unwrap_or(0);
+			if self.next.len() == depth {
+				// entering a (partial) assignment
+				if self.best.as_ref().is_some_and(|(_, best)| violations >= *best) {
+					self.backtrack(); // can't get better than what we have
+					continue;
+				}
+				if depth == self.subset.len() {
+					self.best = Some((self.choices.clone(), violations));
+					self.backtrack();
+					continue;
+				}
+				self.next.push(0);
+			}
+
+			let variable = self.subset[depth];
+			let candidate = self.next[depth];
+			if candidate == problem.candidates[variable].len() || self.best.as_ref().is_some_and(|(_, b)| *b == 0) {
+				self.next.pop();
+				self.backtrack();
+				continue;
+			}
+			if *budget == 0 {
+				return false;
+			}
+			*budget -= 1;
+			self.next[depth] += 1;
+
+			let added = (0..depth)
+				.filter(|&i| problem.violates((variable, candidate), (self.subset[i], self.choices[i])))
+				.count();
+			self.choices.push(candidate);
+			self.violations.push(violations + added);
+		}
+		true
+	}
+	fn backtrack(&mut self) {
+		self.violations.pop();
+		if self.choices.pop().is_none() {
+			self.finished = true;
+		}
+	}
+	/// Whether the finished search proved that the subset can't be assigned without violations.
+	fn is_infeasible(&self) -> bool {
+		self.finished && self.best.as_ref().is_some_and(|(_, violations)| *violations > 0)
+	}
+}
+
+impl Problem {
+	fn new(data: &Data, options: &AssignmentOptions) -> Self {
+		let variables = data.edges().map(|(start, end, _)| (start, end)).collect::<Vec<_>>();
+		let candidates = data
+			.edges()
+			.map(|(start, end, current)| Self::candidates_for(data, start, end, current, options))
+			.collect();
+		Self { data: data.clone(), variables, candidates }
+	}
+
+	fn candidates_for(
+		data: &Data,
+		start: VertexId,
+		end: VertexId,
+		current: &Connection,
+		options: &AssignmentOptions,
+	) -> Vec<(Connection, Arc)> {
+		let orientations = match options.vary_orientations {
+			true => ORIENTATIONS.to_vec(),
+			false => vec![current.orientation],
+		};
+		let sizes = match options.vary_classes {
+			true => (0..data.classes.len()).collect::<Vec<SizeId>>(),
+			false => vec![current.size],
+		};
+
+		// the current state of the connection is tried first, so solutions stay close to the existing drawing
+		let mut connections = vec![current.clone()];
+		for &size in &sizes {
+			for &orientation in &orientations {
+				let connection = Connection::new(orientation, size);
+				if connection.size != current.size || !Self::same_orientation(orientation, current.orientation) {
+					connections.push(connection);
+				}
+			}
+		}
+
+		connections
+			.into_iter()
+			.flat_map(|connection| {
+				let arc = Arc::construct(start, end, &data.vertices, &connection, &data.classes).ok()?;
+				Some((connection, arc))
+			})
+			.collect()
+	}
+
+	fn same_orientation(a: ConnectionOrientation, b: ConnectionOrientation) -> bool {
+		a.is_left() == b.is_left() && a.is_inner() == b.is_inner()
+	}
+
+	/// Whether the given variable/candidate pairs violate the intersection constraint of their edge pair.
+	fn violates(&self, (i, ci): (usize, usize), (j, cj): (usize, usize)) -> bool {
+		let (a, b) = self.variables[i];
+		let (c, d) = self.variables[j];
+		let arc = &self.candidates[i][ci].1;
+		let other = &self.candidates[j][cj].1;
+
+		let count = self.data.intersections_between((&a, &b, arc), (&c, &d, other)).len();
+		match self.data.constraint(edge(a, b), edge(c, d)) {
+			_ if count > 1 => true,
+			IntersectionConstraint::Necessary => count != 1,
+			IntersectionConstraint::Forbidden => count > 0,
+			IntersectionConstraint::Irrelevant => false,
+		}
+	}
+
+	fn assignment(&self, subset: &[usize], choices: &[usize], violations: usize) -> Assignment {
+		let connections = subset
+			.iter()
+			.zip(choices)
+			.map(|(&variable, &candidate)| {
+				let (start, end) = self.variables[variable];
+				(start, end, self.candidates[variable][candidate].0.clone())
+			})
+			.collect();
+		Assignment { connections, violations }
+	}
+
+	fn edges(&self, variables: &[usize]) -> Vec<Edge> {
+		variables.iter().map(|&i| edge(self.variables[i].0, self.variables[i].1)).collect()
+	}
+}
+
+impl AssignmentSolver {
+	/// Prepares the search for the given data (which is copied, so it may change while the search runs).
+	pub fn new(data: &Data, options: AssignmentOptions) -> Self {
+		let problem = Problem::new(data, &options);
+		let search = Search::new((0..problem.variables.len()).collect());
+		Self { problem, options, steps: 0, search, best: None, witness: None }
+	}
+
+	/// Searches until the search is finished.
+	pub fn solve(&mut self) -> AssignmentResult {
+		loop {
+			if let Some(result) = self.step(usize::MAX) {
+				return result;
+			}
+		}
+	}
+
+	/// Continues the search for (at most) the given number of steps.
+	///
+	/// Returns the result once the search is finished (or the step limit is reached), None otherwise.
+	pub fn step(&mut self, steps: usize) -> Option<AssignmentResult> {
+		// connections that can't be drawn at all (vertices too far apart for every class)
+		if let Some(unreachable) = self.problem.candidates.iter().position(|c| c.is_empty()) {
+			return Some(AssignmentResult::Infeasible { best: None, witness: self.problem.edges(&[unreachable]) });
+		}
+
+		let mut budget = steps;
+		loop {
+			let allowed = budget.min(self.options.step_limit.saturating_sub(self.steps));
+			let mut remaining = allowed;
+			let finished = self.search.advance(&self.problem, &mut remaining);
+			self.steps += allowed - remaining;
+			budget -= allowed - remaining;
+
+			if !finished {
+				if self.steps < self.options.step_limit {
+					return None;
+				}
+				return Some(match self.witness.take() {
+					None => AssignmentResult::Undecided { best: self.search_best() },
+					// the witness is infeasible, it just might not be minimal
+					Some((subset, _)) => {
+						AssignmentResult::Infeasible { best: self.best.take(), witness: self.problem.edges(&subset) }
+					},
+				});
+			}
+
+			let (subset, index) = match self.witness.take() {
+				None => {
+					// the search over all connections is finished
+					self.best = self.search_best();
+					if self.best.as_ref().is_some_and(|best| best.violations == 0) {
+						return self.best.take().map(AssignmentResult::Solved);
+					}
+					(self.search.subset.clone(), 0)
+				},
+				// every variable is removed once, if the rest is still infeasible it stays removed
+				Some((mut subset, index)) => match self.search.is_infeasible() {
+					true => {
+						subset.remove(index);
+						(subset, index)
+					},
+					false => (subset, index + 1),
+				},
+			};
+			if index >= subset.len() {
+				let witness = self.problem.edges(&subset);
+				return Some(AssignmentResult::Infeasible { best: self.best.take(), witness });
+			}
+
+			let mut reduced = subset.clone();
+			reduced.remove(index);
+			self.search = Search::new(reduced);
+			self.witness = Some((subset, index));
+		}
+	}
+
+	fn search_best(&self) -> Option<Assignment> {
+		let (choices, violations) = self.search.best.as_ref()?;
+		Some(self.problem.assignment(&self.search.subset, choices, *violations))
+	}
+
+	/// The number of steps the search took so far.
+	pub fn steps(&self) -> usize {
+		self.steps
+	}
+	/// While an infeasible set of connections is shrunk: how many of its connections were tested and its size.
+	pub fn witness_progress(&self) -> Option<(usize, usize)> {
+		self.witness.as_ref().map(|(subset, index)| (*index, subset.len()))
+	}
+}
+
+/// Convenience function for [AssignmentSolver::solve].
+pub fn solve_assignment(data: &Data, options: AssignmentOptions) -> AssignmentResult {
+	AssignmentSolver::new(data, options).solve()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		common::Vector,
+		model::{Classes, Vertex},
+	};
+
+	/// A drawing with one connection of the given class per pair of positions.
+	fn drawing(edges: &[((f64, f64), (f64, f64))], size: SizeId) -> Data {
+		let mut data = Data::new(Classes::default());
+		for ((ax, ay), (bx, by)) in edges {
+			let a = data.add_vertex(Vertex::new(Vector::new(*ax, *ay)));
+			let b = data.add_vertex(Vertex::new(Vector::new(*bx, *by)));
+			data.add_connection(a, b, ConnectionOrientation::InnerRight, size).unwrap();
+		}
+		data
+	}
+
+	/// Three connections far apart from each other, the first two have to intersect.
+	fn infeasible() -> Data {
+		let mut data = drawing(
+			&[((0.0, 0.0), (100.0, 0.0)), ((5000.0, 0.0), (5100.0, 0.0)), ((10000.0, 0.0), (10100.0, 0.0))],
+			2,
+		);
+		data.set_constraint(edge(0, 1), edge(2, 3), IntersectionConstraint::Necessary).unwrap();
+		data
+	}
+
+	fn sorted(mut edges: Vec<Edge>) -> Vec<Edge> {
+		edges.sort();
+		edges
+	}
+
+	#[test]
+	fn keeps_a_satisfying_drawing() {
+		let mut data = drawing(&[((0.0, 0.0), (100.0, 0.0)), ((5000.0, 0.0), (5100.0, 0.0))], 1);
+		data.set_constraint(edge(0, 1), edge(2, 3), IntersectionConstraint::Forbidden).unwrap();
+
+		let AssignmentResult::Solved(assignment) = solve_assignment(&data, AssignmentOptions::default()) else {
+			panic!("expected a solution");
+		};
+		assert_eq!(assignment.violations, 0);
+		for (_, _, connection) in &assignment.connections {
+			assert_eq!(connection.size, 1);
+			assert!(matches!(connection.orientation, ConnectionOrientation::InnerRight));
+		}
+	}
+
+	#[test]
+	fn finds_a_class_that_spans_the_connection() {
+		// the vertices are 500 apart, only the classes with radius 300 and 400 reach
+		let data = drawing(&[((0.0, 0.0), (500.0, 0.0))], 0);
+
+		let AssignmentResult::Solved(assignment) = solve_assignment(&data, AssignmentOptions::default()) else {
+			panic!("expected a solution");
+		};
+		assert_eq!(assignment.connections.len(), 1);
+		assert_eq!(assignment.connections[0].2.size, 2);
+
+		let options = AssignmentOptions { vary_classes: false, ..Default::default() };
+		let AssignmentResult::Infeasible { best, witness } = solve_assignment(&data, options) else {
+			panic!("expected no solution");
+		};
+		assert!(best.is_none());
+		assert_eq!(witness, vec![edge(0, 1)]);
+	}
+
+	#[test]
+	fn reports_the_best_assignment_and_a_minimal_witness() {
+		let AssignmentResult::Infeasible { best, witness } = solve_assignment(&infeasible(), Default::default())
+		else {
+			panic!("expected no solution");
+		};
+		assert_eq!(best.map(|best| best.violations), Some(1));
+		// the third connection is not involved
+		assert_eq!(sorted(witness), vec![edge(0, 1), edge(2, 3)]);
+	}
+
+	#[test]
+	fn stepping_gives_the_same_result_as_solving() {
+		let data = infeasible();
+		let mut solver = AssignmentSolver::new(&data, AssignmentOptions::default());
+		let mut calls = 0;
+		let result = loop {
+			calls += 1;
+			if let Some(result) = solver.step(7) {
+				break result;
+			}
+			assert!(calls < 100_000, "the search doesn't make progress");
+		};
+		assert!(calls > 1);
+		let AssignmentResult::Infeasible { witness, .. } = result else { panic!("expected no solution") };
+		assert_eq!(sorted(witness), vec![edge(0, 1), edge(2, 3)]);
+	}
+
+	#[test]
+	fn stops_at_the_step_limit() {
+		let options = AssignmentOptions { step_limit: 1, ..Default::default() };
+		let mut solver = AssignmentSolver::new(&infeasible(), options);
+		assert!(matches!(solver.solve(), AssignmentResult::Undecided { best: None }));
+		assert_eq!(solver.steps(), 1);
+	}
+}
@@ -0,0 +1,227 @@
+use std::cmp::Ordering;
+
+use crate::{
+	common::{Number, Vector, XorShift},
+	model::{ConflictStatus, Data, VertexId},
+};
+
+#[derive(Clone)]
+pub struct LayoutOptions {
+	/// How far a vertex is moved at most in a single step (at the initial temperature).
+	pub step_size: Number,
+	/// The initial temperature. (Higher temperatures accept worse layouts more often.)
+	pub temperature: Number,
+	/// The factor the temperature is multiplied with after every step.
+	pub cooling: Number,
+	/// After how many steps the optimization stops.
+	pub iterations: usize,
+	pub seed: u64,
+}
+impl Default for LayoutOptions {
+	fn default() -> Self {
+		Self { step_size: 50.0, temperature: 1.0, cooling: 0.999, iterations: 5000, seed: 0x2545_f491_4f6c_dd1d }
+	}
+}
+
+/// How bad a layout is. Compared lexicographically (in field order).
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LayoutCost {
+	/// The number of connections whose arc can't be constructed.
+	pub unreachable: usize,
+	/// The number of intersections that violate their constraint plus the number of missing required intersections.
+	pub violations: usize,
+	/// The number of intersections that are tolerated but not required.
+	pub crossings: usize,
+}
+impl LayoutCost {
+	pub fn of(data: &Data) -> Self {
+		let unreachable = data.unreachable().count();
+		let conflicts = data.conflicts();
+		let missing = data.missing_intersections(&conflicts).len();
+		let violated = conflicts.iter().filter(|c| c.status == ConflictStatus::Violated).count();
+		let crossings = conflicts.iter().filter(|c| c.status == ConflictStatus::Tolerated).count();
+		Self { unreachable, violations: violated + missing, crossings }
+	}
+
+	/// A single number to compare layouts by, used for the acceptance probability.
+	fn energy(&self) -> Number {
+		(self.unreachable * 1000 + self.violations * 10 + self.crossings) as Number
+	}
+}
+
+/// Moves the unpinned vertices to reduce the number of conflicts (simulated annealing).
+///
+/// A step moves one random unpinned vertex by a random offset.
+/// The step is rejected if it makes a connection unreachable that wasn't before, otherwise it's accepted if it
+/// doesn't make the layout worse or (with a probability depending on the temperature) even if it does.
+pub struct LayoutOptimizer {
+	options: LayoutOptions,
+	current: Data,
+	current_cost: LayoutCost,
+	best: Data,
+	best_cost: LayoutCost,
+	movable: Vec<VertexId>,
+	temperature: Number,
+	iteration: usize,
+	random: XorShift,
+}
+
+impl LayoutOptimizer {
+	pub fn new(data: &Data, options: LayoutOptions) -> Self {
+		let cost = LayoutCost::of(data);
+		let movable = data.vertices.iter().enumerate().filter(|(_, v)| !v.pinned).map(|(i, _)| i).collect();
+		Self {
+			current: data.clone(),
+			current_cost: cost,
+			best: data.clone(),
+			best_cost: cost,
+			movable,
+			temperature: options.temperature,
+			iteration: 0,
+			random: XorShift::new(options.seed),
+			options,
+		}
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.iteration >= self.options.iterations
+			|| self.movable.is_empty()
+			|| self.best_cost == LayoutCost::default()
+	}
+
+	/// Performs (at most) the given number of steps.
+	///
+	/// Returns whether the optimization is finished.
+	pub fn step(&mut self, steps: usize) -> bool {
+		for _ in 0..steps {
+			if self.is_finished() {
+				break;
+			}
+			self.iteration += 1;
+
+			let vertex = self.movable[self.random.below(self.movable.len())];
+			let Some(original) = self.current.vertices.get(vertex).map(|v| v.position) else { continue };
+			let offset = Vector::new(self.random.signed_unit(), self.random.signed_unit())
+				* (self.options.step_size * self.temperature.max(0.05));
+			self.current.vertices[vertex].position = original + offset;
+
+			let cost = LayoutCost::of(&self.current);
+			let accept = match cost.cmp(&self.current_cost) {
+				_ if cost.unreachable > self.current_cost.unreachable => false,
+				Ordering::Less | Ordering::Equal => true,
+				Ordering::Greater => {
+					let delta = cost.energy() - self.current_cost.energy();
+					self.random.unit() < (-delta / self.temperature.max(Number::EPSILON)).exp()
+				},
+			};
+
+			if accept {
+				self.current_cost = cost;
+				if cost < self.best_cost {
+					self.best = self.current.clone();
+					self.best_cost = cost;
+				}
+			} else {
+				self.current.vertices[vertex].position = original;
+			}
+			self.temperature *= self.options.cooling;
+		}
+		self.is_finished()
+	}
+
+	/// The best layout found so far.
+	pub fn best(&self) -> &Data {
+		&self.best
+	}
+	pub fn best_cost(&self) -> LayoutCost {
+		self.best_cost
+	}
+	pub fn iteration(&self) -> usize {
+		self.iteration
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::{edge, Classes, ConnectionOrientation, IntersectionConstraint, Vertex};
+
+	/// A ring of random vertices (every third one pinned) with a forbidden intersection between two of its
+	/// connections.
+	fn drawing(seed: u64) -> Data {
+		let mut random = XorShift::new(seed);
+		let mut data = Data::new(Classes::default());
+		for i in 0..8 {
+			let mut vertex = Vertex::new(Vector::new(300.0 * random.unit(), 300.0 * random.unit()));
+			vertex.pinned = i % 3 == 0;
+			data.add_vertex(vertex);
+		}
+		for i in 0..8 {
+			data.add_connection(i, (i + 1) % 8, ConnectionOrientation::InnerRight, 3).unwrap();
+		}
+		data.set_constraint(edge(0, 1), edge(4, 5), IntersectionConstraint::Forbidden).unwrap();
+		data
+	}
+	fn positions(data: &Data) -> Vec<(Number, Number)> {
+		data.vertices.iter().map(|vertex| (vertex.position.x, vertex.position.y)).collect()
+	}
+	/// Runs the optimizer to the end (with fewer iterations than by default).
+	fn optimize(data: &Data, seed: u64) -> LayoutOptimizer {
+		let mut optimizer =
+			LayoutOptimizer::new(data, LayoutOptions { iterations: 300, seed, ..Default::default() });
+		while !optimizer.step(50) {}
+		optimizer
+	}
+
+	#[test]
+	fn never_moves_pinned_vertices() {
+		for seed in 1..6 {
+			let data = drawing(seed);
+			let optimizer = optimize(&data, seed);
+			let (before, after) = (positions(&data), positions(optimizer.best()));
+			for (i, vertex) in data.vertices.iter().enumerate() {
+				if vertex.pinned {
+					assert_eq!(before[i], after[i], "seed {}, vertex {}", seed, i);
+				}
+			}
+		}
+
+		// nothing to move
+		let mut data = drawing(1);
+		for i in 0..data.vertices.len() {
+			data.vertices[i].pinned = true;
+		}
+		let mut optimizer = LayoutOptimizer::new(&data, LayoutOptions::default());
+		assert!(optimizer.step(10));
+		assert_eq!(optimizer.iteration(), 0);
+		assert_eq!(positions(optimizer.best()), positions(&data));
+	}
+
+	#[test]
+	fn keeps_every_connection_constructible() {
+		for seed in 1..6 {
+			let data = drawing(seed);
+			assert_eq!(data.unreachable().count(), 0);
+			let optimizer = optimize(&data, seed);
+			assert_eq!(optimizer.best().unreachable().count(), 0, "seed {}", seed);
+			assert_eq!(optimizer.best().edges().count(), 8);
+		}
+	}
+
+	#[test]
+	fn never_returns_a_worse_layout() {
+		for seed in 1..6 {
+			let data = drawing(seed);
+			let optimizer = optimize(&data, seed);
+			let cost = LayoutCost::of(optimizer.best());
+			assert!(cost == optimizer.best_cost(), "seed {}", seed);
+			assert!(cost <= LayoutCost::of(&data), "seed {}", seed);
+		}
+	}
+
+	#[test]
+	fn is_deterministic_for_a_seed() {
+		let data = drawing(3);
+		assert_eq!(positions(optimize(&data, 7).best()), positions(optimize(&data, 7).best()));
+	}
+}
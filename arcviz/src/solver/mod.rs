@@ -0,0 +1,11 @@
+//! Automatic search for better drawings.
+//!
+//! - [assignment](AssignmentSolver): keeps the vertex positions fixed and changes the properties of the connections
+//! (orientation, class) to satisfy the intersection constraints
+//! - [layout](LayoutOptimizer): keeps the connections fixed and moves the vertices to reduce the number of conflicts
+
+mod assignment;
+pub use assignment::*;
+
+mod layout;
+pub use layout::*;
@@ -7,7 +7,7 @@ use webbit::{
 	Component, ComponentContent,
 };
 
-use super::{Bar, Workspace};
+use super::{Bar, DocumentIO, Workspace};
 
 pub struct App {
 	element: HtmlDivElement,
@@ -17,11 +17,10 @@ pub struct App {
 }
 
 impl App {
-	pub fn new(io: FileIOHandler) -> Self {
+	pub fn new(io: FileIOHandler, document_io: DocumentIO) -> Self {
 		let element = styled(div(), "app");
 		let main = on(&element, styled(div(), "main"));
 
-		let workspace = Workspace::new(io);
 		let dock = SideBar::new(
 			element.clone().into(),
 			"sidebar",
@@ -32,9 +31,11 @@ impl App {
 			"sidebar-control-title",
 			"sidebar-content",
 		);
+		let workspace = Workspace::new(io, document_io, dock.clone());
 
 		let bar = Component::make(Bar::new(workspace.clone(), dock.clone()));
 		bar.mount_in(&main);
+		on(&main, workspace.tabs.clone());
 		let workspace = Component::make_sharable(workspace);
 		workspace.mount_in(&main);
 
@@ -12,9 +12,13 @@ use wasm_bindgen::JsValue;
 use web_sys::{console, Element, Event, HtmlDivElement, HtmlInputElement};
 
 use crate::{
+	io::graph::GraphFormat,
 	model::ConnectionOrientation,
 	render::Stage,
-	ui::{BarChoice, BarChoiceFactory, ConflictView, Picker, SettingsView},
+	ui::{
+		BarChoice, BarChoiceFactory, ConflictView, GraphView, LayoutView, MetadataView, Picker, SettingsView,
+		SolverView,
+	},
 };
 
 use super::{Mode, Workspace};
@@ -46,9 +50,18 @@ pub struct Bar {
 	dock: Rc<SideBar>,
 	element: HtmlDivElement,
 	open: Component<Button>,
-	save_text: Component<Button>,
 	save: Component<Button>,
+	save_as: Component<Button>,
 	export_ipe: Component<Button>,
+	export_svg: Component<Button>,
+	export_tikz: Component<Button>,
+	export_dot: Component<Button>,
+	export_graphml: Component<Button>,
+	undo: Component<Button>,
+	redo: Component<Button>,
+	copy: Component<Button>,
+	cut: Component<Button>,
+	paste: Component<Button>,
 	mode_selector: Component<BarChoice<ModePicker>>,
 	orientation_selector: Component<BarChoice<OrientationPicker>>,
 	size_selector: Component<BarChoice<SizePicker>>,
@@ -59,6 +72,10 @@ pub struct Bar {
 	refresh: Component<Button>,
 	settings: Component<Button>,
 	conflicts: Component<Button>,
+	solver: Component<Button>,
+	layout: Component<Button>,
+	graphs: Component<Button>,
+	document: Component<Button>,
 }
 
 impl Bar {
@@ -73,20 +90,20 @@ impl Bar {
 			}
 		}));
 		open.mount_in(&file_group);
-		let save_text = Component::make(Button::new_with_handler(Some("save"), "bar-accent-button", {
+		let save = Component::make(Button::new_with_handler(Some("save"), "bar-accent-button", {
 			let workspace = workspace.clone();
 			move |_| {
-				workspace.save_text();
+				workspace.save();
 			}
 		}));
-		save_text.mount_in(&file_group);
-		let save = Component::make(Button::new_with_handler(Some("save json"), "bar-accent-button", {
+		save.mount_in(&file_group);
+		let save_as = Component::make(Button::new_with_handler(Some("save as"), "bar-accent-button", {
 			let workspace = workspace.clone();
 			move |_| {
-				workspace.save();
+				workspace.save_as();
 			}
 		}));
-		save.mount_in(&file_group);
+		save_as.mount_in(&file_group);
 		let export_ipe = Component::make(Button::new_with_handler(Some("export ipe"), "bar-accent-button", {
 			let workspace = workspace.clone();
 			move |_| {
@@ -94,6 +111,71 @@ impl Bar {
 			}
 		}));
 		export_ipe.mount_in(&file_group);
+		let export_svg = Component::make(Button::new_with_handler(Some("export svg"), "bar-accent-button", {
+			let workspace = workspace.clone();
+			move |_| {
+				workspace.export_svg();
+			}
+		}));
+		export_svg.mount_in(&file_group);
+		let export_tikz = Component::make(Button::new_with_handler(Some("export tikz"), "bar-accent-button", {
+			let workspace = workspace.clone();
+			move |_| {
+				workspace.export_tikz();
+			}
+		}));
+		export_tikz.mount_in(&file_group);
+		let export_dot = Component::make(Button::new_with_handler(Some("export dot"), "bar-accent-button", {
+			let workspace = workspace.clone();
+			move |_| {
+				workspace.export_graph(GraphFormat::Dot);
+			}
+		}));
+		export_dot.mount_in(&file_group);
+		let export_graphml = Component::make(Button::new_with_handler(Some("export graphml"), "bar-accent-button", {
+			let workspace = workspace.clone();
+			move |_| {
+				workspace.export_graph(GraphFormat::GraphMl);
+			}
+		}));
+		export_graphml.mount_in(&file_group);
+
+		let history_group = on(&element, styled(div(), "bar-group"));
+		let undo = Component::make(Button::new_with_handler(Some("undo"), "bar-accent-button", {
+			let workspace = workspace.clone();
+			move |_| {
+				workspace.undo();
+			}
+		}));
+		undo.mount_in(&history_group);
+		let redo = Component::make(Button::new_with_handler(Some("redo"), "bar-accent-button", {
+			let workspace = workspace.clone();
+			move |_| {
+				workspace.redo();
+			}
+		}));
+		redo.mount_in(&history_group);
+		let copy = Component::make(Button::new_with_handler(Some("copy"), "bar-accent-button", {
+			let workspace = workspace.clone();
+			move |_| {
+				workspace.copy();
+			}
+		}));
+		copy.mount_in(&history_group);
+		let cut = Component::make(Button::new_with_handler(Some("cut"), "bar-accent-button", {
+			let workspace = workspace.clone();
+			move |_| {
+				workspace.cut();
+			}
+		}));
+		cut.mount_in(&history_group);
+		let paste = Component::make(Button::new_with_handler(Some("paste"), "bar-accent-button", {
+			let workspace = workspace.clone();
+			move |_| {
+				workspace.paste();
+			}
+		}));
+		paste.mount_in(&history_group);
 
 		let mut mode_selector =
 			GroupContainer::new("bar-group", BarChoiceFactory::new("bar-button", workspace.clone()));
@@ -175,15 +257,60 @@ impl Bar {
 			}
 		}));
 		conflicts.mount_in(&view_group);
+		let solver = Component::make(Button::new_with_handler(Some("solve"), "bar-accent-button", {
+			let dock = dock.clone();
+			let workspace = workspace.clone();
+			move |_| {
+				dock.set_title(Some("Solver"));
+				dock.open(SolverView::new(workspace.clone()));
+			}
+		}));
+		solver.mount_in(&view_group);
+		let layout = Component::make(Button::new_with_handler(Some("layout"), "bar-accent-button", {
+			let dock = dock.clone();
+			let workspace = workspace.clone();
+			move |_| {
+				dock.set_title(Some("Layout"));
+				dock.open(LayoutView::new(workspace.clone()));
+			}
+		}));
+		layout.mount_in(&view_group);
+		let graphs = Component::make(Button::new_with_handler(Some("graphs"), "bar-accent-button", {
+			let dock = dock.clone();
+			let workspace = workspace.clone();
+			move |_| {
+				dock.set_title(Some("Graphs"));
+				dock.open(GraphView::new(workspace.clone()));
+			}
+		}));
+		graphs.mount_in(&view_group);
+		let document = Component::make(Button::new_with_handler(Some("document"), "bar-accent-button", {
+			let dock = dock.clone();
+			let workspace = workspace.clone();
+			move |_| {
+				dock.set_title(Some("Document"));
+				dock.open(MetadataView::new(workspace.clone()));
+			}
+		}));
+		document.mount_in(&view_group);
 
 		Self {
 			workspace,
 			dock,
 			element,
 			open,
-			save_text,
 			save,
+			save_as,
 			export_ipe,
+			export_svg,
+			export_tikz,
+			export_dot,
+			export_graphml,
+			undo,
+			redo,
+			copy,
+			cut,
+			paste,
 			mode_selector: Component::make(mode_selector),
 			orientation_selector: Component::make(orientation_selector),
 			size_selector: Component::make(size_selector),
@@ -194,6 +321,10 @@ impl Bar {
 			refresh,
 			settings,
 			conflicts,
+			solver,
+			layout,
+			graphs,
+			document,
 		}
 	}
 }
@@ -177,6 +177,32 @@ impl RenderTarget for CanvasTarget<'_> {
 		self.canvas.stroke();
 	}
 
+	fn draw_connection_highlight(
+		&mut self,
+		center: Vector,
+		radius: Number,
+		rotation: Number,
+		angle: Number,
+		color: &str,
+	) {
+		self.canvas.begin_path();
+		self.canvas.ellipse_with_anticlockwise(
+			center.x,
+			center.y,
+			radius,
+			radius,
+			rotation,
+			0.0,
+			angle,
+			angle.is_sign_negative(),
+		);
+		self.canvas.set_global_alpha(0.5);
+		self.canvas.set_line_width(11.0);
+		self.canvas.set_stroke_style_str(color);
+		self.canvas.stroke();
+		self.canvas.set_global_alpha(1.0);
+	}
+
 	fn draw_label(&mut self, anchor: Vector, text: &str) {
 		if !self.resources.show_labels {
 			return;
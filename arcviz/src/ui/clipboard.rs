@@ -0,0 +1,52 @@
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::{Function, Reflect};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::window;
+
+/// `navigator.clipboard` (only available in secure contexts, but always in Electron).
+///
+/// Accessed dynamically, since the bindings of web_sys for it are unstable.
+fn clipboard() -> Option<JsValue> {
+	let navigator = Reflect::get(&window()?, &JsValue::from_str("navigator")).ok()?;
+	let clipboard = Reflect::get(&navigator, &JsValue::from_str("clipboard")).ok()?;
+	(!clipboard.is_undefined() && !clipboard.is_null()).then_some(clipboard)
+}
+fn method(target: &JsValue, name: &str) -> Option<Function> {
+	Reflect::get(target, &JsValue::from_str(name)).ok()?.dyn_into().ok()
+}
+
+/// Replaces the text in the system clipboard (failures are ignored).
+pub fn write_clipboard_text(text: &str) {
+	let Some(clipboard) = clipboard() else { return };
+	let Some(write) = method(&clipboard, "writeText") else { return };
+	let _ = write.call1(&clipboard, &JsValue::from_str(text));
+}
+
+/// Reads the text in the system clipboard.
+///
+/// The callback gets None if that is not possible (no clipboard access, permission denied or no text).
+pub fn read_clipboard_text(callback: impl FnOnce(Option<String>) + 'static) {
+	let Some((clipboard, read)) = clipboard().and_then(|c| Some((c.clone(), method(&c, "readText")?))) else {
+		return callback(None);
+	};
+	let Some(promise) = read.call0(&clipboard).ok() else { return callback(None) };
+	let Some(then) = method(&promise, "then") else { return callback(None) };
+
+	// exactly one of the handlers is called
+	let callback = Rc::new(RefCell::new(Some(callback)));
+	let resolve = Closure::once_into_js({
+		let callback = callback.clone();
+		move |text: JsValue| {
+			if let Some(callback) = callback.borrow_mut().take() {
+				callback(text.as_string());
+			}
+		}
+	});
+	let reject = Closure::once_into_js(move |_: JsValue| {
+		if let Some(callback) = callback.borrow_mut().take() {
+			callback(None);
+		}
+	});
+	let _ = then.call2(&promise, &resolve, &reject);
+}
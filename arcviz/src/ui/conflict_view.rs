@@ -4,7 +4,7 @@ use webbit::{
 };
 use web_sys::HtmlDivElement;
 
-use crate::ui::AppContext;
+use crate::{model::ConflictStatus, ui::AppContext};
 
 pub struct ConflictView {
 	root: HtmlDivElement,
@@ -30,8 +30,20 @@ impl ConflictView {
 
 		let Some(context) = self.context.access() else { return };
 
-		for conflict in context.conflicts_representation() {
-			on(&self.root, styled(label(conflict.0.as_str()), "conflict-element"));
+		let color_conflicts = context.resources.color_conflicts;
+		for (text, _, status) in context.conflicts_representation() {
+			let css = match (color_conflicts, status) {
+				(false, _) => "conflict-element",
+				(true, ConflictStatus::Satisfied) => "conflict-element-satisfied",
+				(true, ConflictStatus::Tolerated) => "conflict-element-tolerated",
+				(true, ConflictStatus::Violated) => "conflict-element-violated",
+			};
+			on(&self.root, styled(label(text.as_str()), css));
+		}
+		if context.resources.show_missing_intersections {
+			for text in context.missing_intersections_representation() {
+				on(&self.root, styled(label(text.as_str()), "conflict-element-missing"));
+			}
 		}
 	}
 }
@@ -0,0 +1,97 @@
+use std::{cell::Cell, rc::Rc};
+
+use web_sys::{window, HtmlDivElement, HtmlElement, PointerEvent};
+use webbit::{
+	components::Button,
+	elements::{div, label, on, styled},
+	events::SharedEventListener,
+	Component,
+};
+
+use crate::{
+	common::Vector,
+	model::{Edge, IntersectionConstraint},
+	ui::AppContext,
+};
+
+/// A small popup that sets the intersection constraint of an edge pair.
+pub struct ConstraintPicker {
+	root: HtmlDivElement,
+	title: HtmlElement,
+	context: AppContext,
+	pair: Rc<Cell<Option<(Edge, Edge)>>>,
+	buttons: Vec<Component<Button>>,
+	down_listener: SharedEventListener<PointerEvent>,
+	up_listener: SharedEventListener<PointerEvent>,
+}
+
+impl ConstraintPicker {
+	pub fn new(parent: HtmlElement, context: AppContext) -> Rc<Self> {
+		let root = on(&parent, styled(div(), "constraint-picker"));
+		let title: HtmlElement = on(&root, styled(label(""), "constraint-picker-title")).into();
+		let pair = Rc::new(Cell::new(None));
+
+		// keep the workspace from interpreting clicks on the picker as tool input
+		let down_listener = SharedEventListener::new(root.clone().into(), "pointerdown")
+			.with_handler(|event: PointerEvent| event.stop_propagation());
+		let up_listener = SharedEventListener::new(root.clone().into(), "pointerup")
+			.with_handler(|event: PointerEvent| event.stop_propagation());
+
+		let choices = [
+			("necessary", Some(IntersectionConstraint::Necessary)),
+			("irrelevant", Some(IntersectionConstraint::Irrelevant)),
+			("forbidden", Some(IntersectionConstraint::Forbidden)),
+			("cancel", None),
+		];
+		let buttons = choices
+			.into_iter()
+			.map(|(text, constraint)| {
+				let button = Component::make(Button::new_with_handler(Some(text), "constraint-picker-button", {
+					let context = context.clone_for("ConstraintPicker.()pick");
+					let pair = pair.clone();
+					let root = root.clone();
+					move |_| {
+						let _ = root.style().set_property("display", "none");
+						let (Some((a, b)), Some(constraint)) = (pair.take(), constraint) else { return };
+						let Some(mut context) = context.access_mut() else { return };
+						if context.set_constraint(a, b, constraint).is_err() {
+							let message = format!("Can't constrain {}", context.edge_pair_name(a, b));
+							drop(context);
+							if let Some(window) = window() {
+								let _ = window.alert_with_message(&message);
+							}
+						}
+					}
+				}));
+				button.mount_in(&root);
+				button
+			})
+			.collect();
+
+		let context = context.clone_for("ConstraintPicker.open()");
+		let this = Rc::new(Self { root, title, context, pair, buttons, down_listener, up_listener });
+		this.close();
+		this
+	}
+
+	/// Opens the picker for the given edge pair.
+	///
+	/// Only pairs that [crate::ui::WorkspaceContext::can_constrain] should be offered.
+	///
+	/// - position: where to show the picker (relative to the parent)
+	/// - current: the constraint the pair currently has
+	pub fn open(&self, position: Vector, a: Edge, b: Edge, current: IntersectionConstraint) {
+		self.pair.set(Some((a, b)));
+		let Some(name) = self.context.access().map(|context| context.edge_pair_name(a, b)) else { return };
+		self.title.set_text_content(Some(&format!("{}: {}", name, current.name())));
+
+		let style = self.root.style();
+		let _ = style.set_property("left", &format!("{}px", position.x));
+		let _ = style.set_property("top", &format!("{}px", position.y));
+		let _ = style.set_property("display", "flex");
+	}
+	pub fn close(&self) {
+		self.pair.set(None);
+		let _ = self.root.style().set_property("display", "none");
+	}
+}
@@ -0,0 +1,68 @@
+use js_sys::{Function, Uint8Array};
+use wasm_bindgen::{closure::Closure, JsValue};
+
+use crate::io::LoadError;
+
+/// A file of the host (a `FileSystemFileHandle` in the browser, a path in Electron).
+#[derive(Clone)]
+pub struct DocumentFile {
+	/// Only passed back to the host to write the file again.
+	pub handle: JsValue,
+	/// The file name, shown as title and used to choose the format (see [crate::io::FileFormat::from_path]).
+	pub name: String,
+}
+
+/// Opens and saves the document of the [super::Workspace] through the file io of the host.
+///
+/// Unlike [webbit::io::FileIOHandler] this keeps the file that was opened or picked,
+/// so the document can be written back without asking for a file again.
+///
+/// The host provides:
+/// - `open(callback(error, data, handle, name))`
+/// - `pick(suggested_name, callback(handle, name))` (handle is null if canceled)
+/// - `write(handle, data, callback(success))`
+pub struct DocumentIO {
+	open: Function,
+	pick: Function,
+	write: Function,
+}
+impl DocumentIO {
+	pub fn new(open: Function, pick: Function, write: Function) -> Self {
+		Self { open, pick, write }
+	}
+
+	/// Asks for a file and reads it, the callback gets None if nothing was chosen.
+	pub fn open(&self, callback: impl FnOnce(Result<Option<(DocumentFile, Vec<u8>)>, LoadError>) + 'static) {
+		let callback =
+			Closure::once_into_js(move |error: JsValue, data: JsValue, handle: JsValue, name: JsValue| {
+				if !error.is_null() && !error.is_undefined() {
+					let message = error.as_string().unwrap_or_else(|| format!("{:?}", error));
+					return callback(Err(LoadError::new(format!("can't read the file ({})", message))));
+				}
+				if data.is_null() || data.is_undefined() {
+					return callback(Ok(None));
+				}
+				let name = name.as_string().unwrap_or_else(|| "untitled".to_owned());
+				callback(Ok(Some((DocumentFile { handle, name }, Uint8Array::new(&data).to_vec()))))
+			});
+		let _ = self.open.call1(&JsValue::NULL, &callback);
+	}
+
+	/// Asks for a file to save to (without writing it), the callback gets None if canceled.
+	pub fn pick(&self, suggested_name: &str, callback: impl FnOnce(Option<DocumentFile>) + 'static) {
+		let callback = Closure::once_into_js(move |handle: JsValue, name: JsValue| {
+			if handle.is_null() || handle.is_undefined() {
+				return callback(None);
+			}
+			let name = name.as_string().unwrap_or_else(|| "untitled".to_owned());
+			callback(Some(DocumentFile { handle, name }))
+		});
+		let _ = self.pick.call2(&JsValue::NULL, &JsValue::from_str(suggested_name), &callback);
+	}
+
+	/// Replaces the content of the file, the callback gets whether that worked.
+	pub fn write(&self, file: &DocumentFile, data: &[u8], callback: impl FnOnce(bool) + 'static) {
+		let callback = Closure::once_into_js(move |success: JsValue| callback(success.is_truthy()));
+		let _ = self.write.call3(&JsValue::NULL, &file.handle, &Uint8Array::from(data), &callback);
+	}
+}
@@ -0,0 +1,89 @@
+use std::rc::Rc;
+
+use webbit::{
+	components::Button,
+	elements::{div, label, on, styled},
+	Component, ComponentContent,
+};
+use web_sys::{HtmlDivElement, HtmlElement};
+
+use crate::{io::graph::InitialLayout, ui::Workspace};
+
+/// Steps through the graphs of an imported graph file (e.g. a family of graphs generated by geng).
+pub struct GraphView {
+	root: HtmlDivElement,
+
+	pub circle: Component<Button>,
+	pub grid: Component<Button>,
+	pub random: Component<Button>,
+	pub previous: Component<Button>,
+	pub next: Component<Button>,
+}
+impl GraphView {
+	pub fn new(workspace: Rc<Workspace>) -> Self {
+		let root = styled(div(), "settings-view");
+		let status: HtmlElement = styled(label(""), "conflict-element").into();
+		Self::report(&workspace, &status);
+
+		let layout_button = |text: &'static str, layout: fn() -> InitialLayout| {
+			let button = Component::make(Button::new_with_handler(Some(text), "bar-button", {
+				let workspace = workspace.clone();
+				let status = status.clone();
+				move |_| {
+					workspace.selection_frame.close();
+					if let Some(mut context) = workspace.context.access_mut() {
+						context.graph_layout = layout();
+						let (index, _) = context.graph_position();
+						let _ = context.show_graph(index);
+					}
+					workspace.refresh_tabs();
+					Self::report(&workspace, &status);
+				}
+			}));
+			button.mount_in(&root);
+			button
+		};
+		let circle = layout_button("circle", || InitialLayout::Circle);
+		let grid = layout_button("grid", || InitialLayout::Grid);
+		let random = layout_button("random", || {
+			InitialLayout::Random((js_sys::Math::random() * u64::MAX as f64) as u64)
+		});
+
+		let step_button = |text: &'static str, step: isize| {
+			let button = Component::make(Button::new_with_handler(Some(text), "bar-button", {
+				let workspace = workspace.clone();
+				let status = status.clone();
+				move |_| {
+					workspace.selection_frame.close();
+					if let Some(mut context) = workspace.context.access_mut() {
+						let (index, count) = context.graph_position();
+						let _ = context.show_graph(index.saturating_add_signed(step).min(count.saturating_sub(1)));
+					}
+					workspace.refresh_tabs();
+					Self::report(&workspace, &status);
+				}
+			}));
+			button.mount_in(&root);
+			button
+		};
+		let previous = step_button("previous", -1);
+		let next = step_button("next", 1);
+		on(&root, status);
+
+		Self { root, circle, grid, random, previous, next }
+	}
+
+	fn report(workspace: &Workspace, status: &HtmlElement) {
+		let Some(context) = workspace.context.access() else { return };
+		let report = match context.graph_position() {
+			(_, 0) => "open a graph6, edge list, DOT or GraphML file to import graphs".to_owned(),
+			(index, count) => format!("graph {} of {}", index + 1, count),
+		};
+		status.set_text_content(Some(&report));
+	}
+}
+impl ComponentContent for GraphView {
+	fn element(&self) -> &web_sys::Element {
+		&self.root
+	}
+}
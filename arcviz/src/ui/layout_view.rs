@@ -0,0 +1,90 @@
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::{closure::Closure, JsCast};
+use webbit::{
+	components::Button,
+	elements::{div, label, on, styled},
+	Component, ComponentContent,
+};
+use web_sys::{HtmlDivElement, HtmlElement};
+
+use crate::{solver::LayoutOptions, ui::Workspace};
+
+/// How many optimization steps are performed per animation frame.
+const STEPS_PER_FRAME: usize = 20;
+
+/// The callback of the running animation loop (None if the optimization isn't advanced).
+type Frame = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
+pub struct LayoutView {
+	root: HtmlDivElement,
+
+	pub optimize: Component<Button>,
+	pub stop: Component<Button>,
+}
+impl LayoutView {
+	pub fn new(workspace: Rc<Workspace>) -> Self {
+		let root = styled(div(), "settings-view");
+		let status: HtmlElement = styled(label("pinned vertices are not moved"), "conflict-element").into();
+		let frame: Frame = Rc::new(RefCell::new(None));
+
+		let optimize = Component::make(Button::new_with_handler(Some("optimize layout"), "bar-button", {
+			let workspace = workspace.clone();
+			let status = status.clone();
+			move |_| {
+				workspace.selection_frame.close();
+				{
+					let Some(mut context) = workspace.context.access_mut() else { return };
+					let seed = (js_sys::Math::random() * u64::MAX as f64) as u64;
+					context.start_layout_optimization(LayoutOptions { seed, ..Default::default() });
+				}
+				workspace.refresh_tabs();
+				// a running loop just continues with the new optimization
+				if frame.borrow().is_none() {
+					Self::animate(workspace.clone(), status.clone(), frame.clone());
+				}
+			}
+		}));
+		optimize.mount_in(&root);
+		let stop = Component::make(Button::new_with_handler(Some("stop"), "bar-button", {
+			let workspace = workspace.clone();
+			move |_| {
+				let Some(mut context) = workspace.context.access_mut() else { return };
+				context.stop_layout_optimization();
+			}
+		}));
+		stop.mount_in(&root);
+		on(&root, status);
+
+		Self { root, optimize, stop }
+	}
+
+	/// Advances the running optimization once per animation frame until it is finished (or stopped).
+	fn animate(workspace: Rc<Workspace>, status: HtmlElement, frame: Frame) {
+		let handle = frame.clone();
+		*handle.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+			let report = match workspace.context.access_mut() {
+				Some(mut context) => context.step_layout_optimization(STEPS_PER_FRAME),
+				None => None,
+			};
+			let Some(report) = report else {
+				let _ = frame.borrow_mut().take(); // nothing running anymore: drop the closure
+				return;
+			};
+			status.set_text_content(Some(&report));
+			Self::request_frame(&frame);
+		}) as Box<dyn FnMut()>));
+		Self::request_frame(&handle);
+	}
+	fn request_frame(frame: &Frame) {
+		let Some(window) = web_sys::window() else { return };
+		let frame = frame.borrow();
+		let Some(callback) = frame.as_ref() else { return };
+		let _ = window.request_animation_frame(callback.as_ref().unchecked_ref());
+	}
+}
+impl ComponentContent for LayoutView {
+	fn element(&self) -> &web_sys::Element {
+		&self.root
+	}
+}
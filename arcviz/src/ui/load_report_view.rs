@@ -0,0 +1,37 @@
+use web_sys::HtmlDivElement;
+use webbit::{
+	elements::{div, label, on, styled},
+	ComponentContent,
+};
+
+use crate::io::LoadError;
+
+/// Lists what went wrong (or was only partially understood) when opening a file.
+pub struct LoadReportView {
+	root: HtmlDivElement,
+}
+impl LoadReportView {
+	pub fn new(problems: &[LoadError]) -> Self {
+		let root = styled(div(), "conflict-view");
+		if problems.len() > 1 {
+			on(&root, label(&format!("{} problems", problems.len())));
+		}
+		for problem in problems {
+			let element = on(&root, styled(div(), "load-report-element"));
+			on(&element, label(&problem.message));
+			let location = match (problem.position, &problem.section) {
+				(Some((line, column)), Some(section)) => format!("line {}, column {} ({})", line, column, section),
+				(Some((line, column)), None) => format!("line {}, column {}", line, column),
+				(None, Some(section)) => format!("in {}", section),
+				(None, None) => continue,
+			};
+			on(&element, styled(label(&location), "load-report-location"));
+		}
+		Self { root }
+	}
+}
+impl ComponentContent for LoadReportView {
+	fn element(&self) -> &web_sys::Element {
+		&self.root
+	}
+}
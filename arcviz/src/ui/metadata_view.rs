@@ -0,0 +1,56 @@
+use std::rc::Rc;
+
+use webbit::{
+	elements::{div, label, on, styled, text},
+	events::SharedEventListener,
+	ComponentContent,
+};
+use web_sys::{Event, HtmlDivElement, HtmlInputElement};
+
+use crate::{io::dto::Metadata, ui::Workspace};
+
+/// Edits the metadata that is saved with the drawing (in the JSON format).
+pub struct MetadataView {
+	root: HtmlDivElement,
+
+	title_listener: SharedEventListener<Event>,
+	author_listener: SharedEventListener<Event>,
+	notes_listener: SharedEventListener<Event>,
+}
+impl MetadataView {
+	pub fn new(workspace: Rc<Workspace>) -> Self {
+		let root = styled(div(), "settings-view");
+		let metadata = workspace.context.access().map(|c| c.metadata.clone()).unwrap_or_default();
+
+		let field = |name: &str, value: &str, update: fn(&mut Metadata, String)| {
+			on(&root, styled(label(name), "conflict-element"));
+			let input: HtmlInputElement = on(&root, styled(text(""), "bar-text"));
+			input.set_value(value);
+			SharedEventListener::new(input.clone().into(), "change").with_handler({
+				let workspace = workspace.clone();
+				move |_| {
+					if let Some(mut context) = workspace.context.access_mut() {
+						update(&mut context.metadata, input.value());
+						context.mark_dirty();
+					}
+					workspace.refresh_tabs();
+				}
+			})
+		};
+		let title_listener = field("title", &metadata.title, |m, v| m.title = v);
+		let author_listener = field("author", &metadata.author, |m, v| m.author = v);
+		let notes_listener = field("notes", &metadata.notes, |m, v| m.notes = v);
+
+		let created = metadata.created.as_deref().unwrap_or("not saved yet");
+		on(&root, styled(label(&format!("created: {}", created)), "conflict-element"));
+		let modified = metadata.modified.as_deref().unwrap_or("not saved yet");
+		on(&root, styled(label(&format!("modified: {}", modified)), "conflict-element"));
+
+		Self { root, title_listener, author_listener, notes_listener }
+	}
+}
+impl ComponentContent for MetadataView {
+	fn element(&self) -> &web_sys::Element {
+		&self.root
+	}
+}
@@ -13,6 +13,12 @@ pub use workspace::*;
 mod workspace_context;
 pub use workspace_context::*;
 
+mod document_io;
+pub use document_io::*;
+
+mod clipboard;
+pub use clipboard::*;
+
 mod canvas_stage;
 pub use canvas_stage::*;
 
@@ -36,3 +42,21 @@ pub use settings_view::*;
 
 mod conflict_view;
 pub use conflict_view::*;
+
+mod constraint_picker;
+pub use constraint_picker::*;
+
+mod solver_view;
+pub use solver_view::*;
+
+mod layout_view;
+pub use layout_view::*;
+
+mod graph_view;
+pub use graph_view::*;
+
+mod load_report_view;
+pub use load_report_view::*;
+
+mod metadata_view;
+pub use metadata_view::*;
@@ -184,6 +184,17 @@ impl SelectionFrame {
 				context.rerender();
 			}
 		};
+		let pin = {
+			let context = self.context.clone_for("menu.()pin");
+			let selection_frame = self.clone();
+			move |_| {
+				{
+					let Some(mut context) = context.access_mut() else { return };
+					context.toggle_pin_selection();
+				}
+				selection_frame.rerender();
+			}
+		};
 		let increase = {
 			let context = self.context.clone_for("menu.()increase");
 			let selection_frame = self.clone();
@@ -220,6 +231,7 @@ impl SelectionFrame {
 				("Evert", Box::new(evert)),
 				("Invert", Box::new(invert)),
 				("Tag", Box::new(tag)),
+				("Pin", Box::new(pin)),
 				("Duplicate", Box::new(duplicate)),
 				("Delete", Box::new(delete)),
 			],
@@ -2,22 +2,30 @@ use std::rc::Rc;
 
 use webbit::{
 	components::Checkbox,
-	elements::{div, styled},
+	elements::{div, label, on, styled, text},
 	errors::TracksEnvironment,
+	events::SharedEventListener,
 	Component, ComponentContent,
 };
-use web_sys::HtmlDivElement;
+use web_sys::{Event, HtmlDivElement, HtmlInputElement};
 
-use crate::{model::Settings, ui::Workspace};
+use crate::{
+	model::{Settings, DEFAULT_EPSILON},
+	ui::Workspace,
+};
 
 pub struct SettingsView {
 	root: HtmlDivElement,
 
 	pub show_grid: Component<Checkbox>,
 	pub show_conflicts: Component<Checkbox>,
+	pub color_conflicts: Component<Checkbox>,
+	pub show_missing_intersections: Component<Checkbox>,
 	pub show_labels: Component<Checkbox>,
 	pub snap_to_grid: Component<Checkbox>,
 	pub integrate_on_move: Component<Checkbox>,
+	/// Sets the tolerance of the active drawing (see [crate::model::Tolerance]).
+	epsilon_listener: SharedEventListener<Event>,
 }
 impl SettingsView {
 	pub fn new(workspace: Rc<Workspace>) -> Self {
@@ -48,6 +56,31 @@ impl SettingsView {
 			}
 		});
 		show_conflicts.mount_in(&root);
+		let color_conflicts =
+			Checkbox::new(Some("color conflicts by constraint"), settings.color_conflicts, "settings-checkbox");
+		color_conflicts.on_change.set_handler({
+			let context = workspace.context.clone_for("color_conflicts./on_change");
+			move |v| {
+				let Some(mut context) = context.access_mut() else { return };
+				context.resources.color_conflicts = v;
+				context.rerender();
+			}
+		});
+		color_conflicts.mount_in(&root);
+		let show_missing_intersections = Checkbox::new(
+			Some("highlight missing required intersections"),
+			settings.show_missing_intersections,
+			"settings-checkbox",
+		);
+		show_missing_intersections.on_change.set_handler({
+			let context = workspace.context.clone_for("show_missing_intersections./on_change");
+			move |v| {
+				let Some(mut context) = context.access_mut() else { return };
+				context.resources.show_missing_intersections = v;
+				context.rerender();
+			}
+		});
+		show_missing_intersections.mount_in(&root);
 		let show_labels = Checkbox::new(Some("show labels"), settings.show_labels, "settings-checkbox");
 		show_labels.on_change.set_handler({
 			let context = workspace.context.clone_for("show_labels./on_change");
@@ -69,7 +102,7 @@ impl SettingsView {
 		});
 		snap_to_grid.mount_in(&root);
 		let integrate_on_move = Checkbox::new(
-			Some("continuously render multiselection drag (performance intensive!)"),
+			Some("continuously render multiselection drag"),
 			settings.integrate_on_move,
 			"settings-checkbox",
 		);
@@ -94,13 +127,33 @@ impl SettingsView {
 		});
 		auto_open_context_menu.mount_in(&root);
 
+		on(&root, styled(label("intersection tolerance (epsilon)"), "conflict-element"));
+		let epsilon: HtmlInputElement = on(&root, styled(text(""), "bar-text"));
+		epsilon.set_value(&workspace.context.access().map_or(DEFAULT_EPSILON, |c| c.epsilon()).to_string());
+		let epsilon_listener = SharedEventListener::new(epsilon.clone().into(), "change").with_handler({
+			let workspace = workspace.clone();
+			move |_| {
+				if let Some(mut context) = workspace.context.access_mut() {
+					// invalid values are replaced by the current one
+					let value = epsilon.value().trim().parse().map_err(|_| ());
+					if value.and_then(|epsilon| context.set_epsilon(epsilon)).is_err() {
+						epsilon.set_value(&context.epsilon().to_string());
+					}
+				}
+				workspace.refresh_tabs();
+			}
+		});
+
 		Self {
 			root,
 			show_grid: Component::make_sharable(show_grid),
 			show_conflicts: Component::make_sharable(show_conflicts),
+			color_conflicts: Component::make_sharable(color_conflicts),
+			show_missing_intersections: Component::make_sharable(show_missing_intersections),
 			show_labels: Component::make_sharable(show_labels),
 			snap_to_grid: Component::make_sharable(snap_to_grid),
 			integrate_on_move: Component::make_sharable(integrate_on_move),
+			epsilon_listener,
 		}
 	}
 }
@@ -0,0 +1,129 @@
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::{closure::Closure, JsCast};
+use webbit::{
+	components::{Button, Checkbox},
+	elements::{div, label, on, styled},
+	Component, ComponentContent,
+};
+use web_sys::HtmlDivElement;
+
+use crate::{solver::AssignmentOptions, ui::Workspace};
+
+/// How many search steps are performed per animation frame.
+const STEPS_PER_FRAME: usize = 500;
+
+/// The callback of the running animation loop (None if the search isn't advanced).
+type Frame = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
+pub struct SolverView {
+	root: HtmlDivElement,
+
+	pub vary_orientations: Component<Checkbox>,
+	pub vary_classes: Component<Checkbox>,
+	pub search: Component<Button>,
+	pub cancel: Component<Button>,
+}
+impl SolverView {
+	pub fn new(workspace: Rc<Workspace>) -> Self {
+		let root = styled(div(), "settings-view");
+
+		let options = Rc::new(RefCell::new(AssignmentOptions::default()));
+		let defaults = AssignmentOptions::default();
+
+		let vary_orientations =
+			Checkbox::new(Some("vary orientations"), defaults.vary_orientations, "settings-checkbox");
+		vary_orientations.on_change.set_handler({
+			let options = options.clone();
+			move |v| {
+				options.borrow_mut().vary_orientations = v;
+			}
+		});
+		vary_orientations.mount_in(&root);
+		let vary_classes = Checkbox::new(Some("vary classes"), defaults.vary_classes, "settings-checkbox");
+		vary_classes.on_change.set_handler({
+			let options = options.clone();
+			move |v| {
+				options.borrow_mut().vary_classes = v;
+			}
+		});
+		vary_classes.mount_in(&root);
+
+		let report = styled(div(), "conflict-view");
+		let frame: Frame = Rc::new(RefCell::new(None));
+		let search = Component::make(Button::new_with_handler(Some("search assignment"), "bar-button", {
+			let workspace = workspace.clone();
+			let report = report.clone();
+			let frame = frame.clone();
+			move |_| {
+				workspace.selection_frame.close();
+				{
+					let Some(mut context) = workspace.context.access_mut() else { return };
+					context.start_assignment(options.borrow().clone());
+				}
+				// a running loop just continues with the new search
+				if frame.borrow().is_none() {
+					Self::animate(workspace.clone(), report.clone(), frame.clone());
+				}
+			}
+		}));
+		search.mount_in(&root);
+		let cancel = Component::make(Button::new_with_handler(Some("cancel"), "bar-button", {
+			let workspace = workspace.clone();
+			let report = report.clone();
+			move |_| {
+				let Some(mut context) = workspace.context.access_mut() else { return };
+				context.stop_assignment();
+				Self::show(&report, vec!["search canceled".to_owned()]);
+			}
+		}));
+		cancel.mount_in(&root);
+		on(&root, report);
+
+		Self {
+			root,
+			vary_orientations: Component::make_sharable(vary_orientations),
+			vary_classes: Component::make_sharable(vary_classes),
+			search,
+			cancel,
+		}
+	}
+
+	/// Advances the running search once per animation frame until it is finished (or canceled).
+	fn animate(workspace: Rc<Workspace>, report: HtmlDivElement, frame: Frame) {
+		let handle = frame.clone();
+		*handle.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+			let lines = match workspace.context.access_mut() {
+				Some(mut context) => context.step_assignment(STEPS_PER_FRAME),
+				None => None,
+			};
+			let Some(lines) = lines else {
+				workspace.refresh_tabs(); // the result may have been applied
+				let _ = frame.borrow_mut().take(); // nothing running anymore: drop the closure
+				return;
+			};
+			Self::show(&report, lines);
+			Self::request_frame(&frame);
+		}) as Box<dyn FnMut()>));
+		Self::request_frame(&handle);
+	}
+	fn request_frame(frame: &Frame) {
+		let Some(window) = web_sys::window() else { return };
+		let frame = frame.borrow();
+		let Some(callback) = frame.as_ref() else { return };
+		let _ = window.request_animation_frame(callback.as_ref().unchecked_ref());
+	}
+	fn show(report: &HtmlDivElement, lines: Vec<String>) {
+		while let Some(child) = report.last_child() {
+			let _ = report.remove_child(&child);
+		}
+		for line in lines {
+			on(report, styled(label(line.as_str()), "conflict-element"));
+		}
+	}
+}
+impl ComponentContent for SolverView {
+	fn element(&self) -> &web_sys::Element {
+		&self.root
+	}
+}
@@ -5,7 +5,7 @@ use web_sys::{HtmlElement, PointerEvent};
 
 use crate::{
 	common::{Bounds, Vector},
-	ui::{Mode, SelectionFrame},
+	ui::{ConstraintPicker, Mode, Selection, SelectionFrame},
 };
 
 use super::AppContext;
@@ -48,18 +48,25 @@ pub struct ToolDispatcher {
 	element: HtmlElement,
 	pointer: Option<(i32, Vector, Control)>,
 	selection_frame: Rc<SelectionFrame>,
+	constraint_picker: Rc<ConstraintPicker>,
 	primary_button: Button,
 	secondary_button: Button,
 	start_position: Vector,
 	show_selection_frame: bool,
 }
 impl ToolDispatcher {
-	pub fn new(context: AppContext, selection_frame: Rc<SelectionFrame>, element: HtmlElement) -> Self {
+	pub fn new(
+		context: AppContext,
+		selection_frame: Rc<SelectionFrame>,
+		constraint_picker: Rc<ConstraintPicker>,
+		element: HtmlElement,
+	) -> Self {
 		Self {
 			context: context,
 			element,
 			pointer: None,
 			selection_frame,
+			constraint_picker,
 			primary_button: Button::Right,
 			secondary_button: Button::Left,
 			start_position: Vector::zero(),
@@ -71,15 +78,33 @@ impl ToolDispatcher {
 		if self.pointer.is_some() {
 			return Ok(false);
 		}; //don't switch pointers
+		let buttons = self.control(Button::from(event.buttons()));
+		if matches!(buttons, Control::Tertiary | Control::Invalid) {
+			return Ok(false);
+		}
 		event.target().unwrap().dyn_into::<HtmlElement>().unwrap().set_pointer_capture(event.pointer_id());
 		self.reset();
 		let position = self.relative_position(&event);
 		self.start_position = position;
-		let buttons = self.control(Button::from(event.buttons()));
 		self.pointer = Some((event.pointer_id(), position.clone(), buttons));
 		let mut context = self.context.access_mut_or(())?;
 
 		self.selection_frame.close();
+		self.constraint_picker.close();
+		context.seal_history();
+
+		// clicking a conflict marker edits the constraint of its edge pair
+		if let (Control::Primary, true) = (buttons, context.resources.show_conflicts) {
+			let conflict = context.get_conflict_at(position).filter(|(a, b)| context.can_constrain(*a, *b));
+			if let Some((a, b)) = conflict {
+				context.selection = None;
+				let constraint = context.constraint(a, b);
+				drop(context);
+				self.constraint_picker.open(position, a, b, constraint);
+				return Ok(true);
+			}
+		}
+
 		context.select_at(position);
 
 		match (buttons, context.selection.is_some(), &context.mode) {
@@ -88,11 +113,6 @@ impl ToolDispatcher {
 				context.adjust_vertex(&id);
 				context.rerender();
 			},
-			(Control::Primary, false, Mode::Edit) => {
-				let id = context.add_vertex_at(position);
-				context.adjust_vertex(&id);
-				context.rerender();
-			},
 			(Control::Primary, _, Mode::Select) => {
 				self.show_selection_frame = true;
 			},
@@ -127,7 +147,10 @@ impl ToolDispatcher {
 			(_, Control::Secondary) => {
 				context.draw_selection_aid(self.start_position, position);
 			},
-			(Mode::Edge | Mode::Edit, Control::Primary) => context.draw_connection_aid(position),
+			(Mode::Edge | Mode::Edit, Control::Primary) => {
+				context.draw_connection_aid(position);
+				context.draw_constraint_aid(self.start_position, position);
+			},
 			_ => (),
 		}
 
@@ -142,8 +165,18 @@ impl ToolDispatcher {
 		let buttons = state.2;
 		let mut context = self.context.access_mut_or(())?;
 		let position = self.relative_position(&event);
+		let mut constraint_target = None;
 
 		match (buttons, context.selection.is_some(), &context.mode) {
+			(Control::Primary, true, Mode::Edge | Mode::Edit)
+				if matches!(context.selection, Some(Selection::Edge(_, _))) =>
+			{
+				// dragging from one edge onto another edits the constraint of that edge pair
+				constraint_target = context.get_constraint_target_at(position);
+				if constraint_target.is_some() {
+					self.show_selection_frame = false;
+				}
+			},
 			(Control::Primary, true, Mode::Edge | Mode::Edit) => {
 				context.connect_at(position);
 				self.show_selection_frame = false;
@@ -160,8 +193,18 @@ impl ToolDispatcher {
 			_ => (),
 		}
 
+		context.seal_history(); // one drag -> one undo step
 		context.reset_aids();
 
+		if let Some((a, b)) = constraint_target {
+			let constraint = context.constraint(a, b);
+			context.selection = None;
+			drop(context);
+			self.constraint_picker.open(position, a, b, constraint);
+			self.pointer = None;
+			return Ok(true);
+		}
+
 		if context.selection.is_some() && self.show_selection_frame {
 			let auto_open_context_menu = context.resources.auto_open_context_menu;
 			drop(context);
@@ -1,13 +1,18 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+	cell::{Cell, RefCell},
+	rc::Rc,
+};
 
 use anyhow::anyhow;
 use ncollide2d::na::{convert, Affine2, Scale2, Translation2};
 use result_or_err::ResultOrErr;
-use wasm_bindgen::JsValue;
-use web_sys::{console, HtmlDivElement, PointerEvent, WheelEvent};
+use wasm_bindgen::JsCast;
+use web_sys::{
+	window, Element, Event, HtmlDivElement, HtmlInputElement, KeyboardEvent, PointerEvent, WheelEvent,
+};
 use webbit::{
-	components::ResizeCanvas,
-	elements::{div, styled},
+	components::{ResizeCanvas, SideBar},
+	elements::{div, label, on, styled},
 	errors::{IgnoreErr, TracksEnvironment},
 	events::{BubbleStopper, SharedEventListener},
 	io::FileIOHandler,
@@ -16,12 +21,16 @@ use webbit::{
 
 use crate::{
 	common::{Number, Vector},
+	io::{graph::GraphFormat, FileFormat, LoadError},
 	model::{ConnectionOrientation, Settings, SizeId},
 	render::{resources::ResourceProvider, RenderTarget, Stage},
 	ui::Mode,
 };
 
-use super::{CanvasStage, HandDispatcher, SelectionFrame, ToolDispatcher, WorkspaceContext};
+use super::{
+	read_clipboard_text, write_clipboard_text, CanvasStage, ConstraintPicker, DocumentFile, DocumentIO,
+	HandDispatcher, LoadReportView, SelectionFrame, ToolDispatcher, WorkspaceContext,
+};
 
 pub type AppContext = Context<WorkspaceContext<CanvasStage>, IgnoreErr>;
 
@@ -38,12 +47,22 @@ pub struct Workspace {
 	move_listener: SharedEventListener<PointerEvent>,
 	up_listener: SharedEventListener<PointerEvent>,
 	wheel_listener: SharedEventListener<WheelEvent>,
+	key_listener: SharedEventListener<KeyboardEvent>,
 	pub selection_frame: Component<SelectionFrame>,
+	/// Only used for exports (which ask for a file every time).
 	io: FileIOHandler,
+	document_io: DocumentIO,
+	/// The open drawings (shown between the bar and the canvas).
+	pub tabs: HtmlDivElement,
+	tab_listener: SharedEventListener<Event>,
+	/// The last position of the pointer over the workspace (where pasted drawings are placed).
+	cursor: Cell<Option<Vector>>,
+	/// Where problems with opened files are reported.
+	dock: Rc<SideBar>,
 }
 
 impl Workspace {
-	pub fn new(io: FileIOHandler) -> Rc<Self> {
+	pub fn new(io: FileIOHandler, document_io: DocumentIO, dock: Rc<SideBar>) -> Rc<Self> {
 		let element = styled(div(), "workspace");
 		BubbleStopper::configure(element.clone().into(), "contextmenu", |b| b.prevent_default());
 
@@ -59,6 +78,10 @@ impl Workspace {
 		let move_listener = SharedEventListener::new(element.clone().into(), "pointermove");
 		let up_listener = SharedEventListener::new(element.clone().into(), "pointerup");
 		let wheel_listener = SharedEventListener::new(element.clone().into(), "wheel");
+		let body = window().unwrap().document().unwrap().body().unwrap();
+		let key_listener = SharedEventListener::new(body.into(), "keydown");
+		let tabs = styled(div(), "tab-bar");
+		let tab_listener = SharedEventListener::new(tabs.clone().into(), "click");
 
 		let stage = CanvasStage::new(
 			canvas1.canvas.clone(),
@@ -73,8 +96,13 @@ impl Workspace {
 		let frame_component = Component::make_sharable(frame.clone());
 		let hand_dispatcher =
 			RefCell::new(HandDispatcher::new(context.clone_for("HandDispatcher"), element.clone().into()));
-		let tool_dispatcher =
-			RefCell::new(ToolDispatcher::new(context.clone_for("ToolDispatcher"), frame, element.clone().into()));
+		let picker = ConstraintPicker::new(element.clone().into(), context.clone_for("ConstraintPicker"));
+		let tool_dispatcher = RefCell::new(ToolDispatcher::new(
+			context.clone_for("ToolDispatcher"),
+			frame,
+			picker,
+			element.clone().into(),
+		));
 
 		let this = Rc::new(Self {
 			element,
@@ -89,8 +117,14 @@ impl Workspace {
 			move_listener,
 			up_listener,
 			wheel_listener,
+			key_listener,
 			selection_frame: frame_component,
 			io,
+			document_io,
+			tabs,
+			tab_listener,
+			cursor: Cell::new(None),
+			dock,
 		});
 
 		this.update();
@@ -149,6 +183,11 @@ impl Workspace {
 				event.prevent_default();
 				event.stop_propagation();
 
+				let frame_start = this.element.get_bounding_client_rect();
+				let position = Vector::new(event.client_x() as f64, event.client_y() as f64)
+					- Vector::new(frame_start.left() as f64, frame_start.top() as f64);
+				this.cursor.set(Some(position));
+
 				if let Ok(hand_dispatcher) = &mut this.hand_dispatcher.try_borrow_mut() {
 					hand_dispatcher.offer_move(event.clone());
 					this.selection_frame.update();
@@ -170,8 +209,12 @@ impl Workspace {
 				if let Ok(hand_dispatcher) = &mut this.hand_dispatcher.try_borrow_mut() {
 					hand_dispatcher.offer_up(event);
 				}
+				if let Some(mut context) = this.context.access_mut() {
+					context.seal_history(); // also ends drags of the selection frame
+				}
 
 				this.selection_frame.update();
+				this.refresh_tabs();
 			}
 		});
 		this.wheel_listener.set_handler({
@@ -208,40 +251,287 @@ impl Workspace {
 			}
 		});
 
-		this.io.on_load().set_handler({
+		this.tab_listener.set_handler({
 			let this = this.clone();
-			move |data| {
-				let Ok(Some(data)) = data else { return };
-				this.selection_frame.close();
-				let Some(mut context) = this.context.access_mut() else { return };
-				if context.load(&data).is_err() && context.load_text(&data).is_err() {
-					console::log_1(&JsValue::from("failed to deserialize"));
+			move |event: Event| {
+				let Some(target) = event.target().and_then(|t| t.dyn_into::<Element>().ok()) else { return };
+				let index = |attribute: &str| {
+					let element = target.closest(&format!("[{}]", attribute)).ok().flatten()?;
+					element.get_attribute(attribute)?.parse::<usize>().ok()
+				};
+				if let Some(index) = index("data-close") {
+					this.close_tab(index);
+				} else if let Some(index) = index("data-tab") {
+					this.switch_tab(index);
+				} else if target.closest("[data-new]").ok().flatten().is_some() {
+					this.new_tab();
 				}
-				context.rerender();
+			}
+		});
+
+		this.key_listener.set_handler({
+			let this = this.clone();
+			move |event: KeyboardEvent| {
+				if event.target().is_some_and(|t| t.dyn_ref::<HtmlInputElement>().is_some()) {
+					return; // keep the native undo of text inputs
+				}
+				if !(event.ctrl_key() || event.meta_key()) {
+					return;
+				}
+				match (event.key().to_lowercase().as_str(), event.shift_key()) {
+					("z", false) => this.undo(),
+					("z", true) | ("y", false) => this.redo(),
+					("s", false) => this.save(),
+					("s", true) => this.save_as(),
+					("c", false) => this.copy(),
+					("x", false) => this.cut(),
+					("v", false) => this.paste(),
+					_ => return,
+				}
+				event.prevent_default();
 			}
 		});
 
 		this
 	}
 
-	pub fn save(&self) {
-		if let Some(context) = self.context.access() {
-			let Ok(data) = context.serialize() else { return };
-			self.io.save(&data);
+	/// Shows the problems in the sidebar.
+	fn report(&self, title: &str, problems: &[LoadError]) {
+		self.dock.set_title(Some(title));
+		self.dock.open(LoadReportView::new(problems));
+	}
+
+	/// Shows the open drawings (with a `*` if they have unsaved changes) and the active one as window title.
+	pub fn refresh_tabs(&self) {
+		while let Some(child) = self.tabs.last_child() {
+			let _ = self.tabs.remove_child(&child);
 		}
+		let Some(context) = self.context.access() else { return };
+		for (index, (name, dirty)) in context.tabs().into_iter().enumerate() {
+			let title = format!("{}{}", name, if dirty { " *" } else { "" });
+			let active = index == context.active_tab();
+			let tab = on(&self.tabs, styled(div(), if active { "tab-active" } else { "tab" }));
+			let _ = tab.set_attribute("data-tab", &index.to_string());
+			on(&tab, label(&title));
+			let close = on(&tab, styled(label("x"), "tab-close"));
+			let _ = close.set_attribute("data-close", &index.to_string());
+			if active {
+				if let Some(document) = window().and_then(|window| window.document()) {
+					document.set_title(&format!("{} - arcviz", title));
+				}
+			}
+		}
+		let new = on(&self.tabs, styled(label("+"), "tab-new"));
+		let _ = new.set_attribute("data-new", "");
 	}
-	pub fn save_text(&self) {
-		if let Some(context) = self.context.access() {
-			self.io.save(&context.to_text());
+	pub fn new_tab(&self) {
+		self.selection_frame.close();
+		if let Some(mut context) = self.context.access_mut() {
+			context.new_tab();
+		}
+		self.refresh_tabs();
+	}
+	pub fn switch_tab(&self, index: usize) {
+		self.selection_frame.close();
+		if let Some(mut context) = self.context.access_mut() {
+			let _ = context.switch_tab(index);
 		}
+		self.refresh_tabs();
 	}
-	pub fn load(&self) {
-		self.io.load();
+	/// Closes the tab (after a confirmation if it has unsaved changes).
+	pub fn close_tab(&self, index: usize) {
+		let dirty = self.context.access().is_some_and(|context| context.tabs().get(index).is_some_and(|t| t.1));
+		if dirty {
+			let message = "The drawing has unsaved changes. Discard them and close it?";
+			let confirmed = window().and_then(|window| window.confirm_with_message(message).ok());
+			if confirmed != Some(true) {
+				return;
+			}
+		}
+		self.selection_frame.close();
+		if let Some(mut context) = self.context.access_mut() {
+			let _ = context.close_tab(index);
+		}
+		self.refresh_tabs();
+	}
+
+	/// Writes the drawing back to its file (asks for one if it has none or was imported from Ipe or a graph).
+	pub fn save(self: &Rc<Self>) {
+		let file = self.context.access().and_then(|context| context.file.clone());
+		match file {
+			Some(file) if matches!(FileFormat::from_path(&file.name), FileFormat::Json | FileFormat::Text) => {
+				self.write(file)
+			},
+			_ => self.save_as(),
+		}
+	}
+	/// Asks for a file and writes the drawing to it, which becomes the file of the drawing.
+	///
+	/// The format is chosen by the extension: JSON for `.json`, the text format otherwise.
+	pub fn save_as(self: &Rc<Self>) {
+		let file = self.context.access().and_then(|context| context.file.clone());
+		let name = match file {
+			Some(file) => match file.name.rsplit_once('.') {
+				Some((stem, _)) => format!("{}.json", stem),
+				None => format!("{}.json", file.name),
+			},
+			None => "drawing.json".to_owned(),
+		};
+		let this = self.clone();
+		self.document_io.pick(&name, move |file| {
+			if let Some(file) = file {
+				this.write(file);
+			}
+		});
+	}
+	fn write(self: &Rc<Self>, file: DocumentFile) {
+		// remember which drawing in which state is written, the user may go on editing or switch tabs meanwhile
+		let (data, token) = {
+			let Some(mut context) = self.context.access_mut() else { return };
+			let data = match FileFormat::from_path(&file.name) {
+				FileFormat::Json => {
+					let Ok(data) = context.serialize() else { return };
+					data
+				},
+				_ => context.to_text(),
+			};
+			(data, context.save_token())
+		};
+		let (target, this) = (file.clone(), self.clone());
+		self.document_io.write(&target, &data, move |success| {
+			if !success {
+				if let Some(window) = window() {
+					let _ = window.alert_with_message(&format!("Failed to save {}", file.name));
+				}
+				return;
+			}
+			if let Some(mut context) = this.context.access_mut() {
+				context.mark_saved_as(token, file);
+			}
+			this.refresh_tabs();
+		});
+	}
+	/// Asks for a file and opens it in a new tab (or the active one if it is an unchanged new drawing).
+	pub fn load(self: &Rc<Self>) {
+		let this = self.clone();
+		self.document_io.open(move |file| {
+			let (file, data) = match file {
+				Ok(Some(file)) => file,
+				Ok(None) => return,
+				Err(error) => return this.report("Failed to open", &[error]),
+			};
+			this.selection_frame.close();
+			let result = {
+				let Some(mut context) = this.context.access_mut() else { return };
+				let new_tab = !context.is_pristine();
+				if new_tab {
+					context.new_tab();
+				}
+				let result = context.open(&data);
+				match &result {
+					Ok(_) => context.file = Some(file),
+					Err(_) if new_tab => {
+						let active = context.active_tab();
+						let _ = context.close_tab(active);
+					},
+					Err(_) => (),
+				}
+				context.rerender();
+				result
+			};
+			this.refresh_tabs();
+			match result {
+				Ok(notes) if notes.is_empty() => (),
+				Ok(notes) => this.report("Opened with notes", &notes),
+				Err(error) => this.report("Failed to open", &[error]),
+			}
+		});
+	}
+
+	/// Copies the selection to the system clipboard (in the text format).
+	pub fn copy(&self) {
+		let text = self.context.access_mut().and_then(|mut context| context.copy_selection());
+		if let Some(text) = text {
+			write_clipboard_text(&text);
+		}
+	}
+	/// Moves the selection to the system clipboard (in the text format).
+	pub fn cut(&self) {
+		self.selection_frame.close();
+		let text = self.context.access_mut().and_then(|mut context| context.cut_selection());
+		if let Some(text) = text {
+			write_clipboard_text(&text);
+		}
+		self.refresh_tabs();
+	}
+	/// Pastes the drawing in the system clipboard at the pointer (or the one copied last if it can't be read).
+	pub fn paste(self: &Rc<Self>) {
+		let this = self.clone();
+		read_clipboard_text(move |text| {
+			let at = this.cursor.get().unwrap_or_else(|| {
+				let frame = this.element.get_bounding_client_rect();
+				Vector::new(frame.width() / 2.0, frame.height() / 2.0)
+			});
+			this.selection_frame.close();
+			let result = match this.context.access_mut() {
+				Some(mut context) => context.paste(text.as_deref(), at),
+				None => return,
+			};
+			if let Err(error) = result {
+				this.report("Failed to paste", &[error]);
+			}
+			this.selection_frame.update();
+			this.refresh_tabs();
+		});
+	}
+
+	pub fn undo(&self) {
+		self.selection_frame.close();
+		if let Some(mut context) = self.context.access_mut() {
+			context.undo();
+		}
+		self.refresh_tabs();
+	}
+	pub fn redo(&self) {
+		self.selection_frame.close();
+		if let Some(mut context) = self.context.access_mut() {
+			context.redo();
+		}
+		self.refresh_tabs();
 	}
 
 	pub fn export_ipe(&self) {
+		let Some((file, unknown_colors)) = self.context.access().map(|context| context.export_ipe()) else {
+			return;
+		};
+		self.io.save(&file);
+		self.report_unknown_colors(&unknown_colors);
+	}
+	pub fn export_svg(&self) {
+		if let Some(context) = self.context.access() {
+			self.io.save(&context.export_svg());
+		}
+	}
+	pub fn export_tikz(&self) {
+		let Some((file, unknown_colors)) = self.context.access().map(|context| context.export_tikz()) else {
+			return;
+		};
+		self.io.save(&file);
+		self.report_unknown_colors(&unknown_colors);
+	}
+	/// Reports the colors an export replaced by black (if any).
+	fn report_unknown_colors(&self, colors: &[String]) {
+		if !colors.is_empty() {
+			let problems = colors
+				.iter()
+				.map(|color| LoadError::new(format!("unknown color \"{}\" (exported as black)", color)))
+				.collect::<Vec<_>>();
+			self.report("Exported with unknown colors", &problems);
+		}
+	}
+	pub fn export_graph(&self, format: GraphFormat) {
 		if let Some(context) = self.context.access() {
-			self.io.save(&context.export_ipe());
+			self.io.save(&context.export_graph(format));
 		}
 	}
 
@@ -274,6 +564,8 @@ impl ComponentContent for Workspace {
 	fn update(&self) -> anyhow::Result<()> {
 		let mut context = self.context.access_mut_or(anyhow!("can't access data"))?;
 		context.rerender();
+		drop(context);
+		self.refresh_tabs();
 		Ok(())
 	}
 }
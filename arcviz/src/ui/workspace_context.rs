@@ -1,10 +1,25 @@
-use std::{str::FromStr, vec};
+use std::{mem, vec};
 
+use ncollide2d::na::Affine2;
 use result_or_err::ResultOrErr;
-use serde::{Deserialize, Serialize};
 
 use crate::{
-	common::{Bounds, Number, Vector}, io::ipe::IpeExporter, model::{ConnectionKind, ConnectionOrientation, Data, Settings, SizeId, Vertex, VertexId}, render::{RenderTarget, Stage}, ui::{CrossRenderer, GridRenderer}
+	common::{Bounds, Number, Vector},
+	io::{
+		dto::{DataDto, Document, Metadata, TextMode},
+		graph::{Graph, GraphFormat, GraphImporter, InitialLayout},
+		ipe::{IpeExporter, IpeImporter},
+		svg::SvgExporter,
+		tikz::TikzExporter,
+		LoadError,
+	},
+	model::{
+		edge, edges_are_adjacent, Classes, Conflict, ConflictStatus, ConnectionKind, ConnectionOrientation, Data,
+		Edge, Edit, History, IntersectionConstraint, Settings, SizeId, Tolerance, Vertex, VertexId,
+	},
+	render::{RenderTarget, Stage},
+	solver::{AssignmentOptions, AssignmentResult, AssignmentSolver, LayoutOptimizer, LayoutOptions},
+	ui::{CrossRenderer, DocumentFile, GridRenderer},
 };
 
 #[derive(Copy, Clone)]
@@ -28,8 +43,85 @@ pub enum Selection {
 	Area(Vec<VertexId>),
 }
 
+/// Identifies a tab and the state of its drawing when it is written (see [WorkspaceContext::mark_saved_as]).
+#[derive(Copy, Clone)]
+pub struct SaveToken {
+	tab: u64,
+	version: (u64, u64),
+}
+
+/// A drawing open in an inactive tab, with everything that belongs to it.
+///
+/// The drawing of the active tab lives in the fields of the [WorkspaceContext] instead.
+struct Tab {
+	/// Stays the same while the tab is open (unlike its index).
+	id: u64,
+	data: Data,
+	history: History,
+	changes: u64,
+	saved: (u64, u64),
+	graphs: Vec<Graph>,
+	graph_index: usize,
+	metadata: Metadata,
+	selection: Option<Selection>,
+	pose: Affine2<Number>,
+	file: Option<DocumentFile>,
+}
+impl Tab {
+	fn new(id: u64, data: Data) -> Self {
+		Self {
+			id,
+			data,
+			history: History::default(),
+			changes: 0,
+			saved: (0, 0),
+			graphs: Vec::new(),
+			graph_index: 0,
+			metadata: Metadata::default(),
+			selection: None,
+			pose: Affine2::identity(),
+			file: None,
+		}
+	}
+}
+
+/// The shown name of a drawing: its file name, its title or "untitled".
+fn tab_name(file: &Option<DocumentFile>, metadata: &Metadata) -> String {
+	match (file, metadata.title.trim()) {
+		(Some(file), _) => file.name.clone(),
+		(None, "") => "untitled".to_owned(),
+		(None, title) => title.to_owned(),
+	}
+}
+
 pub struct WorkspaceContext<S: Stage<Settings>> {
+	/// The id of the active tab.
+	tab_id: u64,
+	next_tab_id: u64,
 	data: Data,
+	history: History,
+	/// The number of changes that are not recorded in the history (e.g. of the metadata).
+	changes: u64,
+	/// The [Self::version] when the drawing was loaded or saved.
+	saved: (u64, u64),
+	/// The running layout optimization and the revision of the data it started from.
+	layout: Option<(LayoutOptimizer, u64)>,
+	/// The running assignment search and the revision of the data it searches for.
+	assignment: Option<(AssignmentSolver, u64)>,
+	/// The graphs of the last imported graph file (see [Self::load_graphs]).
+	graphs: Vec<Graph>,
+	graph_index: usize,
+	/// How the vertices of imported graphs are placed.
+	pub graph_layout: InitialLayout,
+	/// Describes the drawing (only stored in the JSON format).
+	pub metadata: Metadata,
+	/// The file the drawing was opened from or last saved to.
+	pub file: Option<DocumentFile>,
+	/// All open drawings in tab order, None for the active one (which lives in the fields of the context).
+	tabs: Vec<Option<Tab>>,
+	active_tab: usize,
+	/// The text of the subgraph copied last (shared by all tabs, used if the system clipboard can't be read).
+	clipboard: Option<String>,
 	pub resources: Settings,
 	pub stage: S,
 	pub mode: Mode,
@@ -46,7 +138,22 @@ pub struct WorkspaceContext<S: Stage<Settings>> {
 impl<S: Stage<Settings>> WorkspaceContext<S> {
 	pub fn new(stage: S, resources: Settings) -> Self {
 		Self {
+			tab_id: 0,
+			next_tab_id: 1,
 			data: Data::default(),
+			history: History::default(),
+			changes: 0,
+			saved: (0, 0),
+			layout: None,
+			assignment: None,
+			graphs: Vec::new(),
+			graph_index: 0,
+			graph_layout: InitialLayout::Circle,
+			metadata: Metadata::default(),
+			file: None,
+			tabs: vec![None],
+			active_tab: 0,
+			clipboard: None,
 			resources,
 			stage,
 			mode: Mode::Edit,
@@ -61,25 +168,183 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 		}
 	}
 
-	pub fn conflicts_representation(&self) -> Vec<(String, Vector)> {
-		let arcs = self.data.arcs().collect();
-		let conflicts = self.data.conflicts(arcs);
+	/// Records the state before an edit for undo.
+	fn record(&mut self, edit: Edit) {
+		self.history.record(edit, &self.data);
+	}
+	/// Identifies the state of the drawing and its metadata (see [History::revision]).
+	fn version(&self) -> (u64, u64) {
+		(self.history.revision(), self.changes)
+	}
+	/// Whether the drawing (or its metadata) changed since it was loaded or saved.
+	///
+	/// Undoing back to the saved state makes it unchanged again.
+	pub fn is_dirty(&self) -> bool {
+		self.version() != self.saved
+	}
+	/// Marks changes that are not recorded in the history (e.g. of the metadata).
+	pub fn mark_dirty(&mut self) {
+		self.changes += 1;
+	}
+	/// Marks the drawing as unchanged, to be called once it was written to its file.
+	pub fn mark_saved(&mut self) {
+		self.saved = self.version();
+	}
+	/// Remembers the active tab and the state of its drawing before it is written.
+	pub fn save_token(&self) -> SaveToken {
+		SaveToken { tab: self.tab_id, version: self.version() }
+	}
+	/// Associates the tab of the token with the file it was written to, to be called once the write finished.
+	///
+	/// The tab may not be the active one anymore (or closed). It is only marked as unchanged if it was not
+	/// changed while the write was in flight.
+	pub fn mark_saved_as(&mut self, token: SaveToken, file: DocumentFile) {
+		if token.tab == self.tab_id {
+			if self.version() == token.version {
+				self.saved = token.version;
+			}
+			self.file = Some(file);
+			return;
+		}
+		let Some(tab) = self.tabs.iter_mut().flatten().find(|tab| tab.id == token.tab) else { return };
+		if (tab.history.revision(), tab.changes) == token.version {
+			tab.saved = token.version;
+		}
+		tab.file = Some(file);
+	}
+
+	/// The name of every open drawing and whether it has unsaved changes (in tab order).
+	pub fn tabs(&self) -> Vec<(String, bool)> {
+		let tabs = self.tabs.iter().map(|tab| match tab {
+			Some(tab) => (tab_name(&tab.file, &tab.metadata), (tab.history.revision(), tab.changes) != tab.saved),
+			None => (tab_name(&self.file, &self.metadata), self.is_dirty()),
+		});
+		tabs.collect()
+	}
+	pub fn active_tab(&self) -> usize {
+		self.active_tab
+	}
+	/// Whether the active tab is a new drawing that was not changed yet (so a file can be opened in it).
+	pub fn is_pristine(&self) -> bool {
+		!self.is_dirty() && self.file.is_none()
+	}
+	/// Moves the drawing of the active tab out of the fields of the context.
+	fn take_tab(&mut self) -> Tab {
+		self.layout = None;
+		self.assignment = None;
+		Tab {
+			id: self.tab_id,
+			data: mem::replace(&mut self.data, Data::new(Classes::default())),
+			history: mem::take(&mut self.history),
+			changes: self.changes,
+			saved: self.saved,
+			graphs: mem::take(&mut self.graphs),
+			graph_index: self.graph_index,
+			metadata: mem::take(&mut self.metadata),
+			selection: self.selection.take(),
+			pose: *self.stage.base_pose_mut(),
+			file: self.file.take(),
+		}
+	}
+	/// Makes the drawing the one of the active tab.
+	fn restore_tab(&mut self, tab: Tab) {
+		self.tab_id = tab.id;
+		self.data = tab.data;
+		self.history = tab.history;
+		self.changes = tab.changes;
+		self.saved = tab.saved;
+		self.graphs = tab.graphs;
+		self.graph_index = tab.graph_index;
+		self.metadata = tab.metadata;
+		self.selection = tab.selection;
+		*self.stage.base_pose_mut() = tab.pose;
+		self.file = tab.file;
+		self.rerender();
+	}
+	fn empty_tab(&mut self) -> Tab {
+		self.next_tab_id += 1;
+		Tab::new(self.next_tab_id - 1, Data::new(Classes::default()))
+	}
+	/// Opens an empty drawing in a new tab (after the others) and activates it.
+	pub fn new_tab(&mut self) {
+		let current = self.take_tab();
+		self.tabs[self.active_tab] = Some(current);
+		self.tabs.push(None);
+		self.active_tab = self.tabs.len() - 1;
+		let tab = self.empty_tab();
+		self.restore_tab(tab);
+	}
+	pub fn switch_tab(&mut self, index: usize) -> Result<(), ()> {
+		if index == self.active_tab {
+			return Ok(());
+		}
+		let tab = self.tabs.get_mut(index).ok_or(())?.take().ok_or(())?;
+		let current = self.take_tab();
+		self.tabs[self.active_tab] = Some(current);
+		self.active_tab = index;
+		self.restore_tab(tab);
+		Ok(())
+	}
+	/// Closes the tab (discarding unsaved changes), closing the last one leaves an empty drawing.
+	pub fn close_tab(&mut self, index: usize) -> Result<(), ()> {
+		if index >= self.tabs.len() {
+			return Err(());
+		}
+		if self.tabs.len() == 1 {
+			self.take_tab();
+			let tab = self.empty_tab();
+			self.restore_tab(tab);
+			return Ok(());
+		}
+		if index == self.active_tab {
+			self.switch_tab(if index + 1 < self.tabs.len() { index + 1 } else { index - 1 })?;
+		}
+		self.tabs.remove(index);
+		if self.active_tab > index {
+			self.active_tab -= 1;
+		}
+		Ok(())
+	}
+	pub fn vertex_name(&self, id: VertexId) -> String {
+		self.data.vertex_name(id)
+	}
+
+	pub fn conflicts_representation(&self) -> Vec<(String, Vector, ConflictStatus)> {
+		let conflicts = self.data.conflicts();
 		let conflicts = conflicts
 			.into_iter()
-			.map(|((a, b), (c, d), position)| {
-				let a = self.data.vertices.items.get(a).map(|v| v.label.clone()).flatten().unwrap_or(format!("{}", a));
-				let b = self.data.vertices.items.get(b).map(|v| v.label.clone()).flatten().unwrap_or(format!("{}", b));
-				let c = self.data.vertices.items.get(c).map(|v| v.label.clone()).flatten().unwrap_or(format!("{}", c));
-				let d = self.data.vertices.items.get(d).map(|v| v.label.clone()).flatten().unwrap_or(format!("{}", d));
-				(format!("({}) ({}) - ({}) ({}) at [{:.2}; {:.2}]", a, b, c, d, position.x, position.y), position)
+			.map(|Conflict { first: (a, b), second: (c, d), position, status, contact, certain }| {
+				let (a, b, c, d) = (self.vertex_name(a), self.vertex_name(b), self.vertex_name(c), self.vertex_name(d));
+				let position_text = format!("[{:.2}; {:.2}]", position.x, position.y);
+				let (contact, status_name) = (contact.name(), status.name());
+				let mut text =
+					format!("({}) ({}) - ({}) ({}) {} at {} {}", a, b, c, d, contact, position_text, status_name);
+				if !certain {
+					text.push_str(" (uncertain)");
+				}
+				(text, position, status)
 			})
 			.collect::<Vec<_>>();
 		return conflicts;
 	}
 
+	/// Describes the edge pairs that are required to intersect but don't.
+	pub fn missing_intersections_representation(&self) -> Vec<String> {
+		let conflicts = self.data.conflicts();
+		self
+			.data
+			.missing_intersections(&conflicts)
+			.into_iter()
+			.map(|((a, b), (c, d))| {
+				let (a, b, c, d) = (self.vertex_name(a), self.vertex_name(b), self.vertex_name(c), self.vertex_name(d));
+				format!("({}) ({}) - ({}) ({}) missing", a, b, c, d)
+			})
+			.collect()
+	}
+
 	pub fn adjust_vertex(&mut self, vertex: &VertexId) {
 		if self.resources.snap_to_grid {
-			let Some(vertex) = self.data.vertices.items.get_mut(*vertex) else { return };
+			let Some(vertex) = self.data.vertices.get_mut(*vertex) else { return };
 			let quantized = vertex.position / self.snap_grid_spacing;
 			let snapped = Vector::new(quantized.x.round(), quantized.y.round());
 			vertex.position = self.snap_grid_spacing * snapped;
@@ -101,7 +366,7 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 		let target = &mut self.stage.target_canvas(&self.resources);
 
 		target.reset();
-		self.data.render_to(target);
+		self.data.render_to(target, &self.resources);
 		// match self.selection {
 		// 	None => (),
 		// 	Some(Selection::Vertex(id)) => {
@@ -121,6 +386,7 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 	}
 
 	fn add_vertex(&mut self, vertex: Vertex) -> VertexId {
+		self.record(Edit::AddVertex);
 		let id = self.data.add_vertex(vertex);
 		self.rerender();
 		return id;
@@ -156,6 +422,64 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 		}
 	}
 
+	/// The edge pair of the conflict marker at the given position.
+	pub fn get_conflict_at(&mut self, position: Vector) -> Option<(Edge, Edge)> {
+		let position = self.stage.pose().transform_point(&position.into()).into();
+		let (conflict, distance) = self.data.closest_conflict_to(&position)?;
+
+		if distance <= 6.0 {
+			return Some((edge(conflict.first.0, conflict.first.1), edge(conflict.second.0, conflict.second.1)));
+		} else {
+			return None;
+		}
+	}
+
+	/// The edge pair formed by the selected edge and the edge at the given position (if they differ).
+	pub fn get_constraint_target_at(&mut self, position: Vector) -> Option<(Edge, Edge)> {
+		let Some(Selection::Edge(a, b)) = self.selection else { return None };
+		let (c, d) = self.get_connection_at(position)?;
+		let (first, second) = (edge(a, b), edge(c, d));
+		match self.can_constrain(first, second) {
+			true => Some((first, second)),
+			false => None,
+		}
+	}
+
+	pub fn constraint(&self, a: Edge, b: Edge) -> IntersectionConstraint {
+		self.data.constraint(a, b)
+	}
+	/// Whether the constraint of the edge pair can be set (adjacent edges are governed by the base constraint).
+	pub fn can_constrain(&self, a: Edge, b: Edge) -> bool {
+		a != b && !edges_are_adjacent(&a, &b)
+	}
+	/// Names the edge pair by the names of its vertices.
+	pub fn edge_pair_name(&self, a: Edge, b: Edge) -> String {
+		let names = [a.0, a.1, b.0, b.1].map(|id| self.vertex_name(id));
+		format!("({}) ({}) - ({}) ({})", names[0], names[1], names[2], names[3])
+	}
+
+	pub fn set_constraint(&mut self, a: Edge, b: Edge, constraint: IntersectionConstraint) -> Result<(), ()> {
+		let before = self.data.clone();
+		self.data.set_constraint(a, b, constraint)?;
+		self.history.record(Edit::Constrain, &before);
+		self.rerender();
+		Ok(())
+	}
+
+	/// The epsilon borderline intersections are decided with (see [Tolerance]).
+	pub fn epsilon(&self) -> Number {
+		self.data.tolerance.epsilon
+	}
+	pub fn set_epsilon(&mut self, epsilon: Number) -> Result<(), ()> {
+		let tolerance = Tolerance::new(epsilon)?;
+		if tolerance != self.data.tolerance {
+			self.record(Edit::Tolerance);
+			self.data.tolerance = tolerance;
+			self.rerender();
+		}
+		Ok(())
+	}
+
 	pub fn select_at(&mut self, position: Vector) {
 		self.select_vertex_at(position);
 		if self.selection.is_none() {
@@ -184,14 +508,20 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 
 	pub fn connect_to(&mut self, end: VertexId) {
 		let Some(Selection::Vertex(selection)) = self.selection else { return };
-		self.data.remove_connection(end, selection); // no double connections (by choice)
+		if selection == end {
+			return;
+		}
+		self.record(Edit::Connect);
 		self.add_connection(selection, end);
 	}
 
 	pub fn connect_at(&mut self, end: Vector) {
 		let Some(Selection::Vertex(start)) = self.selection else { return };
 		let Some(end) = self.get_vertex_at(end) else { return };
-		self.data.remove_connection(end, start); // no double connections (by choice)
+		if start == end {
+			return;
+		}
+		self.record(Edit::Connect);
 		self.add_connection(start, end);
 	}
 
@@ -204,8 +534,9 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 			Selection::Edge(a, b) => &vec![*a, *b],
 			Selection::Area(items) => items,
 		};
+		self.record(Edit::Move);
 		for &id in ids {
-			let Some(vertex) = self.data.vertices.items.get_mut(id) else { return };
+			let Some(vertex) = self.data.vertices.get_mut(id) else { return };
 			vertex.position = vertex.position + by;
 		}
 		self.rerender();
@@ -214,13 +545,13 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 	pub fn get_selection_bounds(&self) -> Option<Bounds> {
 		let bounds = match self.selection.as_ref()? {
 			Selection::Vertex(id) => {
-				let vertex = self.data.vertices.items.get(*id)?;
+				let vertex = self.data.vertices.get(*id)?;
 				let position = vertex.position;
 				Bounds::new(position, Vector::zero())
 			},
 			Selection::Edge(a, b) => {
-				let va = self.data.vertices.items.get(*a)?;
-				let vb = self.data.vertices.items.get(*b)?;
+				let va = self.data.vertices.get(*a)?;
+				let vb = self.data.vertices.get(*b)?;
 				let mut bounds = Into::<Bounds>::into(va.position).combined_with(&vb.position.into());
 				if let Some(ConnectionKind::Arc(arc)) = self.data.connection(a, b) {
 					bounds = bounds.combined_with(&arc.bounds());
@@ -230,7 +561,7 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 			Selection::Area(ids) => {
 				let mut bounds: Option<Bounds> = None;
 				for &id in ids {
-					let Some(vertex) = self.data.vertices.items.get(id) else { continue };
+					let Some(vertex) = self.data.vertices.get(id) else { continue };
 					bounds = Bounds::merged(&bounds, &Some(vertex.position.into()))
 				}
 				for (_, _, connection) in self.data.connections_subset(ids) {
@@ -251,6 +582,7 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 
 	pub fn delete_selection(&mut self) {
 		let Some(selection) = self.selection.take() else { return };
+		self.record(Edit::Delete);
 		match selection {
 			Selection::Vertex(v) => {
 				self.data.remove_vertex(v);
@@ -266,27 +598,26 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 		}
 	}
 
+	/// Moves the selected connection to the next or previous class (recorded only if the class changes).
 	pub fn change_selection_class(&mut self, increase: bool) {
-		match self.selection {
-			Some(Selection::Edge(a, b)) => {
-				let mut class = 0;
-				if let Some(edge) = self.data.edge_mut(&a, &b) {
-					class = edge.size;
-				}
-				match increase {
-					true => class = self.data.classes.next(class),
-					false => class = self.data.classes.previous(class),
-				}
-				if let Some(edge) = self.data.edge_mut(&a, &b) {
-					edge.size = class;
-				}
-			},
-			_ => (),
+		let Some(Selection::Edge(a, b)) = self.selection else { return };
+		let Some(size) = self.data.edge(&a, &b).map(|edge| edge.size) else { return };
+		let class = match increase {
+			true => self.data.classes.next(size),
+			false => self.data.classes.previous(size),
+		};
+		if class == size {
+			return;
+		}
+		self.record(Edit::Reclassify);
+		if let Some(edge) = self.data.edge_mut(&a, &b) {
+			edge.size = class;
 		}
 	}
 
 	pub fn duplicate_selection(&mut self) {
 		let Some(selection) = self.selection.take() else { return };
+		self.record(Edit::Duplicate);
 		match selection {
 			Selection::Vertex(v) => {
 				let (start, _) = self.data.duplicate_subgraph(vec![v]);
@@ -304,8 +635,63 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 		self.rerender(); // DESIGN: rerender or not? make this consistent
 	}
 
+	/// The selected vertices with the connections between them in the text format (see [DataDto::to_text]).
+	///
+	/// The text is kept for [Self::paste] as well (so it can be pasted in any tab without the system clipboard).
+	pub fn copy_selection(&mut self) -> Option<String> {
+		let ids = match self.selection.as_ref()? {
+			Selection::Vertex(id) => vec![*id],
+			Selection::Edge(a, b) => vec![(*a).min(*b), (*a).max(*b)],
+			Selection::Area(items) => items.clone(),
+		};
+		let text = DataDto::from(&self.data.subgraph(&ids)).to_text();
+		self.clipboard = Some(text.clone());
+		Some(text)
+	}
+	/// Copies the selection (see [Self::copy_selection]) and deletes it.
+	///
+	/// A selected connection is copied with its vertices, so those are deleted as well.
+	pub fn cut_selection(&mut self) -> Option<String> {
+		let text = self.copy_selection()?;
+		if let Some(Selection::Edge(a, b)) = self.selection {
+			self.selection = Some(Selection::Area(vec![a.min(b), a.max(b)]));
+		}
+		self.delete_selection();
+		self.rerender();
+		Some(text)
+	}
+	/// Inserts a drawing in the text format centered at the given position (on the stage) and selects it.
+	///
+	/// Without text the one copied last is used.
+	/// The vertices are appended after the existing ones, connections get the class with the closest radius
+	/// (see [Data::insert]).
+	pub fn paste(&mut self, text: Option<&str>, at: Vector) -> Result<(), LoadError> {
+		let text = match text {
+			Some(text) => text.to_owned(),
+			None => self.clipboard.clone().ok_or_else(|| LoadError::new("nothing was copied yet"))?,
+		};
+		let pasted: Data = DataDto::from_text(&text, TextMode::Strict)?.data.into();
+		let bounds = pasted.vertices.iter().fold(None, |b, v| Bounds::merged(&b, &Some(v.position.into())));
+		let bounds = bounds.ok_or_else(|| LoadError::new("the copied drawing has no vertices"))?;
+		let at: Vector = self.stage.pose().transform_point(&at.into()).into();
+		let offset = at - (bounds.start() + bounds.end()) / 2.0;
+
+		self.record(Edit::Paste);
+		let (start, count) = self.data.insert(&pasted);
+		// only the pasted vertices are touched, so the conflicts of the others stay cached
+		for id in start..start + count {
+			self.data.vertices[id].position = self.data.vertices[id].position + offset;
+		}
+		self.selection = Some(Selection::Area((start..start + count).collect()));
+		self.rerender();
+		Ok(())
+	}
+
 	pub fn label_selection(&mut self) {
 		let Some(selection) = &self.selection else { return };
+		if let Selection::Vertex(_) | Selection::Area(_) = selection {
+			self.record(Edit::Label);
+		}
 		match selection {
 			Selection::Vertex(v) => self.data.label_vertex(*v, self.label.clone()),
 			Selection::Edge(a, b) => (),
@@ -323,6 +709,9 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 		mut transformation: impl FnMut(ConnectionOrientation) -> ConnectionOrientation,
 	) {
 		let Some(selection) = &self.selection else { return };
+		if let Selection::Edge(_, _) | Selection::Area(_) = selection {
+			self.record(Edit::Reorient);
+		}
 		match selection {
 			Selection::Vertex(v) => (),
 			Selection::Edge(a, b) => {
@@ -343,6 +732,151 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 		self.rerender();
 	}
 
+	/// Starts searching orientations and classes for all connections so that the intersection constraints are
+	/// satisfied.
+	///
+	/// The search is advanced by [Self::step_assignment] (so the UI stays responsive and can show the progress).
+	pub fn start_assignment(&mut self, options: AssignmentOptions) {
+		self.assignment = Some((AssignmentSolver::new(&self.data, options), self.history.revision()));
+	}
+	/// Advances the assignment search by the given number of steps.
+	///
+	/// Once the search is finished the best assignment found is applied (as one undoable step).
+	/// Returns a human readable report of the progress or the outcome, None if no search is running.
+	pub fn step_assignment(&mut self, steps: usize) -> Option<Vec<String>> {
+		let (solver, revision) = self.assignment.as_mut()?;
+		if *revision != self.history.revision() {
+			// the drawing was changed in the meantime, so the result wouldn't fit anymore
+			self.assignment = None;
+			return Some(vec!["search canceled (the drawing was changed)".to_owned()]);
+		}
+		let Some(result) = solver.step(steps) else {
+			let progress = match solver.witness_progress() {
+				None => format!("searching: {} steps", solver.steps()),
+				Some((tested, count)) => format!("no solution exists, narrowing down the cause: {}/{}", tested, count),
+			};
+			return Some(vec![progress]);
+		};
+		self.assignment = None;
+
+		let (best, mut report) = match result {
+			AssignmentResult::Solved(assignment) => (Some(assignment), vec!["solution found".to_owned()]),
+			AssignmentResult::Infeasible { best, witness } => {
+				let witness = witness
+					.into_iter()
+					.map(|(a, b)| format!("({}) ({})", self.vertex_name(a), self.vertex_name(b)))
+					.collect::<Vec<_>>();
+				let report = vec![
+					"no solution exists".to_owned(),
+					format!("these connections can't be drawn together: {}", witness.join(", ")),
+				];
+				(best, report)
+			},
+			AssignmentResult::Undecided { best } => (best, vec!["search aborted (step limit reached)".to_owned()]),
+		};
+		if let Some(assignment) = best {
+			if assignment.violations > 0 {
+				report.push(format!("applied best assignment with {} violated constraints", assignment.violations));
+			}
+			self.record(Edit::Solve);
+			assignment.apply_to(&mut self.data);
+			self.rerender();
+		}
+		Some(report)
+	}
+	pub fn stop_assignment(&mut self) {
+		self.assignment = None;
+	}
+
+	/// Pins the selected vertices (or unpins them if all of them are pinned already).
+	///
+	/// Pinned vertices are not moved by the layout optimizer.
+	pub fn toggle_pin_selection(&mut self) {
+		let ids = match &self.selection {
+			None => return,
+			Some(Selection::Vertex(id)) => vec![*id],
+			Some(Selection::Edge(a, b)) => vec![*a, *b],
+			Some(Selection::Area(items)) => items.clone(),
+		};
+		let pinned = ids.iter().all(|id| self.data.vertices.get(*id).is_some_and(|v| v.pinned));
+		self.record(Edit::Pin);
+		for id in ids {
+			if let Some(vertex) = self.data.vertices.get_mut(id) {
+				vertex.pinned = !pinned;
+			}
+		}
+		self.rerender();
+	}
+
+	/// Starts moving the unpinned vertices to reduce the number of conflicts.
+	///
+	/// The optimization is advanced by [Self::step_layout_optimization] (so the UI can show the progress).
+	/// The whole optimization is one undoable step.
+	pub fn start_layout_optimization(&mut self, options: LayoutOptions) {
+		self.record(Edit::Layout);
+		self.history.seal();
+		self.selection = None;
+		self.layout = Some((LayoutOptimizer::new(&self.data, options), self.history.revision()));
+	}
+	/// Advances the layout optimization by the given number of steps and applies the best layout found so far.
+	///
+	/// Any other edit (e.g. dragging a vertex) stops the optimization, so it doesn't overwrite the edit.
+	///
+	/// Returns a human readable progress report or None if no optimization is running.
+	pub fn step_layout_optimization(&mut self, steps: usize) -> Option<String> {
+		let (optimizer, revision) = self.layout.as_mut()?;
+		if *revision != self.history.revision() {
+			self.layout = None;
+			return Some("stopped (the drawing was changed)".to_owned());
+		}
+
+		let finished = optimizer.step(steps);
+		// only the moved vertices are written, so the conflicts of the others stay cached
+		for (id, best) in optimizer.best().vertices.iter().enumerate() {
+			if self.data.vertices.get(id).is_some_and(|vertex| vertex.position != best.position) {
+				self.data.vertices[id].position = best.position;
+			}
+		}
+		let cost = optimizer.best_cost();
+		let report = format!(
+			"{} {}: {} unreachable, {} violations, {} crossings",
+			if finished { "finished after" } else { "step" },
+			optimizer.iteration(),
+			cost.unreachable,
+			cost.violations,
+			cost.crossings,
+		);
+		if finished {
+			self.layout = None;
+		}
+		self.rerender();
+		Some(report)
+	}
+	pub fn stop_layout_optimization(&mut self) {
+		self.layout = None;
+	}
+
+	/// Ends the current undo step, so the next continuous edit (e.g. drag) starts a new one.
+	pub fn seal_history(&mut self) {
+		self.history.seal();
+	}
+
+	pub fn undo(&mut self) {
+		if self.history.undo(&mut self.data).is_ok() {
+			self.layout = None;
+			self.selection = None;
+			self.rerender();
+		}
+	}
+
+	pub fn redo(&mut self) {
+		if self.history.redo(&mut self.data).is_ok() {
+			self.layout = None;
+			self.selection = None;
+			self.rerender();
+		}
+	}
+
 	pub fn render_selection_to(&self, target: &mut impl RenderTarget) {
 		let Some(selection) = &self.selection else { return };
 		let ids = match selection {
@@ -351,38 +885,152 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 			Selection::Area(items) => items,
 		};
 
-		self.data.render_subset_to(target, ids);
+		self.data.render_subset_to(target, ids, &self.resources);
+	}
+
+	/// The drawing in the JSON format (which also updates the timestamps of the metadata).
+	pub fn serialize(&mut self) -> Result<Vec<u8>, ()> {
+		let now = String::from(js_sys::Date::new_0().to_iso_string());
+		self.metadata.created.get_or_insert(now.clone());
+		self.metadata.modified = Some(now);
+		Document::new((&self.data).into(), self.metadata.clone()).to_json()
 	}
 
-	pub fn serialize(&self) -> Result<Vec<u8>, ()> {
-		let mut buffer = Vec::new();
-		let mut serializer = serde_json::Serializer::new(&mut buffer);
-		self.data.serialize(&mut serializer).or_err(())?;
-		Ok(buffer)
+	/// Loads a file in any of the supported formats (JSON, text, Ipe or one of the [GraphFormat]s).
+	///
+	/// Returns notes about the parts of the file that couldn't be imported.
+	/// (Text files with invalid lines are only accepted if the file is in none of the other formats.)
+	pub fn open(&mut self, data: &[u8]) -> Result<Vec<LoadError>, LoadError> {
+		if data.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{') {
+			return self.load(data).map(|()| Vec::new());
+		}
+		if let Ok(diagnostics) = self.load_text(data, TextMode::Strict) {
+			return Ok(diagnostics);
+		}
+		if let Ok(notes) = self.load_ipe(data) {
+			return Ok(notes.into_iter().map(LoadError::new).collect());
+		}
+		match self.load_graphs(data) {
+			Ok(1) => return Ok(Vec::new()),
+			Ok(count) => {
+				let note = format!("the file contains {} graphs, step through them in the graphs view", count);
+				return Ok(vec![LoadError::new(note)]);
+			},
+			Err(()) => (),
+		}
+		self.load_text(data, TextMode::Lenient).map_err(|_| {
+			LoadError::new(
+				"the file is in none of the supported formats (JSON, text, Ipe, graph6, edge list, DOT, GraphML)",
+			)
+		})
 	}
 
-	pub fn load(&mut self, data: &[u8]) -> Result<(), ()> {
-		let mut deserializer = serde_json::Deserializer::from_slice(data);
-		self.data = Data::deserialize(&mut deserializer).or_err(())?;
+	pub fn load(&mut self, data: &[u8]) -> Result<(), LoadError> {
+		let document = Document::from_json(data)?;
+		self.data = document.drawing.into();
+		self.metadata = document.metadata;
+		self.history.clear();
+		self.mark_saved();
+		self.layout = None;
+		self.graphs.clear();
 		Ok(())
 	}
 
-	pub fn load_text(&mut self, data: &[u8]) -> Result<(), ()> {
+	/// Loads a file in the text format, returns why lines were skipped (see [TextMode]).
+	pub fn load_text(&mut self, data: &[u8], mode: TextMode) -> Result<Vec<LoadError>, LoadError> {
+		let text = std::str::from_utf8(data).map_err(|_| LoadError::new("the file is not valid UTF-8"))?;
+		let import = DataDto::from_text(text, mode)?;
+		self.data = import.data.into();
+		self.metadata = Metadata::default();
+		self.history.clear();
+		self.mark_saved();
+		self.layout = None;
+		self.graphs.clear();
+		Ok(import.diagnostics)
+	}
+
+	/// Loads an .ipe file, returns notes about the objects that couldn't be imported.
+	pub fn load_ipe(&mut self, data: &[u8]) -> Result<Vec<String>, ()> {
+		let text = String::from_utf8(data.to_vec()).or_err(())?;
+		let importer = IpeImporter { classes: self.data.classes.clone(), ..Default::default() };
+		let import = importer.import(&text)?;
+		self.data = import.data.into();
+		self.metadata = Metadata::default();
+		self.history.clear();
+		self.mark_saved();
+		self.layout = None;
+		self.graphs.clear();
+		Ok(import.notes)
+	}
+
+	/// Loads a file in one of the [GraphFormat]s and shows its first graph.
+	///
+	/// Returns the number of graphs in the file (see [Self::show_graph] to step through them).
+	pub fn load_graphs(&mut self, data: &[u8]) -> Result<usize, ()> {
 		let text = String::from_utf8(data.to_vec()).or_err(())?;
-		self.data = Data::from_str(&text)?;
+		let graphs = GraphFormat::detect(&text).ok_or(())?.read(&text).or_err(())?;
+		if graphs.is_empty() {
+			return Err(());
+		}
+		self.graphs = graphs;
+		self.show_graph(0)?;
+		Ok(self.graphs.len())
+	}
+	/// Replaces the drawing with the graph of the last imported graph file with the given index.
+	///
+	/// New connections get the currently chosen orientation and class.
+	pub fn show_graph(&mut self, index: usize) -> Result<(), ()> {
+		let graph = self.graphs.get(index).ok_or(())?;
+		let importer = GraphImporter {
+			classes: self.data.classes.clone(),
+			layout: self.graph_layout,
+			orientation: self.orientation,
+			class: self.size,
+			..Default::default()
+		};
+		self.data = importer.import(graph).into();
+		self.metadata = Metadata::default();
+		self.graph_index = index;
+		self.history.clear();
+		self.mark_saved();
+		self.layout = None;
+		self.selection = None;
+		self.rerender();
 		Ok(())
 	}
+	/// The index of the shown graph and the number of graphs in the last imported graph file.
+	pub fn graph_position(&self) -> (usize, usize) {
+		(self.graph_index, self.graphs.len())
+	}
 
 	pub fn to_text(&self) -> Vec<u8> {
-		self.data.to_string().as_bytes().to_vec()
+		DataDto::from(&self.data).to_text().as_bytes().to_vec()
 	}
 
-	pub fn export_ipe(&self) -> Vec<u8> {
-		let mut exporter = IpeExporter::default();
-		self.data.render_to(&mut exporter);
+	/// Returns the file and the colors that had to be replaced (see [IpeExporter::unknown_colors]).
+	pub fn export_ipe(&self) -> (Vec<u8>, Vec<String>) {
+		let mut exporter = IpeExporter::new(&self.data.classes);
+		self.data.render_to(&mut exporter, &self.resources);
+		(exporter.to_string().as_bytes().to_vec(), exporter.unknown_colors().cloned().collect())
+	}
+
+	pub fn export_svg(&self) -> Vec<u8> {
+		let mut exporter = SvgExporter::new(self.resources.show_labels, self.resources.show_conflicts);
+		self.data.render_to(&mut exporter, &self.resources);
 		exporter.to_string().as_bytes().to_vec()
 	}
 
+	/// Returns the file and the colors that had to be replaced (see [TikzExporter::unknown_colors]).
+	pub fn export_tikz(&self) -> (Vec<u8>, Vec<String>) {
+		let mut exporter = TikzExporter::new(&self.data, self.resources.show_labels, self.resources.show_conflicts);
+		self.data.render_to(&mut exporter, &self.resources);
+		(exporter.to_string().as_bytes().to_vec(), exporter.unknown_colors().cloned().collect())
+	}
+
+	pub fn export_graph(&self, format: GraphFormat) -> Vec<u8> {
+		format.write(&Graph::from(&DataDto::from(&self.data))).as_bytes().to_vec()
+	}
+
 	pub fn draw_selection_aid(&mut self, from: Vector, to: Vector) {
 		if self.selection.is_some() {
 			return;
@@ -394,13 +1042,18 @@ impl<S: Stage<Settings>> WorkspaceContext<S> {
 	}
 	pub fn draw_connection_aid(&mut self, to: Vector) {
 		let Some(Selection::Vertex(id)) = &self.selection else { return };
-		let Some(vertex) = self.data.vertices.items.get(*id) else { return };
+		let Some(vertex) = self.data.vertices.get(*id) else { return };
 		let from = vertex.position;
 
 		let from: Vector = self.stage.pose().inverse_transform_point(&from.into()).into();
 		let mut target = self.stage.target_hover(&self.resources);
 		target.draw_aid_line(from, to);
 	}
+	pub fn draw_constraint_aid(&mut self, from: Vector, to: Vector) {
+		let Some(Selection::Edge(_, _)) = &self.selection else { return };
+		let mut target = self.stage.target_hover(&self.resources);
+		target.draw_aid_line(from, to);
+	}
 	pub fn reset_aids(&mut self) {
 		let mut target = self.stage.target_hover(&self.resources);
 		target.reset();
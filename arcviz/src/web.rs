@@ -0,0 +1,29 @@
+use js_sys::Function;
+use webbit::Component;
+use wasm_bindgen::prelude::*;
+use web_sys::window;
+
+use webbit::io::FileIOHandler;
+
+use crate::ui::{App, DocumentIO};
+
+#[wasm_bindgen]
+pub struct Arcviz {
+	app: Component<App>,
+}
+#[wasm_bindgen]
+impl Arcviz {
+	/// `read` and `write` are the file io of the host (see [FileIOHandler]),
+	/// `pick` and `write_to` allow to save to the same file repeatedly (see [DocumentIO]).
+	#[wasm_bindgen(constructor)]
+	pub fn new(read: Function, write: Function, pick: Function, write_to: Function) -> Self {
+		let document_io = DocumentIO::new(read.clone(), pick, write_to);
+		Self { app: Component::make(App::new(FileIOHandler::new(read, write), document_io)) }
+	}
+	#[wasm_bindgen]
+	pub fn mount(&self) {
+		let doc = window().unwrap().document().unwrap();
+		let body = doc.body().unwrap();
+		self.app.mount_in(&body);
+	}
+}
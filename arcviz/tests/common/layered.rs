@@ -0,0 +1,98 @@
+use arcviz::model::{Connection, VertexId};
+
+/// The connection matrix as it was stored before [arcviz::model::Connections] became sparse adjacency lists.
+///
+/// It is kept as a reference: the tests check that the sparse storage gives the same results
+/// and the benchmarks compare their speed.
+///
+/// The matrix is split into layers.
+/// This is because the number of layers corresponds to the number of vertices.
+/// And this way adding/removing a vertex corresponds to just adding/removing a layer.
+///
+/// row/column index:   in-layer index:
+/// 0 | 0 | 0 | ...     0 | 2 | 4 | ...
+/// --/   |   |         --/   |   |
+/// 0   1 | 1           0   1 | 3
+/// ------/             ------/
+/// 0   1   2           0   1   2
+///
+/// global index = in_layer_index + layer_id^2
+#[derive(Clone)]
+pub struct LayeredConnections {
+	items: Vec<Option<Connection>>,
+	size: VertexId,
+}
+
+impl LayeredConnections {
+	pub fn new(size: VertexId) -> Self {
+		Self { items: vec![None; size.pow(2)], size }
+	}
+	fn yx_to_global(y: VertexId, x: VertexId) -> usize {
+		let (layer_id, in_layer_index) = match (y, x) {
+			// y: start; x: end
+			(s, e) if e > s => (e, 2 * e - s),
+			(s, e) => (s, e),
+		};
+		let layer_offset = layer_id.pow(2);
+		layer_offset + in_layer_index
+	}
+
+	/// Gets the specified connection entry if the entry exists.
+	pub fn entry_mut(
+		&mut self,
+		start_vertex: VertexId,
+		end_vertex: VertexId,
+	) -> Result<&mut Option<Connection>, ()> {
+		let global_index = Self::yx_to_global(start_vertex, end_vertex);
+		self.items.get_mut(global_index).ok_or(())
+	}
+	/// Gets the specified connection if the connection exists.
+	pub fn get(&self, start_vertex: VertexId, end_vertex: VertexId) -> Option<&Connection> {
+		self.items.get(Self::yx_to_global(start_vertex, end_vertex))?.as_ref()
+	}
+	/// Adds or removes rows and columns at the end to meet the specified size.
+	pub fn resize(&mut self, size: VertexId) {
+		self.size = size;
+		let target_length = self.size.pow(2);
+		self.items.resize(target_length, None);
+	}
+	/// Removes the row and column that contains the specified vertex.
+	/// Thus reducing the size of the connection matrix by 1.
+	pub fn shrink_by_vertex(&mut self, vertex: VertexId) -> Result<(), ()> {
+		if vertex >= self.size {
+			return Err(());
+		}
+		for layer in (vertex + 1..self.size).rev() {
+			let layer_offset = layer.pow(2);
+			self.items.remove(layer_offset + 2 * layer - vertex); // the row
+			self.items.remove(layer_offset + vertex); // the column
+		}
+		let layer_offset = vertex.pow(2);
+		self.items.drain(layer_offset..layer_offset + 2 * vertex + 1);
+		self.size -= 1;
+		Ok(())
+	}
+
+	pub fn foreach(&self, mut action: impl FnMut(VertexId, VertexId, &Connection)) {
+		let mut layer_id = 0;
+		let mut in_layer_index = 0;
+		for connection in self.items.iter() {
+			let layer_size = 2 * layer_id + 1;
+			let (start, end) = match in_layer_index {
+				i if i <= layer_id => (layer_id, i),
+				i => (layer_size - i - 1, layer_id),
+			};
+
+			in_layer_index += 1;
+			if in_layer_index >= layer_size {
+				layer_id += 1;
+				in_layer_index = 0;
+			}
+
+			let Some(connection) = connection else {
+				continue;
+			};
+			action(start, end, connection);
+		}
+	}
+}
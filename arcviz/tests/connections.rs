@@ -0,0 +1,105 @@
+//! The sparse connection storage against the layered matrix it replaced.
+
+mod common;
+
+use arcviz::{
+	common::{Vector, XorShift},
+	io::dto::{ConnectionDto, DataDto},
+	model::{Classes, Connection, ConnectionOrientation, Data, Vertex, VertexId},
+};
+use common::layered::LayeredConnections;
+
+const ORIENTATIONS: [ConnectionOrientation; 4] = [
+	ConnectionOrientation::InnerRight,
+	ConnectionOrientation::InnerLeft,
+	ConnectionOrientation::OuterRight,
+	ConnectionOrientation::OuterLeft,
+];
+
+/// The connections as the old storage serialized them (see [DataDto::from]).
+fn serialized_connections(connections: &LayeredConnections) -> Vec<(VertexId, VertexId, ConnectionDto)> {
+	let mut result = Vec::new();
+	connections.foreach(|a, b, connection| match a <= b {
+		true => result.push((a, b, ConnectionDto::from(connection))),
+		false => {
+			let orientation = connection.orientation.flipped().into();
+			result.push((b, a, ConnectionDto { orientation, size: connection.size }));
+		},
+	});
+	result.sort_by_key(|&(a, b, _)| (a, b));
+	result
+}
+
+/// Connects the vertices like [Data::add_connection] did with the old storage.
+fn connect(
+	layered: &mut LayeredConnections,
+	a: VertexId,
+	b: VertexId,
+	connection: Connection,
+) -> Result<(), ()> {
+	if a != b {
+		*layered.entry_mut(b, a)? = None;
+	}
+	*layered.entry_mut(a, b)? = Some(connection);
+	Ok(())
+}
+
+/// Applies the same random edits to a drawing and to the old storage.
+fn edit(seed: u64, steps: usize) -> (Data, LayeredConnections) {
+	let mut random = XorShift::new(seed);
+	let mut data = Data::new(Classes::default());
+	let mut layered = LayeredConnections::new(0);
+	for _ in 0..steps {
+		let count = data.vertices.len();
+		let (a, b) = (random.below(count + 1), random.below(count + 1)); // sometimes out of range
+		match random.below(10) {
+			0 | 1 => {
+				let position = Vector::new(random.unit() * 500.0, random.unit() * 500.0);
+				data.add_vertex(Vertex::new(position));
+				layered.resize(data.vertices.len());
+			},
+			2..=6 => {
+				let orientation = ORIENTATIONS[random.below(ORIENTATIONS.len())];
+				let size = random.below(Classes::default().len());
+				let result = data.add_connection(a, b, orientation, size);
+				let expected = connect(&mut layered, a, b, Connection::new(orientation, size));
+				assert_eq!(result, expected, "adding ({}, {})", a, b);
+			},
+			7 | 8 => {
+				let result = data.remove_connection(a, b);
+				let expected = layered.entry_mut(a, b).map(|entry| *entry = None);
+				assert_eq!(result, expected, "removing ({}, {})", a, b);
+			},
+			_ => {
+				let result = data.remove_vertex(a);
+				assert_eq!(result, layered.shrink_by_vertex(a), "removing {}", a);
+			},
+		}
+	}
+	(data, layered)
+}
+
+#[test]
+fn connections_match_the_layered_storage() {
+	for seed in 1..20 {
+		let (data, layered) = edit(seed, 400);
+		let count = data.vertices.len();
+		for start in 0..count {
+			for end in 0..count {
+				assert_eq!(data.connection(&start, &end).is_some(), layered.get(start, end).is_some());
+			}
+		}
+	}
+}
+
+#[test]
+fn serialized_output_is_the_same_as_with_the_layered_storage() {
+	for seed in 1..20 {
+		let (data, layered) = edit(seed, 400);
+		let after = DataDto::from(&data);
+		let before = DataDto { connections: serialized_connections(&layered), ..after.clone() };
+		assert!(!after.connections.is_empty());
+		assert_eq!(serde_json::to_string(&before).unwrap(), serde_json::to_string(&after).unwrap());
+		assert_eq!(before.to_text(), after.to_text());
+	}
+}
@@ -36,7 +36,20 @@ function init () {
 		if (!filePaths[0]) return { data: null };
 		try {
 			const file = await fsPromises.readFile(filePaths[0]);
-			return { data: file };
+			return { data: file, path: filePaths[0], name: path.basename(filePaths[0]) };
+		} catch (err) {
+			return { error: err };
+		}
+	});
+	ipcMain.handle('pick', async (event, suggestedName) => {
+		const { canceled, filePath } = await dialog.showSaveDialog({ defaultPath: suggestedName });
+		if (canceled || !filePath) return { canceled: true };
+		return { path: filePath, name: path.basename(filePath) };
+	});
+	ipcMain.handle('write', async (event, filePath, data) => {
+		try {
+			await fsPromises.writeFile(filePath, data);
+			return {};
 		} catch (err) {
 			return { error: err };
 		}
@@ -25,6 +25,8 @@ contextBridge.exposeInMainWorld( // provide file io in main application
 			ipcRenderer.invoke('open').then(result => {
 				let error = null;
 				let data = null;
+				let path = null;
+				let name = null;
 				if (result.canceled) {
 					console.log('open: canceled');
 				} else if (result.error) {
@@ -32,16 +34,51 @@ contextBridge.exposeInMainWorld( // provide file io in main application
 					console.log('open: error: ' + result.error);
 				} else if (result.data) {
 					data = result.data;
+					path = result.path;
+					name = result.name;
 					console.log('open: ok');
 				} else {
 					console.log('open: empty');
 				}
 				try {
-					callback(error, data);
+					callback(error, data, path, name);
 				} catch {
 					console.log('open: discarded'); // callback might be invalid if user is no longer interested
 				}
 			});
 		},
+		pick: (suggestedName, callback) => {
+			ipcRenderer.invoke('pick', suggestedName).then(result => {
+				let path = null;
+				let name = null;
+				if (result.canceled) {
+					console.log('pick: canceled');
+				} else {
+					path = result.path;
+					name = result.name;
+				}
+				try {
+					callback(path, name);
+				} catch {
+					console.log('pick: discarded'); // callback might be invalid if user is no longer interested
+				}
+			});
+		},
+		write: (path, data, callback) => {
+			ipcRenderer.invoke('write', path, data).then(result => {
+				let success = false;
+				if (result.error) {
+					console.log('write: error: ' + result.error);
+				} else {
+					success = true;
+					console.log('write: ok');
+				}
+				try {
+					callback(success);
+				} catch {
+					console.log('write: discarded'); // callback might be invalid if user is no longer interested
+				}
+			});
+		},
 	}
 );
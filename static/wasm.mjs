@@ -34,9 +34,9 @@ export async function startWebAssembly() {
 				}
 			},
 			open: async (callback) => {
-				function call_callback(error, data) {
+				function call_callback(error, data, handle, name) {
 					try {
-						callback(error, data);
+						callback(error, data, handle, name);
 					} catch {
 						console.log('open: discarded'); // callback might be invalid if user is no longer interested
 					}
@@ -48,7 +48,7 @@ export async function startWebAssembly() {
 						try {
 							let file = await fileHandle.getFile();
 							let reader = new FileReader();
-							reader.onloadend = () => call_callback(null, new Uint8Array(reader.result));
+							reader.onloadend = () => call_callback(null, new Uint8Array(reader.result), fileHandle, file.name);
 							reader.readAsArrayBuffer(file);
 						} catch (err) {
 							console.log('open: error: ' + err);
@@ -63,9 +63,43 @@ export async function startWebAssembly() {
 					call_callback(null, null);
 				}
 			},
+			pick: async (suggestedName, callback) => {
+				function call_callback(handle, name) {
+					try {
+						callback(handle, name);
+					} catch {
+						console.log('pick: discarded'); // callback might be invalid if user is no longer interested
+					}
+				}
+				try {
+					let fileHandle = await window.showSaveFilePicker({ suggestedName });
+					call_callback(fileHandle, fileHandle.name);
+				} catch {
+					console.log('pick: canceled');
+					call_callback(null, null);
+				}
+			},
+			write: async (fileHandle, data, callback) => {
+				function call_callback(success) {
+					try {
+						callback(success);
+					} catch {
+						console.log('write: discarded'); // callback might be invalid if user is no longer interested
+					}
+				}
+				try {
+					let stream = await fileHandle.createWritable();
+					await stream.write(data.buffer);
+					await stream.close();
+					call_callback(true);
+				} catch (err) {
+					console.log('write: error: ' + err);
+					call_callback(false);
+				}
+			},
 		};
 	}
 
-	arcviz = new Arcviz(io.open, io.save);
+	arcviz = new Arcviz(io.open, io.save, io.pick, io.write);
 	arcviz.mount();
 }